@@ -1,60 +1,394 @@
-// TODO: this is ugly and WIP
+// JSON text components, see https://wiki.vg/Chat
 
 use crate::error_type::ErrorType;
 
 use core::convert::TryInto;
 
-use serde::{Deserialize, Serialize};
+use serde::de;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Event {
-    action: String,
-    value: String,
+// What a component shows, its children come after it
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum ChatContent {
+    Text {
+        text: String,
+    },
+    // The client looks the key up in its language file, %s in there are filled in with the arguments
+    Translation {
+        translate: String,
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        with: Vec<Chat>,
+    },
+    // Whatever key the player bound to something, like key.jump
+    Keybind {
+        keybind: String,
+    },
+    Score {
+        score: Score,
+    },
+    // Names of the entities the selector finds, servers resolve these before sending
+    Selector {
+        selector: String,
+    },
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-pub struct Chat {
-    text: String,
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Score {
+    pub name: String,
+    pub objective: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    bold: Option<bool>,
+    pub value: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", content = "value", rename_all = "snake_case")]
+pub enum ClickEvent {
+    OpenUrl(String),
+    RunCommand(String),
+    SuggestCommand(String),
+    // Only in books
+    ChangePage(String),
+    CopyToClipboard(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "action", content = "contents")]
+pub enum HoverEvent {
+    #[serde(rename = "show_text")]
+    Text(Box<Chat>),
+    #[serde(rename = "show_item")]
+    Item(HoverItem),
+    #[serde(rename = "show_entity")]
+    Entity(HoverEntity),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HoverItem {
+    pub id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    italic: Option<bool>,
+    pub count: Option<i32>,
+    // SNBT
     #[serde(skip_serializing_if = "Option::is_none")]
-    underlined: Option<bool>,
+    pub tag: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HoverEntity {
+    #[serde(rename = "type")]
+    pub entity_type: String,
+    pub id: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    strikethrough: Option<bool>,
+    pub name: Option<Box<Chat>>,
+}
+
+impl HoverEvent {
+    pub fn text(text: Chat) -> Self {
+        HoverEvent::Text(Box::new(text))
+    }
+
+    pub fn item(id: &str, count: Option<i32>, tag: Option<String>) -> Self {
+        HoverEvent::Item(HoverItem {
+            id: id.to_string(),
+            count,
+            tag,
+        })
+    }
+
+    pub fn entity(entity_type: &str, uuid: Uuid, name: Option<Chat>) -> Self {
+        HoverEvent::Entity(HoverEntity {
+            entity_type: entity_type.to_string(),
+            id: uuid.to_hyphenated().to_string(),
+            name: name.map(Box::new),
+        })
+    }
+}
+
+// How a component looks, anything that is not set comes from its parent
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Style {
     #[serde(skip_serializing_if = "Option::is_none")]
-    obfuscated: Option<bool>,
+    pub bold: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    color: Option<String>,
+    pub italic: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    insertion: Option<String>,
+    pub underlined: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    click_event: Option<Event>,
+    pub strikethrough: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    hover_event: Option<Event>,
+    pub obfuscated: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    extra: Option<Vec<Chat>>,
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font: Option<String>,
+    // Shift clicking puts this in the chat box
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub insertion: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub click_event: Option<ClickEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hover_event: Option<HoverEvent>,
+}
+
+// Components are built by starting from one of the constructors and chaining the rest, like
+// Chat::new("Click me".to_string()).color("gold".to_string()).click(ClickEvent::RunCommand(...))
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Chat {
+    #[serde(flatten)]
+    pub content: ChatContent,
+    #[serde(flatten)]
+    pub style: Style,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra: Vec<Chat>,
 }
 
 impl Chat {
-    pub fn new(text: String) -> Self {
+    fn from_content(content: ChatContent) -> Self {
         Chat {
-            text,
-            bold: None,
-            italic: None,
-            underlined: None,
-            strikethrough: None,
-            obfuscated: None,
-            color: None,
-            insertion: None,
-            click_event: None,
-            hover_event: None,
-            extra: None,
+            content,
+            style: Style::default(),
+            extra: vec![],
+        }
+    }
+
+    pub fn new(text: String) -> Self {
+        Self::from_content(ChatContent::Text { text })
+    }
+
+    pub fn translate(key: &str, with: Vec<Chat>) -> Self {
+        Self::from_content(ChatContent::Translation {
+            translate: key.to_string(),
+            with,
+        })
+    }
+
+    pub fn keybind(key: &str) -> Self {
+        Self::from_content(ChatContent::Keybind {
+            keybind: key.to_string(),
+        })
+    }
+
+    pub fn score(name: &str, objective: &str, value: Option<String>) -> Self {
+        Self::from_content(ChatContent::Score {
+            score: Score {
+                name: name.to_string(),
+                objective: objective.to_string(),
+                value,
+            },
+        })
+    }
+
+    pub fn selector(selector: &str) -> Self {
+        Self::from_content(ChatContent::Selector {
+            selector: selector.to_string(),
+        })
+    }
+
+    pub fn bold(mut self, bold: bool) -> Self {
+        self.style.bold = Some(bold);
+        self
+    }
+
+    pub fn italic(mut self, italic: bool) -> Self {
+        self.style.italic = Some(italic);
+        self
+    }
+
+    pub fn underlined(mut self, underlined: bool) -> Self {
+        self.style.underlined = Some(underlined);
+        self
+    }
+
+    pub fn strikethrough(mut self, strikethrough: bool) -> Self {
+        self.style.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn obfuscated(mut self, obfuscated: bool) -> Self {
+        self.style.obfuscated = Some(obfuscated);
+        self
+    }
+
+    // A name like red or a hex color like #ff0000
+    pub fn color(mut self, color: String) -> Self {
+        self.style.color = Some(color);
+        self
+    }
+
+    pub fn font(mut self, font: String) -> Self {
+        self.style.font = Some(font);
+        self
+    }
+
+    pub fn insertion(mut self, insertion: String) -> Self {
+        self.style.insertion = Some(insertion);
+        self
+    }
+
+    pub fn click(mut self, click_event: ClickEvent) -> Self {
+        self.style.click_event = Some(click_event);
+        self
+    }
+
+    pub fn hover(mut self, hover_event: HoverEvent) -> Self {
+        self.style.hover_event = Some(hover_event);
+        self
+    }
+
+    pub fn append(mut self, extra: Chat) -> Self {
+        self.extra.push(extra);
+        self
+    }
+
+    // Just the text, for places that cannot show colors like the console. We have no language
+    // files, so translations show their key with the arguments filled in where it has %s.
+    pub fn plain_text(&self) -> String {
+        let mut text = match &self.content {
+            ChatContent::Text { text } => text.clone(),
+            ChatContent::Translation { translate, with } => {
+                let arguments: Vec<String> = with.iter().map(|argument| argument.plain_text()).collect();
+                format_translation(translate, &arguments)
+            }
+            ChatContent::Keybind { keybind } => keybind.clone(),
+            ChatContent::Score { score } => score.value.clone().unwrap_or_default(),
+            ChatContent::Selector { selector } => selector.clone(),
+        };
+        for extra in self.extra.iter() {
+            text.push_str(&extra.plain_text());
         }
+        text
+    }
+
+    // Like the client reads components: a string is text, an array is the first component with
+    // the others added to it
+    fn from_value(value: Value) -> Result<Self, String> {
+        match value {
+            Value::String(text) => Ok(Chat::new(text)),
+            Value::Number(number) => Ok(Chat::new(number.to_string())),
+            Value::Bool(value) => Ok(Chat::new(value.to_string())),
+            Value::Array(values) => {
+                let mut components = values
+                    .into_iter()
+                    .map(Chat::from_value)
+                    .collect::<Result<Vec<Chat>, String>>()?;
+                if components.is_empty() {
+                    return Err("Unexpected empty array of components".to_string());
+                }
+                let mut first = components.remove(0);
+                first.extra.append(&mut components);
+                Ok(first)
+            }
+            Value::Object(_) => {
+                let object: ChatObject = serde_json::from_value(value).map_err(|e| e.to_string())?;
+                object.into_chat()
+            }
+            Value::Null => Err("Don't know how to turn null into a component".to_string()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Chat {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Chat::from_value(Value::deserialize(deserializer)?).map_err(de::Error::custom)
     }
 }
 
+// A component as JSON object, which content it has is decided in the same order as vanilla
+#[derive(Deserialize)]
+struct ChatObject {
+    text: Option<String>,
+    translate: Option<String>,
+    #[serde(default)]
+    with: Vec<Chat>,
+    score: Option<Score>,
+    selector: Option<String>,
+    keybind: Option<String>,
+    #[serde(flatten)]
+    style: Style,
+    #[serde(default)]
+    extra: Vec<Chat>,
+}
+
+impl ChatObject {
+    fn into_chat(self) -> Result<Chat, String> {
+        let content = if let Some(text) = self.text {
+            ChatContent::Text { text }
+        } else if let Some(translate) = self.translate {
+            ChatContent::Translation {
+                translate,
+                with: self.with,
+            }
+        } else if let Some(score) = self.score {
+            ChatContent::Score { score }
+        } else if let Some(selector) = self.selector {
+            ChatContent::Selector { selector }
+        } else if let Some(keybind) = self.keybind {
+            ChatContent::Keybind { keybind }
+        } else {
+            return Err("Don't know how to turn this object into a component".to_string());
+        };
+        Ok(Chat {
+            content,
+            style: self.style,
+            extra: self.extra,
+        })
+    }
+}
+
+// Fills in %s and %1$s like Java's String.format, arguments that have no place go at the end
+fn format_translation(format: &str, arguments: &[String]) -> String {
+    let mut result = String::new();
+    let mut next_argument = 0;
+    let mut used = false;
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            result.push('%');
+            continue;
+        }
+        let mut index = String::new();
+        while let Some(digit) = chars.peek().filter(|c| c.is_ascii_digit()) {
+            index.push(*digit);
+            chars.next();
+        }
+        let argument = if !index.is_empty() && chars.peek() == Some(&'$') {
+            chars.next();
+            index.parse::<usize>().ok().and_then(|i| i.checked_sub(1))
+        } else if index.is_empty() {
+            next_argument += 1;
+            Some(next_argument - 1)
+        } else {
+            result.push('%');
+            result.push_str(&index);
+            continue;
+        };
+        if chars.peek() == Some(&'s') {
+            chars.next();
+            used = true;
+            if let Some(argument) = argument.and_then(|i| arguments.get(i)) {
+                result.push_str(argument);
+            }
+        } else {
+            result.push('%');
+        }
+    }
+    if !used && !arguments.is_empty() {
+        result.push_str(&format!(" [{}]", arguments.join(", ")));
+    }
+    result
+}
+
 #[derive(Debug)]
 pub enum ChatMode {
     Enabled,
@@ -91,3 +425,61 @@ impl Into<i8> for ChatPosition {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{Chat, ClickEvent, HoverEvent};
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_serialize() {
+        let name = Chat::new("Alice".to_string())
+            .click(ClickEvent::SuggestCommand("/tell Alice ".to_string()))
+            .hover(HoverEvent::entity("minecraft:player", Uuid::nil(), None));
+        let message = Chat::translate("chat.type.text", vec![name, Chat::new("Hi".to_string())]).bold(true);
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            json!({
+                "translate": "chat.type.text",
+                "with": [
+                    {
+                        "text": "Alice",
+                        "clickEvent": {"action": "suggest_command", "value": "/tell Alice "},
+                        "hoverEvent": {
+                            "action": "show_entity",
+                            "contents": {"type": "minecraft:player", "id": "00000000-0000-0000-0000-000000000000"}
+                        }
+                    },
+                    {"text": "Hi"}
+                ],
+                "bold": true
+            })
+        );
+        assert_eq!(message.plain_text(), "chat.type.text [Alice, Hi]");
+        assert_eq!(Chat::translate("%2$s: %s", vec![Chat::new("a".to_string()), Chat::new("b".to_string())]).plain_text(), "b: a");
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let chat: Chat = serde_json::from_str(
+            r#"["", {"keybind": "key.jump", "color": "red", "hoverEvent": {"action": "show_text", "contents": "Jump"}}, 5]"#,
+        )
+        .unwrap();
+        let expected = Chat::new(String::new())
+            .append(
+                Chat::keybind("key.jump")
+                    .color("red".to_string())
+                    .hover(HoverEvent::text(Chat::new("Jump".to_string()))),
+            )
+            .append(Chat::new("5".to_string()));
+        assert_eq!(chat, expected);
+
+        let chat: Chat = serde_json::from_str(r#"{"score": {"name": "@s", "objective": "kills"}, "extra": [{"selector": "@p"}]}"#).unwrap();
+        assert_eq!(chat, Chat::score("@s", "kills", None).append(Chat::selector("@p")));
+
+        assert!(serde_json::from_str::<Chat>(r#"{"color": "red"}"#).is_err());
+        assert!(serde_json::from_str::<Chat>("[]").is_err());
+    }
+}
@@ -10,10 +10,15 @@ use crate::server::Entity;
 use crate::Server;
 
 use std::convert::TryInto;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 #[derive(Debug, PartialEq)]
-pub struct HandshakingState {}
+pub struct HandshakingState {
+    pub protocol_version: isize,
+    // Where the client connects from, bans can be for an IP address
+    pub address: Option<IpAddr>,
+}
 
 impl ConnectionStateTrait for HandshakingState {
     fn from_state(_state: &ConnectionState) -> Result<Self, ErrorType> {
@@ -75,6 +80,7 @@ impl ConnectionStateTrait for HandshakingState {
                 )))
             }
             ServerboundPacket::Handshaking(packet) => {
+                self.protocol_version = packet.protocol_version;
                 Ok((queue, ConnectionStateTransition::TransitionTo(packet.next_state)))
             }
             x => Err(ErrorType::Fatal(format!(
@@ -3,26 +3,38 @@ use super::ConnectionStateTag;
 use super::ConnectionStateTrait;
 use super::ConnectionStateTransition;
 
+use crate::chat::Chat;
+use crate::console;
 use crate::error_type::ErrorType;
 use crate::packets::clientbound::*;
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ProtocolVersion;
 use crate::packets::serverbound::ServerboundPacket;
 use crate::player::Player;
+use crate::server::ProtocolCheck;
 use crate::world::World;
 use crate::Server;
 
 use std::convert::TryInto;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 #[derive(Debug, PartialEq)]
 pub struct LoginState {
-    pub player_eid: i32,
+    // Set as soon as the player entity exists, so it can be cleaned up if login fails after that
+    pub player_eid: Option<i32>,
+    pub protocol_version: isize,
+    pub address: Option<IpAddr>,
 }
 
 impl ConnectionStateTrait for LoginState {
     fn from_state(prev_state: &ConnectionState) -> Result<Self, ErrorType> {
         match prev_state {
-            ConnectionState::Handshaking(_) => Ok(Self { player_eid: 0 }),
+            ConnectionState::Handshaking(handshaking_state) => Ok(Self {
+                player_eid: None,
+                protocol_version: handshaking_state.protocol_version,
+                address: handshaking_state.address,
+            }),
             x => Err(ErrorType::Fatal(format!(
                 "Cannot go into Login state from {:#?}",
                 x
@@ -42,6 +54,32 @@ impl ConnectionStateTrait for LoginState {
                     .data
                     .lock()
                     .map_err(|e| ErrorType::Fatal(format!("Could not lock server: {:?}", e)))?;
+
+                // Refuse clients that speak a different protocol
+                let disconnect_message = match server_lock.settings.check_protocol(self.protocol_version) {
+                    ProtocolCheck::Accepted => None,
+                    ProtocolCheck::OutdatedClient => Some(format!(
+                        "Outdated client! Please use {}",
+                        ProtocolVersion::supported_range()
+                    )),
+                    ProtocolCheck::OutdatedServer => Some(format!(
+                        "Outdated server! I only support {}",
+                        ProtocolVersion::supported_range()
+                    )),
+                };
+                if let Some(message) = disconnect_message {
+                    console::log(&format!(
+                        "Disconnecting {} (protocol {}): {}",
+                        packet.username, self.protocol_version, message
+                    ));
+                    queue.push(ClientboundPacket::LoginDisconnect(LoginDisconnectPacket {
+                        reason: Chat::new(message),
+                    }));
+                    return Ok((queue, ConnectionStateTransition::TransitionTo(
+                        ConnectionStateTag::Exit,
+                    )));
+                }
+
                 let uuid;
 
                 // Create uuid based on online mode
@@ -51,6 +89,17 @@ impl ConnectionStateTrait for LoginState {
                     uuid = Player::offline_player_uuid(&packet.username);
                 }
 
+                // Banned and not whitelisted players get turned away before they exist
+                if let Some(message) = server_lock.login_refusal(uuid, self.address) {
+                    console::log(&format!("Disconnecting {}: {}", packet.username, message.replace('\n', " ")));
+                    queue.push(ClientboundPacket::LoginDisconnect(LoginDisconnectPacket {
+                        reason: Chat::new(message),
+                    }));
+                    return Ok((queue, ConnectionStateTransition::TransitionTo(
+                        ConnectionStateTag::Exit,
+                    )));
+                }
+
                 // First reply
                 queue.push(ClientboundPacket::LoginSuccess(LoginSuccessPacket {
                     username: packet.username.clone(),
@@ -65,9 +114,10 @@ impl ConnectionStateTrait for LoginState {
                     .ok_or(ErrorType::Fatal("Invalid selected".to_string()))?;
 
                 // Create and load a new player
-                self.player_eid = server_lock.load_or_create_player(&packet.username, uuid)?;
+                let player_eid = server_lock.load_or_create_player(&packet.username, uuid)?;
+                self.player_eid = Some(player_eid);
                 let entity_arc = world 
-                        .get_entity(self.player_eid)?
+                        .get_entity(player_eid)?
                         .ok_or(ErrorType::Fatal(
                             "Newly created player does not exist".to_string(),
                         ))?;
@@ -91,7 +141,7 @@ impl ConnectionStateTrait for LoginState {
                 let is_flat = world.is_flat;
 
                 queue.push(ClientboundPacket::JoinGame(JoinGamePacket {
-                    entity_id: self.player_eid,
+                    entity_id: player_eid,
                     is_hardcore: server_lock.settings.is_hardcore,
                     gamemode,
                     previous_gamemode,
@@ -3,16 +3,23 @@ mod login;
 mod play;
 mod status;
 
+use crate::chat::Chat;
+use crate::console;
 use crate::error_type::ErrorType;
 use crate::packets::clientbound::Clientbound;
 use crate::packets::clientbound::ClientboundPacket;
+use crate::packets::clientbound::DisconnectPacket;
 use crate::packets::packet_reader::PacketReader;
+use crate::packets::protocol::ProtocolVersion;
 use crate::packets::serverbound::ServerboundPacket;
 use crate::Server;
 
+use std::net::IpAddr;
+use std::net::Shutdown;
 use std::net::TcpStream;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::RwLock;
 
 pub use handshaking::HandshakingState;
 pub use login::LoginState;
@@ -83,28 +90,80 @@ impl ConnectionStateTag {
 
 pub struct ClientHandler {
     stream: TcpStream,
+    address: Option<IpAddr>,
     server: Arc<Server>,
     pub state: Mutex<ConnectionState>,
+    protocol: RwLock<ProtocolVersion>,
+    player_eid: RwLock<Option<i32>>,
+    // Other threads also send packets to this client, this keeps them from interleaving
+    write_lock: Mutex<()>,
 }
 
 impl ClientHandler {
     pub fn new(stream: TcpStream, server: Arc<Server>) -> ClientHandler {
+        let address = stream.peer_addr().ok().map(|address| address.ip());
         Self {
             stream,
+            address,
             server,
-            state: Mutex::new(ConnectionState::Handshaking(HandshakingState {})),
+            state: Mutex::new(ConnectionState::Handshaking(HandshakingState {
+                protocol_version: 0,
+                address,
+            })),
+            // Until the handshake tells us otherwise, assume the newest version
+            protocol: RwLock::new(ProtocolVersion::latest()),
+            player_eid: RwLock::new(None),
+            write_lock: Mutex::new(()),
         }
     }
 
+    // The entity id of the player on this connection, once it has reached the Play state
+    pub fn player_eid(&self) -> Option<i32> {
+        *self.player_eid.read().expect("Could not lock player eid")
+    }
+
+    // The player that was created for this connection while it is still logging in
+    pub fn login_player_eid(&self) -> Option<i32> {
+        match &*self.state.lock().expect("Could not lock state") {
+            ConnectionState::Login(login_state) => login_state.player_eid,
+            _ => None,
+        }
+    }
+
+    pub fn address(&self) -> Option<IpAddr> {
+        self.address
+    }
+
+    pub fn protocol(&self) -> ProtocolVersion {
+        *self.protocol.read().expect("Could not lock protocol version")
+    }
+
     pub fn send_packet(&self, packet: ClientboundPacket) -> Result<(), ErrorType> {
-        println!("C {:?}", packet);
-        packet.writer().write(
+        console::debug(&format!("C {:?}", packet));
+        let writer = packet.writer(&self.protocol());
+        let _write_guard = self
+            .write_lock
+            .lock()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock stream for writing: {:?}", e)))?;
+        writer.write(
             self.stream
                 .try_clone()
                 .map_err(|e| ErrorType::Fatal(format!("Could not clone TCP stream: {:?}", e)))?,
         )
     }
 
+    // Tells the client why it has to go and closes the connection, the thread reading from it
+    // then takes care of the rest
+    pub fn disconnect(&self, reason: Chat) {
+        let packet = ClientboundPacket::Disconnect(DisconnectPacket { reason });
+        if let Err(e) = self.send_packet(packet) {
+            console::log(&format!("Could not send disconnect: {:?}", e));
+        }
+        if let Err(e) = self.stream.shutdown(Shutdown::Both) {
+            console::log(&format!("Could not close connection: {:?}", e));
+        }
+    }
+
     pub fn run(&self) {
         let mut state_tag = ConnectionStateTag::Handshaking;
         let mut reader = PacketReader::new(
@@ -112,32 +171,32 @@ impl ClientHandler {
             0
         );
         while state_tag != ConnectionStateTag::Exit {
-            let res_packet = reader.read_packet(&state_tag);
+            let res_packet = reader.read_packet(&state_tag, &self.protocol());
             if res_packet.is_err() {
                 match res_packet {
                     Err(ErrorType::Fatal(msg)) => {
-                        eprintln!("FATAL: {}", msg);
+                        console::log(&format!("FATAL: {}", msg));
                         break;
                     }
                     Err(ErrorType::Recoverable(msg)) => {
-                        eprintln!("Whoops: {}", msg);
+                        console::log(&format!("Whoops: {}", msg));
                         continue;
                     }
                     Err(ErrorType::GracefulExit) => {
-                        println!("Goodbye o/");
+                        console::debug("Goodbye o/");
                         break;
                     }
                     Ok(_) => unreachable!(),
                 }
             }
             let packet = res_packet.unwrap();
-            println!("S({}) {:?}", match state_tag {
+            console::debug(&format!("S({}) {:?}", match state_tag {
                 ConnectionStateTag::Play => "P",
                 ConnectionStateTag::Handshaking => "H",
                 ConnectionStateTag::Status => "S",
                 ConnectionStateTag::Exit => "E",
                 ConnectionStateTag::Login => "L",
-            }, packet);
+            }, packet));
 
             let result;
             {
@@ -156,16 +215,16 @@ impl ClientHandler {
                         if send_res.is_err() {
                             match send_res {
                                 Err(ErrorType::Fatal(msg)) => {
-                                    eprintln!("FATAL: {}", msg);
+                                    console::log(&format!("FATAL: {}", msg));
                                     transition = ConnectionStateTransition::TransitionTo(
                                         ConnectionStateTag::Exit
                                     );
                                 }
                                 Err(ErrorType::Recoverable(msg)) => {
-                                    eprintln!("Whoops: {}", msg);
+                                    console::log(&format!("Whoops: {}", msg));
                                 }
                                 Err(ErrorType::GracefulExit) => {
-                                    println!("Goodbye o/");
+                                    console::debug("Goodbye o/");
                                     transition = ConnectionStateTransition::TransitionTo(
                                         ConnectionStateTag::Exit
                                     );
@@ -178,6 +237,14 @@ impl ClientHandler {
                         let mut state_lock = self.state.lock().expect("Could not lock state");
                         match transition {
                             ConnectionStateTransition::TransitionTo(new_tag) => {
+                                // The handshake decides which packet mapping the connection uses
+                                if let ConnectionState::Handshaking(handshaking_state) = &*state_lock {
+                                    if let Some(protocol) = ProtocolVersion::from_number(
+                                        handshaking_state.protocol_version
+                                    ) {
+                                        *self.protocol.write().expect("Could not lock protocol version") = protocol;
+                                    }
+                                }
                                 *state_lock = match new_tag {
                                     ConnectionStateTag::Handshaking => ConnectionState::Handshaking(
                                         HandshakingState::from_state(&state_lock).unwrap(),
@@ -189,7 +256,10 @@ impl ClientHandler {
                                         ConnectionState::Login(LoginState::from_state(&state_lock).unwrap())
                                     }
                                     ConnectionStateTag::Play => {
-                                        ConnectionState::Play(PlayState::from_state(&state_lock).unwrap())
+                                        let play_state = PlayState::from_state(&state_lock).unwrap();
+                                        *self.player_eid.write().expect("Could not lock player eid") =
+                                            Some(play_state.player_eid);
+                                        ConnectionState::Play(play_state)
                                     }
                                     ConnectionStateTag::Exit => {
                                         break;
@@ -202,14 +272,14 @@ impl ClientHandler {
                     }
                 },
                 Err(ErrorType::Fatal(msg)) => {
-                    eprintln!("FATAL: {}", msg);
+                    console::log(&format!("FATAL: {}", msg));
                     state_tag = ConnectionStateTag::Exit;
                 }
                 Err(ErrorType::Recoverable(msg)) => {
-                    eprintln!("Whoops: {}", msg);
+                    console::log(&format!("Whoops: {}", msg));
                 }
                 Err(ErrorType::GracefulExit) => {
-                    println!("Goodbye o/");
+                    console::debug("Goodbye o/");
                     state_tag = ConnectionStateTag::Exit;
                 }
             }
@@ -1,4 +1,3 @@
-use rand::random;
 use uuid::Uuid;
 
 use super::ConnectionState;
@@ -7,13 +6,21 @@ use super::ConnectionStateTransition;
 
 use crate::chat::Chat;
 use crate::chat::ChatPosition;
+use crate::console;
 use crate::error_type::ErrorType;
 use crate::packets::clientbound::*;
 use crate::nbt::NBTReader;
 use crate::packets::serverbound::RecipeBook;
 use crate::packets::serverbound::ServerboundPacket;
 use crate::Server;
-use crate::player::OPLevel;
+use crate::player::Movement;
+use crate::player::Player;
+use crate::player::MovementChecker;
+use crate::server::CommandContext;
+use crate::server::CommandSource;
+use crate::server::ItemStack;
+use crate::server::ServerData;
+use crate::world::{DeferredPacket, PacketSink};
 
 use std::convert::TryInto;
 use std::sync::Arc;
@@ -22,13 +29,133 @@ use std::time::Instant;
 #[derive(Debug, PartialEq)]
 pub struct PlayState {
     pub player_eid: i32,
+    pub joined: bool,
+    pub movement_checker: MovementChecker,
+}
+
+impl PlayState {
+    // All four movement packets end up here
+    fn handle_movement(
+        &mut self,
+        movement: Movement,
+        server: &Arc<Server>,
+        server_data: &ServerData,
+        queue: &mut Vec<ClientboundPacket>,
+    ) -> Result<(), ErrorType> {
+        let world = server_data
+            .settings
+            .worlds
+            .get(&server_data.settings.selected_world)
+            .ok_or(ErrorType::Fatal("Invalid selected world".to_string()))?;
+
+        // Get the player
+        let entity_arc = world
+            .get_entity(self.player_eid)?
+            .ok_or(ErrorType::Fatal("Player does not exist".to_string()))?;
+        let mut entity = entity_arc.write().map_err(|e| {
+            ErrorType::Fatal(format!("Could not lock player for writing: {}", e))
+        })?;
+        let player = entity.as_player_mut()?;
+
+        // The client still thinks it is where it was before the teleport
+        if player.is_teleporting() {
+            return Ok(());
+        }
+
+        // Put the player back where they were if the movement is not allowed
+        if let Err(violation) = self.movement_checker.check(&movement, player, world) {
+            console::log(&format!("{} {}, moving them back", player.username, violation));
+            let teleport_id = player.start_teleport();
+            queue.push(ClientboundPacket::PlayerPositionAndLook(
+                PlayerPositionAndLookPacket::from_player(player, teleport_id),
+            ));
+            return Ok(());
+        }
+
+        let previous = (player.position.clone(), player.look.clone());
+        movement.apply_to(player);
+        queue.append(&mut world.update_loaded_chunks(
+            self.player_eid,
+            player,
+            self.chunk_sink(server),
+        )?);
+
+        // Let the players that can see this one know it moved
+        drop(entity);
+        let tracking_packets = world.update_player_tracking(
+            self.player_eid,
+            Some(previous),
+            server_data.settings.view_distance,
+        )?;
+        self.dispatch(server, queue, tracking_packets);
+
+        Ok(())
+    }
+
+    // Runs the command for this player, the feedback and errors only go to them
+    fn run_command(
+        &self,
+        command: &str,
+        server: &Server,
+        server_data: &mut ServerData,
+        queue: &mut Vec<ClientboundPacket>,
+    ) {
+        let dispatcher = server_data.commands.clone();
+        let mut context = CommandContext::new(CommandSource::Player(self.player_eid), server, server_data);
+        if let Err(e) = dispatcher.execute(command, &mut context) {
+            context.feedback.append(&mut e.to_chat());
+        }
+        for message in context.feedback {
+            queue.push(ClientboundPacket::ChatMessage(ChatMessagePacket {
+                message,
+                sender: Uuid::nil(),
+                position: ChatPosition::SystemMessage,
+            }));
+        }
+    }
+
+    // Chunks that are loaded in the background are sent on the next tick
+    fn chunk_sink(&self, server: &Arc<Server>) -> PacketSink {
+        let server = server.clone();
+        let player_eid = self.player_eid;
+        Arc::new(move |packet: DeferredPacket| {
+            let task = Box::new(move |server: &Server| {
+                if let Some(packet) = packet() {
+                    server.send_to_player(player_eid, packet);
+                }
+            });
+            if let Err(e) = server.scheduler.schedule(task) {
+                console::log(&format!("Could not schedule chunk for player {}: {:?}", player_eid, e));
+            }
+        })
+    }
+
+    // Packets for this player go into the queue, the others are sent to their own connection
+    fn dispatch(
+        &self,
+        server: &Server,
+        queue: &mut Vec<ClientboundPacket>,
+        packets: Vec<(i32, ClientboundPacket)>,
+    ) {
+        for (recipient_eid, packet) in packets {
+            if recipient_eid == self.player_eid {
+                queue.push(packet);
+            } else {
+                server.send_to_player(recipient_eid, packet);
+            }
+        }
+    }
 }
 
 impl ConnectionStateTrait for PlayState {
     fn from_state(prev_state: &ConnectionState) -> Result<Self, ErrorType> {
         match prev_state {
             ConnectionState::Login(login_state) => Ok(Self {
-                player_eid: login_state.player_eid,
+                player_eid: login_state.player_eid.ok_or_else(|| {
+                    ErrorType::Fatal("Cannot go into Play state before the player exists".to_string())
+                })?,
+                joined: false,
+                movement_checker: MovementChecker::new(),
             }),
             x => Err(ErrorType::Fatal(format!(
                 "Cannot go into Play state from {:#?}",
@@ -42,18 +169,47 @@ impl ConnectionStateTrait for PlayState {
         packet: ServerboundPacket,
         server: Arc<Server>,
     ) -> Result<(Vec<ClientboundPacket>, ConnectionStateTransition), ErrorType> {
-        let server_lock = server
+        let mut server_lock = server
             .data
             .lock()
             .map_err(|e| ErrorType::Fatal(format!("Could not lock server: {:?}", e)))?;
         let mut queue = vec![];
         match packet {
-            ServerboundPacket::ClientSettings(_packet) => {
-                // Get the player
+            ServerboundPacket::ClientSettings(packet) => {
                 let world = server_lock.settings.worlds
                     .get(&server_lock.settings.selected_world)
                     .ok_or(ErrorType::Fatal("Invalid selected world".to_string()))?;
 
+                let view_distance = server_lock
+                    .settings
+                    .view_distance
+                    .min(packet.view_distance.into());
+
+                // The client sends this again when its settings change, only join the first time
+                if self.joined {
+                    let entity_arc = world
+                        .get_entity(self.player_eid)?
+                        .ok_or(ErrorType::Fatal("Player does not exist".to_string()))?;
+                    let mut entity = entity_arc.write().map_err(|e| {
+                        ErrorType::Fatal(format!("Could not lock player for writing: {}", e))
+                    })?;
+                    let player = entity.as_player_mut()?;
+
+                    player.view_distance = view_distance;
+                    queue.append(&mut world.update_loaded_chunks(
+                        self.player_eid,
+                        player,
+                        self.chunk_sink(&server),
+                    )?);
+                    return Ok((queue, ConnectionStateTransition::Remain));
+                }
+                self.joined = true;
+
+                // Everyone who is online, this includes the new player
+                let online_players = world.get_players()?;
+
+                // Get the player
+
                 let entity_arc = world
                     .get_entity(self.player_eid)?
                     .ok_or(ErrorType::Fatal("Player does not exist".to_string()))?;
@@ -64,10 +220,20 @@ impl ConnectionStateTrait for PlayState {
                     ))
                 })?;
                 let player = entity.as_player_mut()?;
+                player.view_distance = view_distance;
 
                 // Send Held Item
                 queue.push(ClientboundPacket::HeldItemChange(HeldItemChangePacket::from_player(&player)));
 
+                // Send the saved inventory
+                for (index, stack) in player.inventory.iter().enumerate().filter(|(_, stack)| stack.is_some()) {
+                    queue.push(ClientboundPacket::SetSlot(SetSlotPacket {
+                        window_id: 0,
+                        slot: Player::window_slot(index),
+                        item: ItemStack::to_slot(*stack),
+                    }));
+                }
+
                 // Send available recipes
                 queue.push(ClientboundPacket::DeclareRecipes(DeclareRecipesPacket {
                     recipes: server_lock.recipes.clone(),
@@ -79,19 +245,14 @@ impl ConnectionStateTrait for PlayState {
                 // Send OP Level (Entity Status)
                 queue.push(ClientboundPacket::EntityStatus(EntityStatusPacket{
                     entity_id: self.player_eid,
-                    entity_status: match player.op_level {
-                        OPLevel::Player => EntityStatus::PlayerOPLevel0,
-                        OPLevel::Moderator => EntityStatus::PlayerOPLevel1,
-                        OPLevel::Gamemaster => EntityStatus::PlayerOPLevel2,
-                        OPLevel::Admin => EntityStatus::PlayerOPLevel3,
-                        OPLevel::Owner => EntityStatus::PlayerOPLevel4,
-                    }
+                    entity_status: EntityStatus::for_op_level(player.permissions.op_level),
                 }));
 
-                // Send commands
+                // Send the commands this player may use
+                let (nodes, root_node) = server_lock.commands.nodes_for(&player.permissions);
                 queue.push(ClientboundPacket::DeclareCommands(DeclareCommandsPacket{
-                    nodes: server_lock.command_nodes.clone(),
-                    root_node: server_lock.command_root_node,
+                    nodes,
+                    root_node,
                 }));
 
                 // TEMP: unlock all exisiting recipes
@@ -100,58 +261,57 @@ impl ConnectionStateTrait for PlayState {
                 // Send unlocked recipes
                 queue.push(ClientboundPacket::UnlockRecipes(UnlockRecipesPacket::init_from_player(&player)));
 
-                // Send Player Info
-                queue.push(ClientboundPacket::PlayerInfo(PlayerInfoPacket::add_players(vec![player])));
+                // Send Player Info of everyone online
+                queue.push(ClientboundPacket::PlayerInfo(PlayerInfoPacket::add_players(
+                    online_players.iter().collect()
+                )));
 
-                // Tell player where they are
-                let player_chunk_x = (player.position.x / 16.0).floor() as i32;
-                let player_chunk_z = (player.position.z / 16.0).floor() as i32;
-                queue.push(ClientboundPacket::UpdateViewPosition(UpdateViewPositionPacket {
-                    chunk_x: player_chunk_x,
-                    chunk_z: player_chunk_z
-                }));
+                // Tell everyone else about the new player
+                server.send_to_all_except(
+                    self.player_eid,
+                    ClientboundPacket::PlayerInfo(PlayerInfoPacket::add_players(vec![player])),
+                );
 
                 // --v-- temporary, unordered packets for testing --v--
 
-                // TODO: Fix broadcasting
-                // Send a welcome message
-                queue.push(ClientboundPacket::ChatMessage(ChatMessagePacket {
-                    message: Chat::new(format!(
-                        "{} joined the game",
-                        player.username
-                    )),
+                // Send a welcome message to everyone
+                let join_message = ClientboundPacket::ChatMessage(ChatMessagePacket {
+                    message: Chat::translate("multiplayer.player.joined", vec![player.display_name()])
+                        .color("yellow".to_string()),
                     sender: Uuid::nil(),
                     position: ChatPosition::SystemMessage,
-                }));
+                });
+                console::log(&format!("{} joined the game", player.username));
+                server.send_to_all_except(self.player_eid, join_message.clone());
+                queue.push(join_message);
 
-                for x in -8i32..8 {
-                    for z in -8i32..8 {
-                        let column = world.get_chunk_column(
-                            (player_chunk_x + x).try_into().unwrap(),
-                            (player_chunk_z + z).try_into().unwrap(),
-                        );
-                        queue.push(ClientboundPacket::ChunkData(ChunkDataPacket::from_chunk_column(
-                            x,
-                            z,
-                            column
-                        )));
-                    }
-                }
+                // Send the time
+                queue.push(ClientboundPacket::TimeUpdate(world.time.time_update_packet()));
+
+                // Send the weather
+                queue.append(&mut world.weather.join_packets());
+
+                // Send the chunks around the player
+                queue.append(&mut world.update_loaded_chunks(
+                    self.player_eid,
+                    player,
+                    self.chunk_sink(&server),
+                )?);
 
                 // Send the player position and look packet
-                let x = player.position.x;
-                let y = player.position.y;
-                let z = player.position.z;
-                let pitch = player.look.pitch;
-                let yaw = player.look.yaw;
-                queue.push(ClientboundPacket::PlayerPositionAndLook(PlayerPositionAndLookPacket {
-                    x: ValueType::Absolute(x),
-                    y: ValueType::Absolute(y),
-                    z: ValueType::Absolute(z),
-                    yaw: ValueType::Absolute(yaw),
-                    pitch: ValueType::Absolute(pitch),
-                    teleport_id: random(),
-                }));
+                let teleport_id = player.start_teleport();
+                queue.push(ClientboundPacket::PlayerPositionAndLook(
+                    PlayerPositionAndLookPacket::from_player(player, teleport_id),
+                ));
+
+                // Show the new player to everyone nearby and the other way around
+                drop(entity);
+                let tracking_packets = world.update_player_tracking(
+                    self.player_eid,
+                    None,
+                    server_lock.settings.view_distance,
+                )?;
+                self.dispatch(&server, &mut queue, tracking_packets);
 
                 Ok((queue, ConnectionStateTransition::Remain))
             }
@@ -174,15 +334,52 @@ impl ConnectionStateTrait for PlayState {
                 })?;
                 let player = entity.as_player()?;
 
+                if let Some(command) = packet.message.strip_prefix('/') {
+                    console::log(&format!("{} issued server command: /{}", player.username, command));
+                    // Commands might need to change the player
+                    drop(entity);
+                    self.run_command(command, &server, &mut server_lock, &mut queue);
+                    return Ok((queue, ConnectionStateTransition::Remain));
+                }
+
                 // Send the message to all players
+                console::log(&format!("<{}> {}", player.username, packet.message));
+                let message = Chat::translate(
+                    "chat.type.text",
+                    vec![player.display_name(), Chat::new(packet.message.clone())],
+                );
                 let chat_packet = ClientboundPacket::ChatMessage(ChatMessagePacket {
-                    message: Chat::new(format!("<{}> {}", player.username, packet.message)),
+                    message,
                     sender: player.uuid,
                     position: ChatPosition::SystemMessage,
                 });
                 server.send_to_all(chat_packet);
                 Ok((queue, ConnectionStateTransition::Remain))
             }
+            ServerboundPacket::TabComplete(packet) => {
+                let command = packet.text.strip_prefix('/').unwrap_or(&packet.text);
+                let prefix_length = packet.text.chars().count() - command.chars().count();
+
+                let dispatcher = server_lock.commands.clone();
+                let context = CommandContext::new(
+                    CommandSource::Player(self.player_eid),
+                    &server,
+                    &mut server_lock,
+                );
+                let suggestions = dispatcher.suggest(command, &context).map_err(|e| {
+                    ErrorType::Recoverable(format!("Could not complete {}: {}", packet.text, e))
+                })?;
+
+                queue.push(ClientboundPacket::TabComplete(TabCompletePacket {
+                    transaction_id: packet.transaction_id.try_into().map_err(|_| {
+                        ErrorType::Recoverable(format!("Invalid transaction id {}", packet.transaction_id))
+                    })?,
+                    start: (suggestions.start + prefix_length).try_into().unwrap(),
+                    length: suggestions.length.try_into().unwrap(),
+                    matches: suggestions.matches.into_iter().map(|text| (text, None)).collect(),
+                }));
+                Ok((queue, ConnectionStateTransition::Remain))
+            }
             ServerboundPacket::KeepAlive(packet) => {
                 let now = Instant::now();
                 let world = server_lock
@@ -201,11 +398,11 @@ impl ConnectionStateTrait for PlayState {
                         e.to_string()
                     ))
                 })?;
-                let mut player = entity.as_player_mut()?;
+                let player = entity.as_player_mut()?;
 
                 if let Some((last_id, last_time)) = player.last_keepalive_sent {
                     if packet.id != last_id {
-                        eprintln!("Got keepalive id {}, but expected {}, discarding...", packet.id, last_id);
+                        console::log(&format!("Got keepalive id {}, but expected {}, discarding...", packet.id, last_id));
                     } else {
                         let latency = (now - last_time).as_millis().try_into().unwrap();
                         player.latency = Some(latency);
@@ -215,7 +412,7 @@ impl ConnectionStateTrait for PlayState {
                         ));
                     }
                 } else {
-                    eprintln!("Got keepalive with id {} without having sent one...", packet.id);
+                    console::log(&format!("Got keepalive with id {} without having sent one...", packet.id));
                 }
 
                 Ok((queue, ConnectionStateTransition::Remain))
@@ -223,7 +420,7 @@ impl ConnectionStateTrait for PlayState {
             ServerboundPacket::PluginMessage(packet) => {
                 match packet.channel.as_str() {
                     "minecraft:brand" => {
-                        println!(
+                        console::debug(&format!(
                             "Player {} is using brand {}",
                             self.player_eid,
                             NBTReader::new(
@@ -232,7 +429,7 @@ impl ConnectionStateTrait for PlayState {
                                     ErrorType::Recoverable(format!("Packet data too big"))
                                 )?
                             ).read_string()?
-                        );
+                        ));
                         Ok((queue, ConnectionStateTransition::Remain))
                     }
                     c => {
@@ -240,38 +437,45 @@ impl ConnectionStateTrait for PlayState {
                     }
                 }
             }
-            ServerboundPacket::TeleportConfirm(_packet) => {
-                // TODO: validate teleport id == sent teleport id
-                Ok((queue, ConnectionStateTransition::Remain))
-            }
-            ServerboundPacket::PlayerPositionAndRotation(packet) => {
+            ServerboundPacket::TeleportConfirm(packet) => {
                 let world = server_lock
                     .settings
                     .worlds
                     .get(&server_lock.settings.selected_world)
-                    .ok_or(ErrorType::Fatal("Invalid selected".to_string()))?;
+                    .ok_or(ErrorType::Fatal("Invalid selected world".to_string()))?;
 
                 // Get the player
                 let entity_arc = world
                     .get_entity(self.player_eid)?
                     .ok_or(ErrorType::Fatal("Player does not exist".to_string()))?;
                 let mut entity = entity_arc.write().map_err(|e| {
-                    ErrorType::Fatal(format!(
-                        "Could not lock player for writing: {}",
-                        e.to_string()
-                    ))
+                    ErrorType::Fatal(format!("Could not lock player for writing: {}", e))
                 })?;
                 let player = entity.as_player_mut()?;
 
-                player.position.x = packet.x;
-                player.position.y = packet.feet_y;
-                player.position.z = packet.z;
-                player.look.yaw = packet.yaw;
-                player.look.pitch = packet.pitch;
-                player.position.on_ground = packet.on_ground;
+                let teleport_id = packet.teleport_id.try_into().map_err(|_| {
+                    ErrorType::Recoverable(format!("Invalid teleport id {}", packet.teleport_id))
+                })?;
+                player.confirm_teleport(teleport_id)?;
 
                 Ok((queue, ConnectionStateTransition::Remain))
             }
+            ServerboundPacket::PlayerPositionAndRotation(packet) => {
+                self.handle_movement(packet.into(), &server, &server_lock, &mut queue)?;
+                Ok((queue, ConnectionStateTransition::Remain))
+            }
+            ServerboundPacket::PlayerPosition(packet) => {
+                self.handle_movement(packet.into(), &server, &server_lock, &mut queue)?;
+                Ok((queue, ConnectionStateTransition::Remain))
+            }
+            ServerboundPacket::PlayerRotation(packet) => {
+                self.handle_movement(packet.into(), &server, &server_lock, &mut queue)?;
+                Ok((queue, ConnectionStateTransition::Remain))
+            }
+            ServerboundPacket::PlayerMovement(packet) => {
+                self.handle_movement(packet.into(), &server, &server_lock, &mut queue)?;
+                Ok((queue, ConnectionStateTransition::Remain))
+            }
             ServerboundPacket::HeldItemChange(packet) => {
                 if packet.slot > 8 || packet.slot < 0 {
                     return Err(ErrorType::Recoverable(format!(
@@ -7,6 +7,7 @@ use crate::chat::Chat;
 use crate::error_type::ErrorType;
 use crate::packets::clientbound::status_response::StatusResponsePlayer;
 use crate::packets::clientbound::*;
+use crate::packets::protocol::ProtocolVersion;
 use crate::packets::serverbound::ServerboundPacket;
 use crate::server::Entity;
 use crate::Server;
@@ -15,12 +16,16 @@ use std::convert::TryInto;
 use std::sync::Arc;
 
 #[derive(Debug, PartialEq)]
-pub struct StatusState {}
+pub struct StatusState {
+    pub protocol_version: isize,
+}
 
 impl ConnectionStateTrait for StatusState {
     fn from_state(prev_state: &ConnectionState) -> Result<Self, ErrorType> {
         match prev_state {
-            ConnectionState::Handshaking(_) => Ok(Self {}),
+            ConnectionState::Handshaking(handshaking_state) => Ok(Self {
+                protocol_version: handshaking_state.protocol_version,
+            }),
             x => Err(ErrorType::Fatal(format!(
                 "Cannot go into Status state from {:#?}",
                 x
@@ -69,9 +74,16 @@ impl ConnectionStateTrait for StatusState {
                     })
                     .collect::<Vec<_>>();
 
+                // Echo the version of the client if we support it, so it does not show up as
+                // incompatible
+                let version_protocol = match ProtocolVersion::from_number(self.protocol_version) {
+                    Some(protocol) => protocol.number as usize,
+                    None => server_lock.settings.protocol_version,
+                };
+
                 queue.push(ClientboundPacket::StatusResponse(StatusResponsePacket {
                     version_name: server_lock.settings.version.to_string(),
-                    version_protocol: server_lock.settings.protocol_version,
+                    version_protocol,
                     players_max: server_lock.settings.max_players.try_into().unwrap(),
                     players_curr: player_names.len(),
                     sample: player_names
@@ -0,0 +1,405 @@
+// The server console: reads commands from stdin and keeps what is being typed below everything
+// that gets logged. Packets go to their own log file so they do not drown out the rest.
+
+use crate::Server;
+
+use std::fs;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::BufRead;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+const PROMPT: &str = "> ";
+const PACKET_LOG: &str = "logs/packets.log";
+const MAX_HISTORY: usize = 100;
+
+// The line that is being typed, only shown while the terminal is in raw mode
+struct Prompt {
+    active: bool,
+    buffer: Vec<char>,
+    cursor: usize,
+}
+
+impl Prompt {
+    fn draw(&self, out: &mut impl Write) -> io::Result<()> {
+        let line: String = self.buffer.iter().collect();
+        write!(out, "\r\x1b[K{}{}", PROMPT, line)?;
+        let after_cursor = self.buffer.len() - self.cursor;
+        if after_cursor > 0 {
+            write!(out, "\x1b[{}D", after_cursor)?;
+        }
+        Ok(())
+    }
+}
+
+static PROMPT_STATE: Mutex<Prompt> = Mutex::new(Prompt {
+    active: false,
+    buffer: vec![],
+    cursor: 0,
+});
+static PACKET_LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+// Everything meant for whoever runs the server goes through here
+pub fn log(message: &str) {
+    let prompt = match PROMPT_STATE.lock() {
+        Ok(prompt) => prompt,
+        Err(_) => {
+            println!("{}", message);
+            return;
+        }
+    };
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    let result = if prompt.active {
+        write!(out, "\r\x1b[K{}\n", message).and_then(|_| prompt.draw(&mut out))
+    } else {
+        writeln!(out, "{}", message)
+    };
+    if result.and_then(|_| out.flush()).is_err() {
+        eprintln!("Could not write to the console: {}", message);
+    }
+}
+
+// Packet dumps and other noise, these end up in logs/packets.log
+pub fn debug(message: &str) {
+    let mut file = match PACKET_LOG_FILE.lock() {
+        Ok(file) => file,
+        Err(_) => return,
+    };
+    if file.is_none() {
+        let path = Path::new(PACKET_LOG);
+        if let Some(directory) = path.parent() {
+            let _ = fs::create_dir_all(directory);
+        }
+        *file = OpenOptions::new().create(true).append(true).open(path).ok();
+    }
+    if let Some(file) = file.as_mut() {
+        let _ = writeln!(file, "{}", message);
+    }
+}
+
+// Reads commands until stdin closes, a leading slash is optional
+pub fn run(server: Arc<Server>) {
+    match LineEditor::new() {
+        Some(mut editor) => {
+            while let Some(line) = editor.read_line(&server) {
+                run_line(&server, &line);
+            }
+        }
+        None => {
+            for line in io::stdin().lock().lines() {
+                match line {
+                    Ok(line) => run_line(&server, &line),
+                    Err(e) => {
+                        log(&format!("Could not read from the console: {}", e));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn run_line(server: &Server, line: &str) {
+    let command = line.trim();
+    let command = command.strip_prefix('/').unwrap_or(command);
+    if !command.is_empty() {
+        server.run_console_command(command);
+    }
+}
+
+// Puts the terminal back the way it was, the line editor changes it
+pub fn restore_terminal() {
+    #[cfg(unix)]
+    raw_mode::disable();
+    if let Ok(mut prompt) = PROMPT_STATE.lock() {
+        prompt.active = false;
+    }
+}
+
+// A crash must not leave the shell that started the server in raw mode
+#[cfg(unix)]
+fn restore_terminal_on_panic() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        raw_mode::disable();
+        // The panicking thread may be the one holding the prompt
+        if let Ok(mut prompt) = PROMPT_STATE.try_lock() {
+            if prompt.active {
+                prompt.active = false;
+                print!("\r\x1b[K");
+                let _ = io::stdout().flush();
+            }
+        }
+        default_hook(info);
+    }));
+}
+
+// Editing with arrow keys, history and tab completion, only when stdin is a terminal
+struct LineEditor {
+    history: Vec<String>,
+}
+
+enum Key {
+    Char(char),
+    Enter,
+    Backspace,
+    Delete,
+    Tab,
+    Up,
+    Down,
+    Left,
+    Right,
+    Home,
+    End,
+    Interrupt,
+    Other,
+}
+
+impl LineEditor {
+    #[cfg(unix)]
+    fn new() -> Option<Self> {
+        if !raw_mode::enable() {
+            return None;
+        }
+        restore_terminal_on_panic();
+        Some(Self { history: vec![] })
+    }
+
+    #[cfg(not(unix))]
+    fn new() -> Option<Self> {
+        None
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Prompt)) {
+        let mut prompt = match PROMPT_STATE.lock() {
+            Ok(prompt) => prompt,
+            Err(_) => return,
+        };
+        f(&mut prompt);
+        let stdout = io::stdout();
+        let mut out = stdout.lock();
+        let _ = prompt.draw(&mut out).and_then(|_| out.flush());
+    }
+
+    fn read_line(&mut self, server: &Server) -> Option<String> {
+        self.update(|prompt| {
+            prompt.active = true;
+            prompt.buffer.clear();
+            prompt.cursor = 0;
+        });
+        let stdin = io::stdin();
+        let mut input = stdin.lock().bytes();
+        // Where we are in the history, the line that was being typed is kept in draft
+        let mut history_index = self.history.len();
+        let mut draft = vec![];
+
+        loop {
+            let key = read_key(&mut input)?;
+            match key {
+                Key::Enter => break,
+                Key::Interrupt => {
+                    self.update(|prompt| {
+                        prompt.buffer = "stop".chars().collect();
+                        prompt.cursor = prompt.buffer.len();
+                    });
+                    break;
+                }
+                Key::Char(c) => self.update(|prompt| {
+                    prompt.buffer.insert(prompt.cursor, c);
+                    prompt.cursor += 1;
+                }),
+                Key::Backspace => self.update(|prompt| {
+                    if prompt.cursor > 0 {
+                        prompt.cursor -= 1;
+                        prompt.buffer.remove(prompt.cursor);
+                    }
+                }),
+                Key::Delete => self.update(|prompt| {
+                    if prompt.cursor < prompt.buffer.len() {
+                        prompt.buffer.remove(prompt.cursor);
+                    }
+                }),
+                Key::Left => self.update(|prompt| prompt.cursor = prompt.cursor.saturating_sub(1)),
+                Key::Right => self.update(|prompt| prompt.cursor = (prompt.cursor + 1).min(prompt.buffer.len())),
+                Key::Home => self.update(|prompt| prompt.cursor = 0),
+                Key::End => self.update(|prompt| prompt.cursor = prompt.buffer.len()),
+                Key::Up | Key::Down => {
+                    let new_index = match key {
+                        Key::Up if history_index > 0 => history_index - 1,
+                        Key::Down if history_index < self.history.len() => history_index + 1,
+                        _ => continue,
+                    };
+                    let line: Vec<char> = match self.history.get(new_index) {
+                        Some(line) => line.chars().collect(),
+                        None => draft.clone(),
+                    };
+                    self.update(|prompt| {
+                        if history_index == self.history.len() {
+                            draft = prompt.buffer.clone();
+                        }
+                        prompt.buffer = line;
+                        prompt.cursor = prompt.buffer.len();
+                    });
+                    history_index = new_index;
+                }
+                Key::Tab => self.complete(server),
+                Key::Other => {}
+            }
+        }
+
+        let line = match PROMPT_STATE.lock() {
+            Ok(mut prompt) => {
+                prompt.active = false;
+                let line: String = prompt.buffer.iter().collect();
+                let stdout = io::stdout();
+                let mut out = stdout.lock();
+                let _ = write!(out, "\r\x1b[K{}{}\n", PROMPT, line).and_then(|_| out.flush());
+                line
+            }
+            Err(_) => return None,
+        };
+        if !line.trim().is_empty() && self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            if self.history.len() > MAX_HISTORY {
+                self.history.remove(0);
+            }
+        }
+        Some(line)
+    }
+
+    // Fills in what all suggestions have in common, shows them when that does not get any further
+    fn complete(&self, server: &Server) {
+        let (typed, cursor) = match PROMPT_STATE.lock() {
+            Ok(prompt) => (prompt.buffer[..prompt.cursor].iter().collect::<String>(), prompt.cursor),
+            Err(_) => return,
+        };
+        let command = typed.strip_prefix('/').unwrap_or(&typed);
+        let prefix_length = typed.chars().count() - command.chars().count();
+        let suggestions = match server.complete_console_command(command) {
+            Some(suggestions) if !suggestions.matches.is_empty() => suggestions,
+            _ => return,
+        };
+
+        let start = prefix_length + suggestions.start;
+        let already_typed = cursor.saturating_sub(start);
+        let common = common_prefix(&suggestions.matches);
+        if suggestions.matches.len() > 1 && common.chars().count() <= already_typed {
+            log(&suggestions.matches.join("  "));
+            return;
+        }
+        let replacement = if suggestions.matches.len() == 1 {
+            format!("{} ", suggestions.matches[0])
+        } else {
+            common
+        };
+        self.update(|prompt| {
+            let end = (start + suggestions.length).min(prompt.buffer.len());
+            prompt.buffer.splice(start.min(end)..end, replacement.chars());
+            prompt.cursor = start + replacement.chars().count();
+        });
+    }
+}
+
+fn common_prefix(values: &[String]) -> String {
+    let mut prefix: Vec<char> = values[0].chars().collect();
+    for value in values.iter().skip(1) {
+        let length = prefix.iter().zip(value.chars()).take_while(|(a, b)| **a == *b).count();
+        prefix.truncate(length);
+    }
+    prefix.into_iter().collect()
+}
+
+fn read_key(input: &mut impl Iterator<Item = io::Result<u8>>) -> Option<Key> {
+    let byte = input.next()?.ok()?;
+    Some(match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        // Ctrl-C
+        3 => Key::Interrupt,
+        8 | 127 => Key::Backspace,
+        0x1b => {
+            if input.next()?.ok()? != b'[' {
+                return Some(Key::Other);
+            }
+            match input.next()?.ok()? {
+                b'A' => Key::Up,
+                b'B' => Key::Down,
+                b'C' => Key::Right,
+                b'D' => Key::Left,
+                b'H' => Key::Home,
+                b'F' => Key::End,
+                b'3' => {
+                    input.next()?.ok()?;
+                    Key::Delete
+                }
+                _ => Key::Other,
+            }
+        }
+        byte if byte < 0x20 => Key::Other,
+        byte if byte < 0x80 => Key::Char(byte as char),
+        // The start of a multi byte UTF-8 character
+        byte => {
+            let length = match byte {
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                _ => 4,
+            };
+            let mut bytes = vec![byte];
+            for _ in 1..length {
+                bytes.push(input.next()?.ok()?);
+            }
+            match std::str::from_utf8(&bytes).ok().and_then(|s| s.chars().next()) {
+                Some(c) => Key::Char(c),
+                None => Key::Other,
+            }
+        }
+    })
+}
+
+#[cfg(unix)]
+mod raw_mode {
+    use std::sync::Mutex;
+
+    // How the terminal was set up before we took it over
+    static ORIGINAL: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+    // Keys come in one by one without being echoed, output is left alone so \n still works
+    pub fn enable() -> bool {
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) != 1 || libc::isatty(libc::STDOUT_FILENO) != 1 {
+                return false;
+            }
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return false;
+            }
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return false;
+            }
+            if let Ok(mut saved) = ORIGINAL.lock() {
+                *saved = Some(original);
+            }
+        }
+        true
+    }
+
+    pub fn disable() {
+        if let Ok(mut saved) = ORIGINAL.lock() {
+            if let Some(original) = saved.take() {
+                unsafe {
+                    libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &original);
+                }
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 mod chat;
 mod client_handler;
+mod console;
 mod error_type;
 mod nbt;
 mod packets;
@@ -7,38 +8,57 @@ mod server;
 mod world;
 mod player;
 
+use chat::Chat;
+use chat::ChatPosition;
 use client_handler::ClientHandler;
-use client_handler::ConnectionState;
+use packets::clientbound::ChatMessagePacket;
 use packets::clientbound::ClientboundPacket;
+use packets::clientbound::DestroyEntitiesPacket;
 use packets::clientbound::KeepAlivePacket;
+use packets::clientbound::PlayerInfoPacket;
+use server::CommandContext;
+use server::CommandSource;
+use server::Scheduler;
 use server::ServerData;
+use server::Suggestions;
 
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::net::TcpListener;
-use std::ops::Deref;
+use std::process;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+const TICK_DURATION: Duration = Duration::from_millis(50);
+// When the server is further behind than this, it skips ticks instead of catching up
+const MAX_TICK_LAG: Duration = Duration::from_secs(2);
+const LAG_WARNING_INTERVAL: Duration = Duration::from_secs(15);
+
+const KEEPALIVE_INTERVAL_TICKS: u64 = 20 * 20;
+const TIME_UPDATE_INTERVAL_TICKS: u64 = 20;
+const AUTOSAVE_INTERVAL_TICKS: u64 = 5 * 60 * 20;
 
 pub struct Server {
     data: Arc<Mutex<ServerData>>,
     connections: Arc<Mutex<HashMap<usize, Arc<ClientHandler>>>>,
+    pub scheduler: Scheduler,
 }
 
 impl Server {
     pub fn run(self) {
         let server_arc = Arc::new(self);
 
-        // Set up keepalive ticks (in the future this could be game ticks)
+        // Set up game ticks
         let server_arc_copy = server_arc.clone();
-        thread::spawn(move || {
-            let twenty_seconds = Duration::new(20, 0);
-            loop {
-                thread::sleep(twenty_seconds);
-                server_arc_copy.send_keepalive();
-            }
-        });
+        thread::spawn(move || server_arc_copy.run_ticks());
+
+        // Set up the console
+        let server_arc_copy = server_arc.clone();
+        thread::spawn(move || console::run(server_arc_copy));
 
         // Set up client listener
         let listener = TcpListener::bind("0.0.0.0:25565").expect("Could not start server");
@@ -51,7 +71,7 @@ impl Server {
             curr_id += 1;
             thread::spawn(move || {
                 let client_handler =
-                    ClientHandler::new(stream.expect("Invalid stream"), server_copy);
+                    ClientHandler::new(stream.expect("Invalid stream"), server_copy.clone());
                 let ch_arc = Arc::new(client_handler);
                 connections_copy
                     .lock()
@@ -62,80 +82,349 @@ impl Server {
                     .lock()
                     .expect("Could not lock connection list")
                     .remove(&connection_id);
+                server_copy.on_disconnect(&ch_arc);
             });
         }
     }
 
-    pub fn send_to_all(&self, packet: ClientboundPacket) {
+    // All connections that have a player in the world. The connection table is only locked
+    // while copying, so callers can hold other locks while sending.
+    fn player_connections(&self) -> Vec<(i32, Arc<ClientHandler>)> {
         self.connections
             .lock()
             .expect("Could not lock connection table")
             .values()
-            .for_each(|x| x.send_packet(packet.clone()).unwrap());
+            .filter_map(|x| x.player_eid().map(|eid| (eid, x.clone())))
+            .collect()
     }
 
-    pub fn send_keepalive(&self) {
-        self.connections
-            .lock()
-            .expect("Could not lock connection table")
-            .values()
-            .for_each(|x| {
-                // This whole function is to get the player object for the ClientHandler
-                // This is a mess...
-                let player_eid;
-                {
-                    let state_lock = x.state.lock().expect("Could not lock state");
-                    if let ConnectionState::Handshaking(_) = *state_lock {
-                        return;
-                    }
-                    if let ConnectionState::Status(_) = *state_lock {
-                        return;
+    pub fn send_to_all(&self, packet: ClientboundPacket) {
+        for (player_eid, client_handler) in self.player_connections() {
+            if let Err(e) = client_handler.send_packet(packet.clone()) {
+                console::log(&format!("Could not send packet to player {}: {:?}", player_eid, e));
+            }
+        }
+    }
+
+    pub fn send_to_player(&self, player_eid: i32, packet: ClientboundPacket) {
+        let maybe_client_handler = self
+            .player_connections()
+            .into_iter()
+            .find(|(eid, _)| *eid == player_eid);
+        if let Some((_, client_handler)) = maybe_client_handler {
+            if let Err(e) = client_handler.send_packet(packet) {
+                console::log(&format!("Could not send packet to player {}: {:?}", player_eid, e));
+            }
+        }
+    }
+
+    pub fn send_to_players(&self, player_eids: &[i32], packet: ClientboundPacket) {
+        for (player_eid, client_handler) in self.player_connections() {
+            if !player_eids.contains(&player_eid) {
+                continue;
+            }
+            if let Err(e) = client_handler.send_packet(packet.clone()) {
+                console::log(&format!("Could not send packet to player {}: {:?}", player_eid, e));
+            }
+        }
+    }
+
+    pub fn send_to_all_except(&self, excluded_eid: i32, packet: ClientboundPacket) {
+        for (player_eid, client_handler) in self.player_connections() {
+            if player_eid == excluded_eid {
+                continue;
+            }
+            if let Err(e) = client_handler.send_packet(packet.clone()) {
+                console::log(&format!("Could not send packet to player {}: {:?}", player_eid, e));
+            }
+        }
+    }
+
+    pub fn disconnect_player(&self, player_eid: i32, reason: Chat) {
+        let maybe_client_handler = self
+            .player_connections()
+            .into_iter()
+            .find(|(eid, _)| *eid == player_eid);
+        if let Some((_, client_handler)) = maybe_client_handler {
+            client_handler.disconnect(reason);
+        }
+    }
+
+    // The address an online player connected from
+    pub fn player_address(&self, player_eid: i32) -> Option<IpAddr> {
+        self.player_connections()
+            .into_iter()
+            .find(|(eid, _)| *eid == player_eid)
+            .and_then(|(_, client_handler)| client_handler.address())
+    }
+
+    // Commands from the console may do everything, what they have to say is logged
+    pub fn run_console_command(&self, command: &str) {
+        let mut server_data_lock = self.data.lock().expect("Could not lock server data");
+        let dispatcher = server_data_lock.commands.clone();
+        let mut context = CommandContext::new(CommandSource::Console, self, &mut server_data_lock);
+        if let Err(e) = dispatcher.execute(command, &mut context) {
+            context.feedback.append(&mut e.to_chat());
+        }
+        for message in context.feedback {
+            console::log(&message.plain_text());
+        }
+    }
+
+    pub fn complete_console_command(&self, command: &str) -> Option<Suggestions> {
+        let mut server_data_lock = self.data.lock().expect("Could not lock server data");
+        let dispatcher = server_data_lock.commands.clone();
+        let context = CommandContext::new(CommandSource::Console, self, &mut server_data_lock);
+        dispatcher.suggest(command, &context).ok()
+    }
+
+    // Saves everything and exits, this has to run without the server data locked
+    pub fn stop(&self) {
+        console::log("Saving players and worlds");
+        {
+            let server_data_lock = self.data.lock().expect("Could not lock server data");
+            for (player_eid, client_handler) in self.player_connections() {
+                client_handler.disconnect(Chat::new("Server closed".to_string()));
+                match server_data_lock.remove_player(player_eid) {
+                    Ok(Some(player)) => {
+                        if let Err(e) = server_data_lock.save_player(&player) {
+                            console::log(&format!("Could not save player {}: {:?}", player.username, e));
+                        }
                     }
-                    player_eid = match state_lock.deref() {
-                        ConnectionState::Play(playstate) => playstate.player_eid,
-                        ConnectionState::Login(loginstate) => loginstate.player_eid,
-                        _ => unreachable!()
-                    };
+                    Ok(None) => {}
+                    Err(e) => console::log(&format!("Could not remove player {}: {:?}", player_eid, e)),
+                }
+            }
+            // The chunk workers are stopped with the process, so wait until they wrote everything
+            for world in server_data_lock.settings.worlds.values() {
+                if let Err(e) = world.chunk_provider.save_all_blocking() {
+                    console::log(&format!("Could not save chunks of {}: {:?}", world.name, e));
                 }
-                let maybe_server_data_lock = self.data.lock();
+                if let Err(e) = world.save_level_data() {
+                    console::log(&format!("Could not save level data of {}: {:?}", world.name, e));
+                }
+            }
+        }
+        console::restore_terminal();
+        process::exit(0);
+    }
+
+    // Runs a tick every 50 ms, ticks that are late run right after each other to catch up
+    fn run_ticks(&self) {
+        let mut next_tick = Instant::now();
+        let mut last_lag_warning: Option<Instant> = None;
+        loop {
+            let now = Instant::now();
+            if now < next_tick {
+                thread::sleep(next_tick - now);
+                continue;
+            }
 
-                if maybe_server_data_lock.is_err() {
-                    eprintln!("Could not lock server data");
-                    return;
+            let lag = now - next_tick;
+            if lag > MAX_TICK_LAG {
+                let warned_recently = matches!(
+                    last_lag_warning,
+                    Some(warning) if now - warning <= LAG_WARNING_INTERVAL
+                );
+                if !warned_recently {
+                    console::log(&format!(
+                        "Can't keep up! Is the server overloaded? Running {}ms or {} ticks behind",
+                        lag.as_millis(),
+                        lag.as_millis() / TICK_DURATION.as_millis()
+                    ));
+                    last_lag_warning = Some(now);
                 }
+                next_tick = now;
+            }
+
+            self.tick();
+            next_tick += TICK_DURATION;
+        }
+    }
+
+    fn tick(&self) {
+        let (tick, tasks) = match self.scheduler.next_tick() {
+            Ok(next_tick) => next_tick,
+            Err(e) => {
+                console::log(&format!("Could not start tick: {:?}", e));
+                return;
+            }
+        };
+
+        for task in tasks {
+            task(self);
+        }
+
+        self.tick_worlds(tick);
+
+        if tick.is_multiple_of(KEEPALIVE_INTERVAL_TICKS) {
+            self.send_keepalive();
+            self.log_chunk_stats();
+        }
+
+        if tick.is_multiple_of(AUTOSAVE_INTERVAL_TICKS) {
+            self.save_chunks();
+        }
+    }
+
+    fn tick_worlds(&self, tick: u64) {
+        let mut server_data_lock = match self.data.lock() {
+            Ok(server_data_lock) => server_data_lock,
+            Err(_) => {
+                console::log("Could not lock server data");
+                return;
+            }
+        };
+        // The client moves the sun by itself, this only keeps it in sync
+        let send_time = tick.is_multiple_of(TIME_UPDATE_INTERVAL_TICKS);
+
+        for world in server_data_lock.settings.worlds.values_mut() {
+            // Time and weather only concern the players that are in this world
+            let player_eids = match world.player_eids() {
+                Ok(player_eids) => player_eids,
+                Err(e) => {
+                    console::log(&format!("Could not get the players of {}: {:?}", world.name, e));
+                    vec![]
+                }
+            };
+
+            world.time.tick();
+            if send_time {
+                self.send_to_players(
+                    &player_eids,
+                    ClientboundPacket::TimeUpdate(world.time.time_update_packet()),
+                );
+            }
+
+            for packet in world.weather.tick() {
+                self.send_to_players(&player_eids, packet);
+            }
+        }
+    }
+
+    pub fn save_chunks(&self) {
+        let server_data_lock = match self.data.lock() {
+            Ok(server_data_lock) => server_data_lock,
+            Err(_) => {
+                console::log("Could not lock server data");
+                return;
+            }
+        };
+        for world in server_data_lock.settings.worlds.values() {
+            if let Err(e) = world.chunk_provider.save_all() {
+                console::log(&format!("Could not save chunks of {}: {:?}", world.name, e));
+            }
+            if let Err(e) = world.save_level_data() {
+                console::log(&format!("Could not save level data of {}: {:?}", world.name, e));
+            }
+        }
+    }
 
-                let server_data_lock = maybe_server_data_lock.unwrap();
-                let world = server_data_lock.settings.worlds
-                    .get(&server_data_lock.settings.selected_world)
-                    .expect("Invalid world selected");
+    pub fn log_chunk_stats(&self) {
+        let server_data_lock = match self.data.lock() {
+            Ok(server_data_lock) => server_data_lock,
+            Err(_) => {
+                console::log("Could not lock server data");
+                return;
+            }
+        };
+        for world in server_data_lock.settings.worlds.values() {
+            match world.chunk_provider.stats() {
+                Ok(stats) => console::debug(&format!("World {}: {}", world.name, stats)),
+                Err(e) => console::log(&format!("Could not get chunk stats of {}: {:?}", world.name, e)),
+            }
+        }
+    }
+
+    pub fn send_keepalive(&self) {
+        let server_data_lock = match self.data.lock() {
+            Ok(server_data_lock) => server_data_lock,
+            Err(_) => {
+                console::log("Could not lock server data");
+                return;
+            }
+        };
+        let world = server_data_lock.settings.worlds
+            .get(&server_data_lock.settings.selected_world)
+            .expect("Invalid world selected");
 
-                let maybe_entity_arc = world.get_entity(player_eid);
-                if maybe_entity_arc.is_err() {
-                    eprintln!("Player could not be found");
-                    return;
+        for (player_eid, client_handler) in self.player_connections() {
+            let entity_arc = match world.get_entity(player_eid) {
+                Ok(Some(entity_arc)) => entity_arc,
+                Ok(None) => {
+                    console::log("Player does not exist");
+                    continue;
                 }
-                let maybe_entity_res = maybe_entity_arc.unwrap();
-                if maybe_entity_res.is_none() {
-                    eprintln!("Player does not exist");
-                    return;
+                Err(_) => {
+                    console::log("Player could not be found");
+                    continue;
                 }
-                let maybe_entity = maybe_entity_res.unwrap();
-                let maybe_entity_lock = maybe_entity.write();
-                if maybe_entity_lock.is_err() {
-                    eprintln!("Could not lock player for writing");
-                    return
+            };
+            let mut entity = match entity_arc.write() {
+                Ok(entity) => entity,
+                Err(_) => {
+                    console::log("Could not lock player for writing");
+                    continue;
                 }
-                let mut maybe_player_entity = maybe_entity_lock.unwrap();
-                let maybe_player = maybe_player_entity.as_player_mut();
-                if maybe_player.is_err() {
-                    eprintln!("Could not load player");
-                    return
+            };
+            let player = match entity.as_player_mut() {
+                Ok(player) => player,
+                Err(_) => {
+                    console::log("Could not load player");
+                    continue;
                 }
-                let player = maybe_player.unwrap();
+            };
 
-                let packet = ClientboundPacket::KeepAlive(KeepAlivePacket::for_player(player));
-                x.send_packet(packet.clone()).unwrap()
-            });
+            let packet = ClientboundPacket::KeepAlive(KeepAlivePacket::for_player(player));
+            if let Err(e) = client_handler.send_packet(packet) {
+                console::log(&format!("Could not send keepalive to player {}: {:?}", player_eid, e));
+            }
+        }
+    }
+
+    // Called when a connection is closed, takes the player out of the world and tells everyone
+    pub fn on_disconnect(&self, client_handler: &ClientHandler) {
+        let player_eid = match client_handler.player_eid() {
+            Some(player_eid) => player_eid,
+            None => {
+                // Nobody saw a player that did not get past login, it only has to leave the world
+                if let Some(player_eid) = client_handler.login_player_eid() {
+                    let server_data_lock = self.data.lock().expect("Could not lock server data");
+                    if let Err(e) = server_data_lock.remove_player(player_eid) {
+                        console::log(&format!("Could not remove player {}: {:?}", player_eid, e));
+                    }
+                }
+                return;
+            }
+        };
+
+        let server_data_lock = self.data.lock().expect("Could not lock server data");
+        let player = match server_data_lock.remove_player(player_eid) {
+            Ok(Some(player)) => player,
+            Ok(None) => return,
+            Err(e) => {
+                console::log(&format!("Could not remove player {}: {:?}", player_eid, e));
+                return;
+            }
+        };
+
+        if let Err(e) = server_data_lock.save_player(&player) {
+            console::log(&format!("Could not save player {}: {:?}", player.username, e));
+        }
+
+        self.send_to_all(ClientboundPacket::PlayerInfo(PlayerInfoPacket::RemovePlayer(
+            vec![player.uuid]
+        )));
+        self.send_to_all(ClientboundPacket::DestroyEntities(DestroyEntitiesPacket {
+            entity_ids: vec![player_eid],
+        }));
+        console::log(&format!("{} left the game", player.username));
+        self.send_to_all(ClientboundPacket::ChatMessage(ChatMessagePacket {
+            message: Chat::translate("multiplayer.player.left", vec![player.display_name()])
+                .color("yellow".to_string()),
+            sender: Uuid::nil(),
+            position: ChatPosition::SystemMessage,
+        }));
     }
 }
 
@@ -143,6 +432,7 @@ fn main() {
     let server = Server {
         data: Arc::new(Mutex::new(ServerData::new())),
         connections: Arc::new(Mutex::new(HashMap::new())),
+        scheduler: Scheduler::new(),
     };
     server.run();
 }
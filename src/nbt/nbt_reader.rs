@@ -20,7 +20,8 @@ impl<S: Read> NBTReader<S> {
     }
 
     pub fn read_varint(&mut self) -> Result<isize, ErrorType> {
-        self.read_var(5)
+        // VarInts are signed 32 bit numbers, negative ones use all 5 bytes
+        Ok(self.read_var(5)? as i32 as isize)
     }
 
     pub fn read_varlong(&mut self) -> Result<isize, ErrorType> {
@@ -1,5 +1,7 @@
 use crate::world::Difficulty;
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
 
 use super::Clientbound;
 
@@ -10,9 +12,9 @@ pub struct ChangeDifficultyPacket {
 }
 
 impl Clientbound for ChangeDifficultyPacket {
-    fn writer(&self) -> PacketWriter {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
         // This changed to 0x0C in newer versions
-        let mut writer = PacketWriter::new(0x0D);
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::ChangeDifficulty));
 
         writer.add_unsigned_byte(self.difficulty.into());
         writer.add_boolean(self.difficulty_locked);
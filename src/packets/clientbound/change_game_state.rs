@@ -0,0 +1,40 @@
+use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
+
+use crate::player::Gamemode;
+
+use super::Clientbound;
+
+#[derive(Debug, Clone, Copy)]
+pub enum GameStateChange {
+    EndRaining,
+    BeginRaining,
+    ChangeGamemode(Gamemode),
+    // From 0 (clear) to 1 (full rain or thunder)
+    RainLevelChange(f32),
+    ThunderLevelChange(f32),
+}
+
+#[derive(Debug, Clone)]
+pub struct ChangeGameStatePacket {
+    pub change: GameStateChange,
+}
+
+impl Clientbound for ChangeGameStatePacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::ChangeGameState));
+
+        let (reason, value) = match self.change {
+            GameStateChange::EndRaining => (1, 0.0),
+            GameStateChange::BeginRaining => (2, 0.0),
+            GameStateChange::ChangeGamemode(gamemode) => (3, gamemode as i32 as f32),
+            GameStateChange::RainLevelChange(level) => (7, level),
+            GameStateChange::ThunderLevelChange(level) => (8, level),
+        };
+        writer.add_unsigned_byte(reason);
+        writer.add_float(value);
+
+        writer
+    }
+}
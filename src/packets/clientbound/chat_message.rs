@@ -1,5 +1,7 @@
 use super::super::Clientbound;
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
 
 use crate::chat::Chat;
 use crate::chat::ChatPosition;
@@ -15,8 +17,8 @@ pub struct ChatMessagePacket {
 }
 
 impl Clientbound for ChatMessagePacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x0E);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::ChatMessage));
         writer.add_json(json!(self.message));
         writer.add_signed_byte(self.position.clone().into());
         writer.add_uuid(self.sender);
@@ -2,14 +2,16 @@ use std::convert::TryInto;
 use std::fmt::Debug;
 
 use super::Clientbound;
+use crate::console;
 use crate::packets::packet_writer::PacketWriter;
-use crate::world::ChunkColumn;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
+use crate::world::{ChunkColumn, ChunkPosition};
 use crate::nbt::{NBTTag, NamedNBTTag};
 
 #[derive(Clone)]
 pub struct ChunkDataPacket {
-    x: i32,
-    z: i32,
+    position: ChunkPosition,
     full_chunk: bool,
     primary_bitmask: i32,
     heightmaps: NBTTag,
@@ -21,8 +23,8 @@ pub struct ChunkDataPacket {
 impl Debug for ChunkDataPacket {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_map()
-            .entry(&"x", &format!("{}", self.x))
-            .entry(&"z", &format!("{}", self.z))
+            .entry(&"x", &format!("{}", self.position.x))
+            .entry(&"z", &format!("{}", self.position.z))
             .entry(&"full_chunk", &format!("{}", self.full_chunk))
             .entry(&"primary_bitmask", &format!("{:016b}", self.primary_bitmask))
             .entry(&"heightmaps", &"<stripped>")
@@ -34,17 +36,17 @@ impl Debug for ChunkDataPacket {
 }
 
 impl Clientbound for ChunkDataPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x20);
-        writer.add_signed_int(self.x);
-        writer.add_signed_int(self.z);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::ChunkData));
+        writer.add_signed_int(self.position.x);
+        writer.add_signed_int(self.position.z);
         writer.add_boolean(self.full_chunk);
         writer.add_varint(self.primary_bitmask);
         writer.add_named_nbt(&NamedNBTTag::new("", self.heightmaps.clone()));
 
         if self.full_chunk {
             if self.biomes.len() != 1024 {
-                eprintln!("INVALID BIOME DATA: expected 1024 biome entries, but got {}.", self.biomes.len())
+                console::log(&format!("INVALID BIOME DATA: expected 1024 biome entries, but got {}.", self.biomes.len()))
             }
             writer.add_varint(self.biomes.len().try_into().unwrap());
             for biome in self.biomes.iter() {
@@ -67,11 +69,7 @@ impl Clientbound for ChunkDataPacket {
 }
 
 impl ChunkDataPacket {
-    pub fn from_chunk_column(
-        x: i32,
-        z: i32,
-        column: ChunkColumn
-    ) -> Self {
+    pub fn from_chunk_column(column: &ChunkColumn) -> Self {
         let mut primary_bitmask = 0;
         let mut sections = vec![];
         for (i, maybe_section) in column.get_sections().iter().enumerate() {
@@ -82,8 +80,7 @@ impl ChunkDataPacket {
         }
         
         Self {
-            x,
-            z,
+            position: column.position,
             full_chunk: true,
             primary_bitmask,
             heightmaps: column.get_heightmaps(),
@@ -1,6 +1,8 @@
 use std::convert::TryInto;
 
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
 use crate::server::CommandNode;
 
 use super::Clientbound;
@@ -12,13 +14,13 @@ pub struct DeclareCommandsPacket {
 }
 
 impl Clientbound for DeclareCommandsPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x10);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::DeclareCommands));
 
         writer.add_varint(self.nodes.len().try_into().unwrap());
 
         for node in self.nodes.iter() {
-            node.write(&mut writer);
+            node.write(&mut writer, protocol);
         }
 
         writer.add_varint(self.root_node);
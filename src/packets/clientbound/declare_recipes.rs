@@ -2,6 +2,8 @@ use std::convert::TryInto;
 
 use crate::server::Recipe;
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
 
 use super::Clientbound;
 
@@ -11,8 +13,8 @@ pub struct DeclareRecipesPacket {
 }
 
 impl Clientbound for DeclareRecipesPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x5A);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::DeclareRecipes));
 
         writer.add_varint(self.recipes.len().try_into().unwrap());
         for recipe in self.recipes.iter() {
@@ -0,0 +1,22 @@
+use std::convert::TryInto;
+
+use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+use super::super::Clientbound;
+
+#[derive(Debug, Clone)]
+pub struct DestroyEntitiesPacket {
+    pub entity_ids: Vec<i32>,
+}
+
+impl Clientbound for DestroyEntitiesPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::DestroyEntities));
+        writer.add_varint(self.entity_ids.len().try_into().unwrap());
+        for entity_id in self.entity_ids.iter() {
+            writer.add_varint(*entity_id);
+        }
+        writer
+    }
+}
@@ -0,0 +1,21 @@
+use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+use super::super::Clientbound;
+
+use crate::chat::Chat;
+
+use serde_json::json;
+
+#[derive(Debug, Clone)]
+pub struct DisconnectPacket {
+    pub reason: Chat,
+}
+
+impl Clientbound for DisconnectPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::Disconnect));
+        writer.add_json(json!(self.reason));
+        writer
+    }
+}
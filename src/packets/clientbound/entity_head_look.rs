@@ -0,0 +1,19 @@
+use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+use super::super::Clientbound;
+
+#[derive(Debug, Clone)]
+pub struct EntityHeadLookPacket {
+    pub entity_id: i32,
+    pub head_yaw: f32,
+}
+
+impl Clientbound for EntityHeadLookPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::EntityHeadLook));
+        writer.add_varint(self.entity_id);
+        writer.add_angle(self.head_yaw);
+        writer
+    }
+}
@@ -0,0 +1,32 @@
+use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+use super::super::Clientbound;
+
+// Deltas are in 1/4096th of a block, so this can only move an entity less than 8 blocks
+#[derive(Debug, Clone)]
+pub struct EntityPositionAndRotationPacket {
+    pub entity_id: i32,
+    pub delta_x: i16,
+    pub delta_y: i16,
+    pub delta_z: i16,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+impl Clientbound for EntityPositionAndRotationPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(
+            protocol.clientbound_id(ClientboundPacketId::EntityPositionAndRotation)
+        );
+        writer.add_varint(self.entity_id);
+        writer.add_signed_short(self.delta_x);
+        writer.add_signed_short(self.delta_y);
+        writer.add_signed_short(self.delta_z);
+        writer.add_angle(self.yaw);
+        writer.add_angle(self.pitch);
+        writer.add_boolean(self.on_ground);
+        writer
+    }
+}
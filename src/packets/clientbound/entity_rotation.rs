@@ -0,0 +1,23 @@
+use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+use super::super::Clientbound;
+
+#[derive(Debug, Clone)]
+pub struct EntityRotationPacket {
+    pub entity_id: i32,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+impl Clientbound for EntityRotationPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::EntityRotation));
+        writer.add_varint(self.entity_id);
+        writer.add_angle(self.yaw);
+        writer.add_angle(self.pitch);
+        writer.add_boolean(self.on_ground);
+        writer
+    }
+}
@@ -1,4 +1,8 @@
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
+
+use crate::player::OPLevel;
 
 use super::Clientbound;
 
@@ -116,6 +120,19 @@ pub enum EntityStatus {
     LivingEntityFeetBreak,
 }
 
+impl EntityStatus {
+    // Tells the client which commands it may try, for things like F3 + F4
+    pub fn for_op_level(op_level: OPLevel) -> Self {
+        match op_level {
+            OPLevel::Player => EntityStatus::PlayerOPLevel0,
+            OPLevel::Moderator => EntityStatus::PlayerOPLevel1,
+            OPLevel::Gamemaster => EntityStatus::PlayerOPLevel2,
+            OPLevel::Admin => EntityStatus::PlayerOPLevel3,
+            OPLevel::Owner => EntityStatus::PlayerOPLevel4,
+        }
+    }
+}
+
 impl Into<u8> for EntityStatus {
     fn into(self) -> u8 {
         match self {
@@ -188,8 +205,8 @@ pub struct EntityStatusPacket {
 }
 
 impl Clientbound for EntityStatusPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x1A);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::EntityStatus));
 
         writer.add_signed_int(self.entity_id);
         writer.add_unsigned_byte(self.entity_status.into());
@@ -0,0 +1,29 @@
+use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+use super::super::Clientbound;
+
+#[derive(Debug, Clone)]
+pub struct EntityTeleportPacket {
+    pub entity_id: i32,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+impl Clientbound for EntityTeleportPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::EntityTeleport));
+        writer.add_varint(self.entity_id);
+        writer.add_signed_double(self.x);
+        writer.add_signed_double(self.y);
+        writer.add_signed_double(self.z);
+        writer.add_angle(self.yaw);
+        writer.add_angle(self.pitch);
+        writer.add_boolean(self.on_ground);
+        writer
+    }
+}
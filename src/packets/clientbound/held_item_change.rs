@@ -2,6 +2,8 @@ use crate::player::Player;
 
 use super::super::Clientbound;
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
 
 #[derive(Debug, Clone)]
 pub struct HeldItemChangePacket {
@@ -9,8 +11,8 @@ pub struct HeldItemChangePacket {
 }
 
 impl Clientbound for HeldItemChangePacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x3F);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::HeldItemChange));
         writer.add_unsigned_byte(self.slot);
         writer
     }
@@ -1,4 +1,6 @@
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
 use super::super::Clientbound;
 
 use crate::nbt::NamedNBTTag;
@@ -28,8 +30,8 @@ pub struct JoinGamePacket {
 }
 
 impl Clientbound for JoinGamePacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x24);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::JoinGame));
         writer.add_signed_int(self.entity_id);
         writer.add_unsigned_byte(self.is_hardcore as u8);
         writer.add_unsigned_byte(self.gamemode as u8);
@@ -6,6 +6,8 @@ use crate::player::Player;
 
 use super::super::Clientbound;
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
 
 #[derive(Debug, Clone)]
 pub struct KeepAlivePacket {
@@ -13,8 +15,8 @@ pub struct KeepAlivePacket {
 }
 
 impl Clientbound for KeepAlivePacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x1F);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::KeepAlive));
         writer.add_signed_long(self.id);
         writer
     }
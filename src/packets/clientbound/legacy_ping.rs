@@ -1,4 +1,5 @@
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ProtocolVersion;
 use super::super::Clientbound;
 
 #[derive(Debug, Clone)]
@@ -12,7 +13,7 @@ pub struct LegacyPingClientboundPacket {
 }
 
 impl Clientbound for LegacyPingClientboundPacket {
-    fn writer(&self) -> PacketWriter {
+    fn writer(&self, _protocol: &ProtocolVersion) -> PacketWriter {
         let protocol_version_string = format!("{}", self.protocol_version);
         let curr_player_count_string = format!("{}", self.curr_player_count);
         let max_player_count_string = format!("{}", self.max_player_count);
@@ -1,4 +1,6 @@
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
 use super::super::Clientbound;
 
 use uuid::Uuid;
@@ -10,8 +12,8 @@ pub struct LoginSuccessPacket {
 }
 
 impl Clientbound for LoginSuccessPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x02);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::LoginSuccess));
         writer.add_uuid(self.uuid);
         writer.add_string(&self.username);
         writer
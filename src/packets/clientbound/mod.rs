@@ -18,6 +18,20 @@ pub mod tags;
 pub mod entity_status;
 pub mod declare_commands;
 pub mod player_info;
+pub mod login_disconnect;
+pub mod destroy_entities;
+pub mod spawn_player;
+pub mod entity_position;
+pub mod entity_position_and_rotation;
+pub mod entity_rotation;
+pub mod entity_head_look;
+pub mod entity_teleport;
+pub mod unload_chunk;
+pub mod time_update;
+pub mod change_game_state;
+pub mod tab_complete;
+pub mod disconnect;
+pub mod set_slot;
 
 pub use chat_message::*;
 pub use held_item_change::*;
@@ -39,8 +53,23 @@ pub use tags::*;
 pub use entity_status::*;
 pub use declare_commands::*;
 pub use player_info::*;
+pub use login_disconnect::*;
+pub use destroy_entities::*;
+pub use spawn_player::*;
+pub use entity_position::*;
+pub use entity_position_and_rotation::*;
+pub use entity_rotation::*;
+pub use entity_head_look::*;
+pub use entity_teleport::*;
+pub use unload_chunk::*;
+pub use time_update::*;
+pub use change_game_state::*;
+pub use tab_complete::*;
+pub use disconnect::*;
+pub use set_slot::*;
 
 use super::packet_writer::PacketWriter;
+use super::protocol::ProtocolVersion;
 
 #[derive(Debug, Clone)]
 pub enum ClientboundPacket {
@@ -64,35 +93,63 @@ pub enum ClientboundPacket {
     EntityStatus(EntityStatusPacket),
     DeclareCommands(DeclareCommandsPacket),
     PlayerInfo(PlayerInfoPacket),
+    LoginDisconnect(LoginDisconnectPacket),
+    DestroyEntities(DestroyEntitiesPacket),
+    SpawnPlayer(SpawnPlayerPacket),
+    EntityPosition(EntityPositionPacket),
+    EntityPositionAndRotation(EntityPositionAndRotationPacket),
+    EntityRotation(EntityRotationPacket),
+    EntityHeadLook(EntityHeadLookPacket),
+    EntityTeleport(EntityTeleportPacket),
+    UnloadChunk(UnloadChunkPacket),
+    TimeUpdate(TimeUpdatePacket),
+    ChangeGameState(ChangeGameStatePacket),
+    TabComplete(TabCompletePacket),
+    Disconnect(DisconnectPacket),
+    SetSlot(SetSlotPacket),
 }
 
 pub trait Clientbound {
-    fn writer(&self) -> PacketWriter;
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter;
 }
 
 impl Clientbound for ClientboundPacket {
-    fn writer(&self) -> PacketWriter {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
         match self {
-            ClientboundPacket::LegacyPing(p) => p.writer(),
-            ClientboundPacket::StatusResponse(p) => p.writer(),
-            ClientboundPacket::Pong(p) => p.writer(),
-            ClientboundPacket::LoginSuccess(p) => p.writer(),
-            ClientboundPacket::JoinGame(p) => p.writer(),
-            ClientboundPacket::HeldItemChange(p) => p.writer(),
-            ClientboundPacket::PlayerPositionAndLook(p) => p.writer(),
-            ClientboundPacket::ChatMessage(p) => p.writer(),
-            ClientboundPacket::KeepAlive(p) => p.writer(),
-            ClientboundPacket::ChunkData(p) => p.writer(),
-            ClientboundPacket::UpdateViewPosition(p) => p.writer(),
-            ClientboundPacket::PluginMessage(p) => p.writer(),
-            ClientboundPacket::ChangeDifficulty(p) => p.writer(),
-            ClientboundPacket::PlayerAbilities(p) => p.writer(),
-            ClientboundPacket::DeclareRecipes(p) => p.writer(),
-            ClientboundPacket::UnlockRecipes(p) => p.writer(),
-            ClientboundPacket::Tags(p) => p.writer(),
-            ClientboundPacket::EntityStatus(p) => p.writer(),
-            ClientboundPacket::DeclareCommands(p) => p.writer(),
-            ClientboundPacket::PlayerInfo(p) => p.writer(),
+            ClientboundPacket::LegacyPing(p) => p.writer(protocol),
+            ClientboundPacket::StatusResponse(p) => p.writer(protocol),
+            ClientboundPacket::Pong(p) => p.writer(protocol),
+            ClientboundPacket::LoginSuccess(p) => p.writer(protocol),
+            ClientboundPacket::JoinGame(p) => p.writer(protocol),
+            ClientboundPacket::HeldItemChange(p) => p.writer(protocol),
+            ClientboundPacket::PlayerPositionAndLook(p) => p.writer(protocol),
+            ClientboundPacket::ChatMessage(p) => p.writer(protocol),
+            ClientboundPacket::KeepAlive(p) => p.writer(protocol),
+            ClientboundPacket::ChunkData(p) => p.writer(protocol),
+            ClientboundPacket::UpdateViewPosition(p) => p.writer(protocol),
+            ClientboundPacket::PluginMessage(p) => p.writer(protocol),
+            ClientboundPacket::ChangeDifficulty(p) => p.writer(protocol),
+            ClientboundPacket::PlayerAbilities(p) => p.writer(protocol),
+            ClientboundPacket::DeclareRecipes(p) => p.writer(protocol),
+            ClientboundPacket::UnlockRecipes(p) => p.writer(protocol),
+            ClientboundPacket::Tags(p) => p.writer(protocol),
+            ClientboundPacket::EntityStatus(p) => p.writer(protocol),
+            ClientboundPacket::DeclareCommands(p) => p.writer(protocol),
+            ClientboundPacket::PlayerInfo(p) => p.writer(protocol),
+            ClientboundPacket::LoginDisconnect(p) => p.writer(protocol),
+            ClientboundPacket::DestroyEntities(p) => p.writer(protocol),
+            ClientboundPacket::SpawnPlayer(p) => p.writer(protocol),
+            ClientboundPacket::EntityPosition(p) => p.writer(protocol),
+            ClientboundPacket::EntityPositionAndRotation(p) => p.writer(protocol),
+            ClientboundPacket::EntityRotation(p) => p.writer(protocol),
+            ClientboundPacket::EntityHeadLook(p) => p.writer(protocol),
+            ClientboundPacket::EntityTeleport(p) => p.writer(protocol),
+            ClientboundPacket::UnloadChunk(p) => p.writer(protocol),
+            ClientboundPacket::TimeUpdate(p) => p.writer(protocol),
+            ClientboundPacket::ChangeGameState(p) => p.writer(protocol),
+            ClientboundPacket::TabComplete(p) => p.writer(protocol),
+            ClientboundPacket::Disconnect(p) => p.writer(protocol),
+            ClientboundPacket::SetSlot(p) => p.writer(protocol),
         }
     }
 }
@@ -1,5 +1,7 @@
 use crate::player::{Abilities, Player};
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
 
 use super::Clientbound;
 
@@ -11,8 +13,8 @@ pub struct PlayerAbilitiesPacket {
 }
 
 impl Clientbound for PlayerAbilitiesPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x30);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::PlayerAbilities));
 
         writer.add_unsigned_byte(self.abilities.value);
         writer.add_float(self.flying_speed);
@@ -5,6 +5,8 @@ use uuid::Uuid;
 
 use super::Clientbound;
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
 use crate::player::{Gamemode, Player};
 use crate::chat::Chat;
 
@@ -34,8 +36,8 @@ pub enum PlayerInfoPacket {
 }
 
 impl Clientbound for PlayerInfoPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x32);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::PlayerInfo));
 
         let action_id = match self {
             PlayerInfoPacket::AddPlayer(_) => 0,
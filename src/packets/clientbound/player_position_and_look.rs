@@ -1,5 +1,9 @@
 use super::super::Clientbound;
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+
+use crate::player::Player;
 
 #[derive(Debug, Clone)]
 pub enum ValueType<T> {
@@ -33,8 +37,21 @@ pub struct PlayerPositionAndLookPacket {
     pub teleport_id: i32,
 }
 
+impl PlayerPositionAndLookPacket {
+    pub fn from_player(player: &Player, teleport_id: i32) -> Self {
+        Self {
+            x: ValueType::Absolute(player.position.x),
+            y: ValueType::Absolute(player.position.y),
+            z: ValueType::Absolute(player.position.z),
+            yaw: ValueType::Absolute(player.look.yaw),
+            pitch: ValueType::Absolute(player.look.pitch),
+            teleport_id,
+        }
+    }
+}
+
 impl Clientbound for PlayerPositionAndLookPacket {
-    fn writer(&self) -> PacketWriter {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
         let mut flags: u8 = 0;
         if self.x.is_relative() {
             flags |= 0x01;
@@ -52,7 +69,7 @@ impl Clientbound for PlayerPositionAndLookPacket {
             flags |= 0x10;
         }
 
-        let mut writer = PacketWriter::new(0x34);
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::PlayerPositionAndLook));
         writer.add_signed_double(*self.x.get_value());
         writer.add_signed_double(*self.y.get_value());
         writer.add_signed_double(*self.z.get_value());
@@ -1,4 +1,6 @@
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
 
 use super::Clientbound;
 
@@ -9,8 +11,8 @@ pub struct PluginMessagePacket {
 }
 
 impl Clientbound for PluginMessagePacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x17);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::PluginMessage));
 
         writer.add_string(&self.channel);
         self.data.iter().for_each(|b| writer.add_unsigned_byte(*b));
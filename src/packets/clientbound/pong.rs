@@ -1,5 +1,7 @@
 use super::super::Clientbound;
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
 
 #[derive(Debug, Clone)]
 pub struct PongPacket {
@@ -7,8 +9,8 @@ pub struct PongPacket {
 }
 
 impl Clientbound for PongPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x01);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::Pong));
         writer.add_signed_long(self.payload);
         writer
     }
@@ -0,0 +1,24 @@
+use crate::server::Slot;
+
+use super::super::Clientbound;
+use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+
+#[derive(Debug, Clone)]
+pub struct SetSlotPacket {
+    // 0 is the inventory of the player
+    pub window_id: i8,
+    pub slot: i16,
+    pub item: Slot,
+}
+
+impl Clientbound for SetSlotPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::SetSlot));
+        writer.add_signed_byte(self.window_id);
+        writer.add_signed_short(self.slot);
+        self.item.write(&mut writer);
+        writer
+    }
+}
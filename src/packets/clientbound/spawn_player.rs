@@ -0,0 +1,47 @@
+use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
+use super::super::Clientbound;
+
+use crate::player::Player;
+
+use uuid::Uuid;
+
+#[derive(Debug, Clone)]
+pub struct SpawnPlayerPacket {
+    pub entity_id: i32,
+    pub uuid: Uuid,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl Clientbound for SpawnPlayerPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::SpawnPlayer));
+        writer.add_varint(self.entity_id);
+        writer.add_uuid(self.uuid);
+        writer.add_signed_double(self.x);
+        writer.add_signed_double(self.y);
+        writer.add_signed_double(self.z);
+        writer.add_angle(self.yaw);
+        writer.add_angle(self.pitch);
+        writer
+    }
+}
+
+impl SpawnPlayerPacket {
+    pub fn from_player(entity_id: i32, player: &Player) -> Self {
+        Self {
+            entity_id,
+            uuid: player.uuid,
+            x: player.position.x,
+            y: player.position.y,
+            z: player.position.z,
+            yaw: player.look.yaw,
+            pitch: player.look.pitch,
+        }
+    }
+}
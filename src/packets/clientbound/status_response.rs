@@ -1,4 +1,6 @@
 use super::super::packet_writer::PacketWriter;
+use super::super::protocol::ClientboundPacketId;
+use super::super::protocol::ProtocolVersion;
 use super::super::Clientbound;
 
 use crate::chat::Chat;
@@ -34,8 +36,8 @@ pub struct StatusResponsePacket {
 }
 
 impl Clientbound for StatusResponsePacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x00);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::StatusResponse));
         let status_json = json!({
             "version": {
                 "name": self.version_name,
@@ -0,0 +1,40 @@
+use std::convert::TryInto;
+
+use serde_json::json;
+
+use crate::chat::Chat;
+use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
+
+use super::Clientbound;
+
+#[derive(Debug, Clone)]
+pub struct TabCompletePacket {
+    pub transaction_id: i32,
+    // The part of the text the matches replace
+    pub start: i32,
+    pub length: i32,
+    // Match, Tooltip
+    pub matches: Vec<(String, Option<Chat>)>,
+}
+
+impl Clientbound for TabCompletePacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::TabComplete));
+
+        writer.add_varint(self.transaction_id);
+        writer.add_varint(self.start);
+        writer.add_varint(self.length);
+        writer.add_varint(self.matches.len().try_into().unwrap());
+        for (text, tooltip) in self.matches.iter() {
+            writer.add_string(text);
+            writer.add_boolean(tooltip.is_some());
+            if let Some(tooltip) = tooltip {
+                writer.add_json(json!(tooltip));
+            }
+        }
+
+        writer
+    }
+}
@@ -1,6 +1,8 @@
 use std::convert::TryInto;
 
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
 use crate::server::{Tag, Tags};
 
 use super::Clientbound;
@@ -14,8 +16,8 @@ pub struct TagsPacket {
 }
 
 impl Clientbound for TagsPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x5B);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::Tags));
 
         writer.add_varint(self.block_tags.len().try_into().unwrap());
         for tag in self.block_tags.iter() {
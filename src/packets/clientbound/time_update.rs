@@ -0,0 +1,23 @@
+use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
+
+use super::Clientbound;
+
+#[derive(Debug, Clone)]
+pub struct TimeUpdatePacket {
+    pub world_age: i64,
+    // Negative values stop the sun from moving on the client
+    pub time_of_day: i64,
+}
+
+impl Clientbound for TimeUpdatePacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::TimeUpdate));
+
+        writer.add_signed_long(self.world_age);
+        writer.add_signed_long(self.time_of_day);
+
+        writer
+    }
+}
@@ -0,0 +1,22 @@
+use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
+use crate::world::ChunkPosition;
+
+use super::Clientbound;
+
+#[derive(Debug, Clone)]
+pub struct UnloadChunkPacket {
+    pub position: ChunkPosition,
+}
+
+impl Clientbound for UnloadChunkPacket {
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::UnloadChunk));
+
+        writer.add_signed_int(self.position.x);
+        writer.add_signed_int(self.position.z);
+
+        writer
+    }
+}
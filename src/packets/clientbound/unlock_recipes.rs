@@ -2,7 +2,10 @@ use std::convert::TryInto;
 
 use super::Clientbound;
 
+use crate::console;
 use crate::{packets::packet_writer::PacketWriter, player::Player};
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum UnlockRecipesAction {
@@ -37,8 +40,8 @@ pub struct UnlockRecipesPacket {
 }
 
 impl Clientbound for UnlockRecipesPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x35);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::UnlockRecipes));
 
         writer.add_unsigned_byte(self.action.into());
         writer.add_boolean(self.crafting_table_open);
@@ -61,10 +64,10 @@ impl Clientbound for UnlockRecipesPacket {
                     writer.add_string(&recipe_id);
                 }
             } else {
-                eprintln!("Missing list 2 for init unlocked recipes!");
+                console::log("Missing list 2 for init unlocked recipes!");
             }
         } else if self.recipes_list2.is_some() {
-            eprintln!("Recipe list 2 should only be used in action Init, not {:?}", self.action);
+            console::log(&format!("Recipe list 2 should only be used in action Init, not {:?}", self.action));
         }
 
         writer
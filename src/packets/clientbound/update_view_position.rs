@@ -1,19 +1,21 @@
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ClientboundPacketId;
+use crate::packets::protocol::ProtocolVersion;
+use crate::world::ChunkPosition;
 
 use super::Clientbound;
 
 #[derive(Debug, Clone)]
 pub struct UpdateViewPositionPacket {
-    pub chunk_x: i32,
-    pub chunk_z: i32,
+    pub position: ChunkPosition,
 }
 
 impl Clientbound for UpdateViewPositionPacket {
-    fn writer(&self) -> PacketWriter {
-        let mut writer = PacketWriter::new(0x40);
+    fn writer(&self, protocol: &ProtocolVersion) -> PacketWriter {
+        let mut writer = PacketWriter::new(protocol.clientbound_id(ClientboundPacketId::UpdateViewPosition));
 
-        writer.add_varint(self.chunk_x);
-        writer.add_varint(self.chunk_z);
+        writer.add_varint(self.position.x);
+        writer.add_varint(self.position.z);
 
         writer
     }
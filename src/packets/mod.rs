@@ -1,6 +1,7 @@
 pub mod clientbound;
 pub mod packet_reader;
 pub mod packet_writer;
+pub mod protocol;
 pub mod serverbound;
 
 use clientbound::*;
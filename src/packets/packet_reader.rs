@@ -1,3 +1,5 @@
+use super::protocol::ProtocolVersion;
+use super::protocol::ServerboundPacketId;
 use super::serverbound::*;
 
 use crate::client_handler::ConnectionStateTag;
@@ -12,101 +14,89 @@ impl PacketReader {
     pub fn read_packet(
         &mut self,
         state: &ConnectionStateTag,
+        protocol: &ProtocolVersion,
     ) -> Result<ServerboundPacket, ErrorType> {
         // Length set to MAX because we do not know the length yet, will be reset after the first
         // VarInt is read
         self.curr_packet_length = isize::MAX;
-        match state {
-            ConnectionStateTag::Handshaking => self.read_handshaking_packet(),
-            ConnectionStateTag::Status => self.read_status_packet(),
-            ConnectionStateTag::Login => self.read_login_packet(),
-            ConnectionStateTag::Play => self.read_play_packet(),
-            ConnectionStateTag::Exit => unreachable!(),
-        }
-    }
-
-    fn read_handshaking_packet(&mut self) -> Result<ServerboundPacket, ErrorType> {
-        if self.peek_byte()? == 0xfe {
+        if *state == ConnectionStateTag::Handshaking && self.peek_byte()? == 0xfe {
             // Length set to MAX because we cannot know this in advance
             self.curr_packet_length = isize::MAX;
             self.curr_packet_index = 0;
-            Ok(ServerboundPacket::LegacyPing(
+            return Ok(ServerboundPacket::LegacyPing(
                 LegacyPingServerboundPacket::from_reader(self)?,
-            ))
-        } else {
-            self.curr_packet_length = self.read_varint()?;
-            self.curr_packet_index = 0;
-            let packet_id = self.read_varint()?;
-            match packet_id {
-                0x00 => Ok(ServerboundPacket::Handshaking(
-                    HandshakingPacket::from_reader(self)?,
-                )),
-                x => Err(ErrorType::Fatal(format!("Invalid packet {:#04x}", x))),
-            }
+            ));
         }
-    }
 
-    fn read_status_packet(&mut self) -> Result<ServerboundPacket, ErrorType> {
         self.curr_packet_length = self.read_varint()?;
         self.curr_packet_index = 0;
         let packet_id = self.read_varint()?;
-        match packet_id {
-            0x00 => Ok(ServerboundPacket::StatusRequest(
-                StatusRequestPacket::from_reader(self)?,
-            )),
-            0x01 => Ok(ServerboundPacket::Ping(PingPacket::from_reader(self)?)),
-            x => Err(ErrorType::Fatal(format!("Invalid packet {:#04x}", x))),
+        match protocol.serverbound_packet(state, packet_id) {
+            Some(id) => self.read_packet_with_id(id),
+            None if *state == ConnectionStateTag::Play => {
+                self.read_until_end()?;
+                Err(ErrorType::Recoverable(format!(
+                    "Unimplemented packet {:#04x}",
+                    packet_id
+                )))
+            }
+            None => Err(ErrorType::Fatal(format!("Invalid packet {:#04x}", packet_id))),
         }
     }
 
-    fn read_login_packet(&mut self) -> Result<ServerboundPacket, ErrorType> {
-        self.curr_packet_length = self.read_varint()?;
-        self.curr_packet_index = 0;
-        let packet_id = self.read_varint()?;
-        match packet_id {
-            0x00 => Ok(ServerboundPacket::LoginStart(
+    fn read_packet_with_id(
+        &mut self,
+        id: ServerboundPacketId,
+    ) -> Result<ServerboundPacket, ErrorType> {
+        match id {
+            ServerboundPacketId::Handshaking => Ok(ServerboundPacket::Handshaking(
+                HandshakingPacket::from_reader(self)?,
+            )),
+            ServerboundPacketId::StatusRequest => Ok(ServerboundPacket::StatusRequest(
+                StatusRequestPacket::from_reader(self)?,
+            )),
+            ServerboundPacketId::Ping => Ok(ServerboundPacket::Ping(
+                PingPacket::from_reader(self)?
+            )),
+            ServerboundPacketId::LoginStart => Ok(ServerboundPacket::LoginStart(
                 LoginStartPacket::from_reader(self)?,
             )),
-            x => Err(ErrorType::Fatal(format!("Invalid packet {:#04x}", x))),
-        }
-    }
-
-    fn read_play_packet(&mut self) -> Result<ServerboundPacket, ErrorType> {
-        self.curr_packet_length = self.read_varint()?;
-        self.curr_packet_index = 0;
-        let packet_id = self.read_varint()?;
-        match packet_id {
-            0x00 => Ok(ServerboundPacket::TeleportConfirm(
+            ServerboundPacketId::TeleportConfirm => Ok(ServerboundPacket::TeleportConfirm(
                 TeleportConfirmPacket::from_reader(self)?
             )),
-            0x03 => Ok(ServerboundPacket::ChatMessage(
+            ServerboundPacketId::ChatMessage => Ok(ServerboundPacket::ChatMessage(
                 ChatMessagePacket::from_reader(self)?
             )),
-            0x05 => Ok(ServerboundPacket::ClientSettings(
+            ServerboundPacketId::ClientSettings => Ok(ServerboundPacket::ClientSettings(
                 ClientSettingsPacket::from_reader(self)?
             )),
-            0x0b => Ok(ServerboundPacket::PluginMessage(
+            ServerboundPacketId::PluginMessage => Ok(ServerboundPacket::PluginMessage(
                 PluginMessagePacket::from_reader(self)?
             )),
-            0x10 => Ok(ServerboundPacket::KeepAlive(
+            ServerboundPacketId::KeepAlive => Ok(ServerboundPacket::KeepAlive(
                 KeepAlivePacket::from_reader(self)?
             )),
-            0x13 => Ok(ServerboundPacket::PlayerPositionAndRotation(
+            ServerboundPacketId::PlayerPositionAndRotation => Ok(ServerboundPacket::PlayerPositionAndRotation(
                 PlayerPositionAndRotationPacket::from_reader(self)?
             )),
-            0x1e => Ok(ServerboundPacket::SetRecipeBookState(
+            ServerboundPacketId::PlayerPosition => Ok(ServerboundPacket::PlayerPosition(
+                PlayerPositionPacket::from_reader(self)?
+            )),
+            ServerboundPacketId::PlayerRotation => Ok(ServerboundPacket::PlayerRotation(
+                PlayerRotationPacket::from_reader(self)?
+            )),
+            ServerboundPacketId::PlayerMovement => Ok(ServerboundPacket::PlayerMovement(
+                PlayerMovementPacket::from_reader(self)?
+            )),
+            ServerboundPacketId::SetRecipeBookState => Ok(ServerboundPacket::SetRecipeBookState(
                 SetRecipeBookStatePacket::from_reader(self)?
             )),
-            0x25 => Ok(ServerboundPacket::HeldItemChange(
+            ServerboundPacketId::HeldItemChange => Ok(ServerboundPacket::HeldItemChange(
                 HeldItemChangePacket::from_reader(self)?
             )),
-            x => {
-                self.read_until_end()?;
-                Err(ErrorType::Recoverable(format!(
-                    "Unimplemented packet {:#04x}",
-                    x
-                )))
-            },
+            ServerboundPacketId::TabComplete => Ok(ServerboundPacket::TabComplete(
+                TabCompletePacket::from_reader(self)?
+            )),
         }
     }
 
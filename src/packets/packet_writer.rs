@@ -28,16 +28,21 @@ impl PacketWriter {
         }
     }
 
+    #[cfg(test)]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
     pub fn write(&self, mut stream: TcpStream) -> Result<(), ErrorType> {
         if self.include_length {
             let mut data = Self::to_varint(self.data.len().try_into().expect("Too much data"));
             data.extend(&self.data);
             stream
-                .write(&data)
+                .write_all(&data)
                 .map_err(|e| ErrorType::Fatal(e.to_string()))?;
         } else {
             stream
-                .write(&self.data)
+                .write_all(&self.data)
                 .map_err(|e| ErrorType::Fatal(e.to_string()))?;
         }
 
@@ -52,6 +57,10 @@ impl PacketWriter {
         self.data.push(byte as u8);
     }
 
+    pub fn add_signed_short(&mut self, value: i16) {
+        self.data.append(&mut value.to_be_bytes().into());
+    }
+
     pub fn add_unsigned_short(&mut self, value: u16) {
         self.data.append(&mut value.to_be_bytes().into());
     }
@@ -68,6 +77,11 @@ impl PacketWriter {
         self.data.append(&mut value.to_be_bytes().into());
     }
 
+    // Angles are sent in steps of 1/256 of a full turn
+    pub fn add_angle(&mut self, degrees: f32) {
+        self.add_unsigned_byte((degrees * 256.0 / 360.0).rem_euclid(256.0) as u8);
+    }
+
     pub fn add_utf16_string(&mut self, value: &String) {
         value
             .encode_utf16()
@@ -0,0 +1,226 @@
+mod v1_16_2;
+
+use crate::client_handler::ConnectionStateTag;
+
+// Every packet the server knows how to write, independent of the id a given version uses for it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClientboundPacketId {
+    StatusResponse,
+    Pong,
+    LoginDisconnect,
+    LoginSuccess,
+    ChangeDifficulty,
+    ChatMessage,
+    DeclareCommands,
+    PluginMessage,
+    EntityStatus,
+    KeepAlive,
+    ChunkData,
+    JoinGame,
+    PlayerAbilities,
+    PlayerInfo,
+    PlayerPositionAndLook,
+    UnlockRecipes,
+    HeldItemChange,
+    UpdateViewPosition,
+    DeclareRecipes,
+    Tags,
+    DestroyEntities,
+    SpawnPlayer,
+    EntityPosition,
+    EntityPositionAndRotation,
+    EntityRotation,
+    EntityHeadLook,
+    EntityTeleport,
+    UnloadChunk,
+    TimeUpdate,
+    ChangeGameState,
+    TabComplete,
+    Disconnect,
+    SetSlot,
+}
+
+// Every packet the server knows how to read, independent of the id a given version uses for it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServerboundPacketId {
+    Handshaking,
+    StatusRequest,
+    Ping,
+    LoginStart,
+    TeleportConfirm,
+    ChatMessage,
+    ClientSettings,
+    PluginMessage,
+    KeepAlive,
+    PlayerPositionAndRotation,
+    PlayerPosition,
+    PlayerRotation,
+    PlayerMovement,
+    SetRecipeBookState,
+    HeldItemChange,
+    TabComplete,
+}
+
+// The packet id tables, multiple protocol versions can share the same one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PacketMapping {
+    V1_16_2,
+}
+
+impl PacketMapping {
+    pub fn clientbound_id(&self, packet: ClientboundPacketId) -> u8 {
+        match self {
+            PacketMapping::V1_16_2 => v1_16_2::clientbound_id(packet),
+        }
+    }
+
+    pub fn serverbound_packet(
+        &self,
+        state: &ConnectionStateTag,
+        id: isize,
+    ) -> Option<ServerboundPacketId> {
+        match self {
+            PacketMapping::V1_16_2 => v1_16_2::serverbound_packet(state, id),
+        }
+    }
+
+    // The minecraft:time argument only has a minimum from 1.19.4 on, before it has no properties
+    pub fn time_argument_has_min(&self) -> bool {
+        match self {
+            PacketMapping::V1_16_2 => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProtocolVersion {
+    pub number: isize,
+    pub name: &'static str,
+    pub mapping: PacketMapping,
+}
+
+// Sorted from oldest to newest
+pub const SUPPORTED_VERSIONS: [ProtocolVersion; 4] = [
+    ProtocolVersion {
+        number: 751,
+        name: "1.16.2",
+        mapping: PacketMapping::V1_16_2,
+    },
+    ProtocolVersion {
+        number: 752,
+        name: "1.16.3-rc1",
+        mapping: PacketMapping::V1_16_2,
+    },
+    ProtocolVersion {
+        number: 753,
+        name: "1.16.3",
+        mapping: PacketMapping::V1_16_2,
+    },
+    ProtocolVersion {
+        number: 754,
+        name: "1.16.5",
+        mapping: PacketMapping::V1_16_2,
+    },
+];
+
+impl ProtocolVersion {
+    pub fn from_number(number: isize) -> Option<Self> {
+        SUPPORTED_VERSIONS
+            .iter()
+            .find(|version| version.number == number)
+            .copied()
+    }
+
+    pub fn oldest() -> Self {
+        SUPPORTED_VERSIONS[0]
+    }
+
+    pub fn latest() -> Self {
+        SUPPORTED_VERSIONS[SUPPORTED_VERSIONS.len() - 1]
+    }
+
+    // Like "1.16.2 - 1.16.5 (protocol 751 - 754)", for telling clients what they can connect with
+    pub fn supported_range() -> String {
+        let (oldest, latest) = (Self::oldest(), Self::latest());
+        format!("{} - {} (protocol {} - {})", oldest.name, latest.name, oldest.number, latest.number)
+    }
+
+    pub fn clientbound_id(&self, packet: ClientboundPacketId) -> u8 {
+        self.mapping.clientbound_id(packet)
+    }
+
+    pub fn serverbound_packet(
+        &self,
+        state: &ConnectionStateTag,
+        id: isize,
+    ) -> Option<ServerboundPacketId> {
+        self.mapping.serverbound_packet(state, id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ClientboundPacketId, PacketMapping, ProtocolVersion, ServerboundPacketId};
+    use crate::client_handler::ConnectionStateTag;
+
+    #[test]
+    fn test_versions() {
+        assert_eq!(ProtocolVersion::from_number(750), None);
+        assert_eq!(ProtocolVersion::from_number(753).map(|version| version.name), Some("1.16.3"));
+        assert_eq!(ProtocolVersion::supported_range(), "1.16.2 - 1.16.5 (protocol 751 - 754)");
+    }
+
+    #[test]
+    fn test_v1_16_2_mapping() {
+        let version = ProtocolVersion::from_number(754).unwrap();
+        assert_eq!(version.mapping, PacketMapping::V1_16_2);
+        assert_eq!(version.clientbound_id(ClientboundPacketId::ChunkData), 0x20);
+        assert_eq!(version.clientbound_id(ClientboundPacketId::DeclareCommands), 0x10);
+        assert_eq!(
+            version.serverbound_packet(&ConnectionStateTag::Play, 0x10),
+            Some(ServerboundPacketId::KeepAlive)
+        );
+        assert_eq!(
+            version.serverbound_packet(&ConnectionStateTag::Login, 0x00),
+            Some(ServerboundPacketId::LoginStart)
+        );
+        assert_eq!(version.serverbound_packet(&ConnectionStateTag::Play, 0x7f), None);
+
+        // Two play packets with the same id would be read as the wrong one by the client
+        let play_packets = [
+            ClientboundPacketId::SpawnPlayer,
+            ClientboundPacketId::ChangeDifficulty,
+            ClientboundPacketId::ChatMessage,
+            ClientboundPacketId::TabComplete,
+            ClientboundPacketId::DeclareCommands,
+            ClientboundPacketId::PluginMessage,
+            ClientboundPacketId::Disconnect,
+            ClientboundPacketId::EntityStatus,
+            ClientboundPacketId::UnloadChunk,
+            ClientboundPacketId::ChangeGameState,
+            ClientboundPacketId::KeepAlive,
+            ClientboundPacketId::ChunkData,
+            ClientboundPacketId::JoinGame,
+            ClientboundPacketId::EntityPosition,
+            ClientboundPacketId::EntityPositionAndRotation,
+            ClientboundPacketId::EntityRotation,
+            ClientboundPacketId::PlayerAbilities,
+            ClientboundPacketId::PlayerInfo,
+            ClientboundPacketId::PlayerPositionAndLook,
+            ClientboundPacketId::UnlockRecipes,
+            ClientboundPacketId::DestroyEntities,
+            ClientboundPacketId::EntityHeadLook,
+            ClientboundPacketId::HeldItemChange,
+            ClientboundPacketId::UpdateViewPosition,
+            ClientboundPacketId::TimeUpdate,
+            ClientboundPacketId::EntityTeleport,
+            ClientboundPacketId::DeclareRecipes,
+            ClientboundPacketId::Tags,
+            ClientboundPacketId::SetSlot,
+        ];
+        let mut ids = play_packets.iter().map(|packet| version.clientbound_id(*packet)).collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), play_packets.len());
+    }
+}
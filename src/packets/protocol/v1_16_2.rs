@@ -0,0 +1,84 @@
+// Packet ids for protocol versions 751 (1.16.2) up to 754 (1.16.4 and 1.16.5), which did not
+// change any of the packets we use.
+
+use super::ClientboundPacketId;
+use super::ServerboundPacketId;
+
+use crate::client_handler::ConnectionStateTag;
+
+pub fn clientbound_id(packet: ClientboundPacketId) -> u8 {
+    match packet {
+        // Status
+        ClientboundPacketId::StatusResponse => 0x00,
+        ClientboundPacketId::Pong => 0x01,
+
+        // Login
+        ClientboundPacketId::LoginDisconnect => 0x00,
+        ClientboundPacketId::LoginSuccess => 0x02,
+
+        // Play
+        ClientboundPacketId::SpawnPlayer => 0x04,
+        ClientboundPacketId::ChangeDifficulty => 0x0D,
+        ClientboundPacketId::ChatMessage => 0x0E,
+        ClientboundPacketId::TabComplete => 0x0F,
+        ClientboundPacketId::DeclareCommands => 0x10,
+        ClientboundPacketId::SetSlot => 0x15,
+        ClientboundPacketId::PluginMessage => 0x17,
+        ClientboundPacketId::Disconnect => 0x19,
+        ClientboundPacketId::EntityStatus => 0x1A,
+        ClientboundPacketId::UnloadChunk => 0x1C,
+        ClientboundPacketId::ChangeGameState => 0x1D,
+        ClientboundPacketId::KeepAlive => 0x1F,
+        ClientboundPacketId::ChunkData => 0x20,
+        ClientboundPacketId::JoinGame => 0x24,
+        ClientboundPacketId::EntityPosition => 0x27,
+        ClientboundPacketId::EntityPositionAndRotation => 0x28,
+        ClientboundPacketId::EntityRotation => 0x29,
+        ClientboundPacketId::PlayerAbilities => 0x30,
+        ClientboundPacketId::PlayerInfo => 0x32,
+        ClientboundPacketId::PlayerPositionAndLook => 0x34,
+        ClientboundPacketId::UnlockRecipes => 0x35,
+        ClientboundPacketId::DestroyEntities => 0x36,
+        ClientboundPacketId::EntityHeadLook => 0x3A,
+        ClientboundPacketId::HeldItemChange => 0x3F,
+        ClientboundPacketId::UpdateViewPosition => 0x40,
+        ClientboundPacketId::TimeUpdate => 0x4E,
+        ClientboundPacketId::EntityTeleport => 0x56,
+        ClientboundPacketId::DeclareRecipes => 0x5A,
+        ClientboundPacketId::Tags => 0x5B,
+    }
+}
+
+pub fn serverbound_packet(state: &ConnectionStateTag, id: isize) -> Option<ServerboundPacketId> {
+    match state {
+        ConnectionStateTag::Handshaking => match id {
+            0x00 => Some(ServerboundPacketId::Handshaking),
+            _ => None,
+        },
+        ConnectionStateTag::Status => match id {
+            0x00 => Some(ServerboundPacketId::StatusRequest),
+            0x01 => Some(ServerboundPacketId::Ping),
+            _ => None,
+        },
+        ConnectionStateTag::Login => match id {
+            0x00 => Some(ServerboundPacketId::LoginStart),
+            _ => None,
+        },
+        ConnectionStateTag::Play => match id {
+            0x00 => Some(ServerboundPacketId::TeleportConfirm),
+            0x03 => Some(ServerboundPacketId::ChatMessage),
+            0x05 => Some(ServerboundPacketId::ClientSettings),
+            0x06 => Some(ServerboundPacketId::TabComplete),
+            0x0b => Some(ServerboundPacketId::PluginMessage),
+            0x10 => Some(ServerboundPacketId::KeepAlive),
+            0x12 => Some(ServerboundPacketId::PlayerPosition),
+            0x13 => Some(ServerboundPacketId::PlayerPositionAndRotation),
+            0x14 => Some(ServerboundPacketId::PlayerRotation),
+            0x15 => Some(ServerboundPacketId::PlayerMovement),
+            0x1e => Some(ServerboundPacketId::SetRecipeBookState),
+            0x25 => Some(ServerboundPacketId::HeldItemChange),
+            _ => None,
+        },
+        ConnectionStateTag::Exit => None,
+    }
+}
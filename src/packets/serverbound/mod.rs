@@ -9,8 +9,12 @@ pub mod status_request;
 pub mod plugin_message;
 pub mod teleport_confirm;
 pub mod player_position_and_rotation;
+pub mod player_position;
+pub mod player_rotation;
+pub mod player_movement;
 pub mod held_item_change;
 pub mod set_recipe_book_state;
+pub mod tab_complete;
 
 pub use chat_message::*;
 pub use client_settings::*;
@@ -23,8 +27,12 @@ pub use status_request::*;
 pub use plugin_message::*;
 pub use teleport_confirm::*;
 pub use player_position_and_rotation::*;
+pub use player_position::*;
+pub use player_rotation::*;
+pub use player_movement::*;
 pub use held_item_change::*;
 pub use set_recipe_book_state::*;
+pub use tab_complete::*;
 
 use super::packet_reader::PacketReader;
 
@@ -43,8 +51,12 @@ pub enum ServerboundPacket {
     PluginMessage(PluginMessagePacket),
     TeleportConfirm(TeleportConfirmPacket),
     PlayerPositionAndRotation(PlayerPositionAndRotationPacket),
+    PlayerPosition(PlayerPositionPacket),
+    PlayerRotation(PlayerRotationPacket),
+    PlayerMovement(PlayerMovementPacket),
     HeldItemChange(HeldItemChangePacket),
     SetRecipeBookState(SetRecipeBookStatePacket),
+    TabComplete(TabCompletePacket),
 }
 
 pub trait Serverbound {
@@ -0,0 +1,17 @@
+use crate::packets::packet_reader::PacketReader;
+use crate::error_type::ErrorType;
+
+use super::Serverbound;
+
+#[derive(Debug)]
+pub struct PlayerMovementPacket {
+    pub on_ground: bool,
+}
+
+impl Serverbound for PlayerMovementPacket {
+    fn from_reader(reader: &mut PacketReader) -> Result<Self, ErrorType> {
+        Ok(PlayerMovementPacket {
+            on_ground: reader.read_bool()?,
+        })
+    }
+}
@@ -0,0 +1,23 @@
+use crate::packets::packet_reader::PacketReader;
+use crate::error_type::ErrorType;
+
+use super::Serverbound;
+
+#[derive(Debug)]
+pub struct PlayerPositionPacket {
+    pub x: f64,
+    pub feet_y: f64,
+    pub z: f64,
+    pub on_ground: bool,
+}
+
+impl Serverbound for PlayerPositionPacket {
+    fn from_reader(reader: &mut PacketReader) -> Result<Self, ErrorType> {
+        Ok(PlayerPositionPacket {
+            x: reader.read_double()?,
+            feet_y: reader.read_double()?,
+            z: reader.read_double()?,
+            on_ground: reader.read_bool()?,
+        })
+    }
+}
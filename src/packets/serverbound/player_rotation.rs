@@ -0,0 +1,21 @@
+use crate::packets::packet_reader::PacketReader;
+use crate::error_type::ErrorType;
+
+use super::Serverbound;
+
+#[derive(Debug)]
+pub struct PlayerRotationPacket {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub on_ground: bool,
+}
+
+impl Serverbound for PlayerRotationPacket {
+    fn from_reader(reader: &mut PacketReader) -> Result<Self, ErrorType> {
+        Ok(PlayerRotationPacket {
+            yaw: reader.read_float()?,
+            pitch: reader.read_float()?,
+            on_ground: reader.read_bool()?,
+        })
+    }
+}
@@ -0,0 +1,20 @@
+use crate::packets::packet_reader::PacketReader;
+use crate::error_type::ErrorType;
+
+use super::Serverbound;
+
+#[derive(Debug)]
+pub struct TabCompletePacket {
+    pub transaction_id: isize,
+    // Everything before the cursor, including the leading slash
+    pub text: String,
+}
+
+impl Serverbound for TabCompletePacket {
+    fn from_reader(reader: &mut PacketReader) -> Result<Self, ErrorType> {
+        Ok(Self {
+            transaction_id: reader.read_varint()?,
+            text: reader.read_string()?,
+        })
+    }
+}
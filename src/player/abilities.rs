@@ -12,22 +12,31 @@ pub enum Ability {
     CreativeMode,
 }
 
+impl Ability {
+    fn bitmask(&self) -> u8 {
+        match self {
+            Ability::Invulnerable => 0b0001,
+            Ability::Flying       => 0b0010,
+            Ability::AllowFlying  => 0b0100,
+            Ability::CreativeMode => 0b1000,
+        }
+    }
+}
+
 impl Abilities {
     pub fn new() -> Self {
         Self { value: 0 }
     }
 
     pub fn add_ability(self, ability: Ability) -> Self {
-        let new_ability_bitmask = match ability {
-            Ability::Invulnerable => 0b0001,
-            Ability::Flying       => 0b0010,
-            Ability::AllowFlying  => 0b0100,
-            Ability::CreativeMode => 0b1000,
-        };
         return Self {
-            value: self.value | new_ability_bitmask
+            value: self.value | ability.bitmask()
         }
     }
+
+    pub fn has_ability(&self, ability: Ability) -> bool {
+        self.value & ability.bitmask() != 0
+    }
 }
 
 impl Debug for Abilities {
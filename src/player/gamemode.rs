@@ -1,4 +1,8 @@
-#[derive(Debug, Clone, Copy)]
+use serde::{Deserialize, Serialize};
+
+use super::{Abilities, Ability};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 #[allow(dead_code)]
 pub enum Gamemode {
     Survival = 0,
@@ -6,3 +10,56 @@ pub enum Gamemode {
     Adventure = 2,
     Spectator = 3,
 }
+
+impl Gamemode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "survival" => Some(Gamemode::Survival),
+            "creative" => Some(Gamemode::Creative),
+            "adventure" => Some(Gamemode::Adventure),
+            "spectator" => Some(Gamemode::Spectator),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Gamemode::Survival => "survival",
+            Gamemode::Creative => "creative",
+            Gamemode::Adventure => "adventure",
+            Gamemode::Spectator => "spectator",
+        }
+    }
+
+    // How it is called in messages
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Gamemode::Survival => "Survival Mode",
+            Gamemode::Creative => "Creative Mode",
+            Gamemode::Adventure => "Adventure Mode",
+            Gamemode::Spectator => "Spectator Mode",
+        }
+    }
+
+    // What a player in this gamemode may do, flying is kept by the caller
+    pub fn abilities(&self, flying: bool) -> Abilities {
+        match self {
+            Gamemode::Survival | Gamemode::Adventure => Abilities::new(),
+            Gamemode::Creative => {
+                let abilities = Abilities::new()
+                    .add_ability(Ability::Invulnerable)
+                    .add_ability(Ability::AllowFlying)
+                    .add_ability(Ability::CreativeMode);
+                if flying {
+                    abilities.add_ability(Ability::Flying)
+                } else {
+                    abilities
+                }
+            }
+            Gamemode::Spectator => Abilities::new()
+                .add_ability(Ability::Invulnerable)
+                .add_ability(Ability::AllowFlying)
+                .add_ability(Ability::Flying),
+        }
+    }
+}
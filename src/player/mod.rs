@@ -1,16 +1,24 @@
 mod abilities;
 mod gamemode;
+mod movement;
+mod permissions;
+mod player_data;
 
-use std::{collections::HashMap, time::Instant};
+use std::{collections::{HashMap, HashSet, VecDeque}, time::Instant};
 
 pub use abilities::*;
 pub use gamemode::*;
+pub use movement::*;
+pub use permissions::*;
+pub use player_data::*;
 
+use rand::random;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use crate::{server::Dimension, chat::Chat};
+use crate::{server::{Dimension, ItemStack, MAX_STACK_SIZE}, chat::{Chat, ClickEvent, HoverEvent}, error_type::ErrorType, world::LoadedChunks};
 
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Position {
     pub x: f64,
     pub y: f64,
@@ -18,13 +26,13 @@ pub struct Position {
     pub on_ground: bool,
 }
 
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct Look {
     pub yaw: f32,
     pub pitch: f32,
 }
 
-#[derive(Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct RecipeBookState {
     pub crafting_table_open: bool,
     pub crafting_table_filter: bool,
@@ -36,6 +44,9 @@ pub struct RecipeBookState {
     pub smoker_filter: bool,
 }
 
+// The hotbar comes first and then the rest of the inventory from the top left
+pub const INVENTORY_SIZE: usize = 36;
+
 #[derive(Clone)]
 pub struct Player {
     pub uuid: Uuid,
@@ -44,6 +55,7 @@ pub struct Player {
     pub previous_gamemode: Option<Gamemode>,
     pub dimension: Dimension,
     pub selected_slot: u8,
+    pub inventory: Vec<Option<ItemStack>>,
     pub position: Position,
     pub look: Look,
     pub abilities: Abilities,
@@ -51,15 +63,22 @@ pub struct Player {
     pub fov_modifier: f32,
     pub recipe_book_state: RecipeBookState,
     pub unlocked_recipes: Vec<String>,
-    pub op_level: OPLevel,
+    pub permissions: Permissions,
     pub properties: HashMap<String, (String, Option<String>)>,
     pub last_keepalive_sent: Option<(i64, Instant)>,
     pub latency: Option<i32>,
     pub displayname: Option<Chat>,
+    // Entities the client of this player has been told about
+    pub tracked_entities: HashSet<i32>,
+    // Teleports the client has not confirmed yet, oldest first
+    pub pending_teleports: VecDeque<i32>,
+    // The smallest of the view distance of the server and the one of the client
+    pub view_distance: i32,
+    pub loaded_chunks: LoadedChunks,
 }
 
 // Names from https://minecraft.fandom.com/wiki/Permission_level#Java_Edition
-#[derive(Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub enum OPLevel {
     Player = 0,
     Moderator = 1,
@@ -68,13 +87,26 @@ pub enum OPLevel {
     Owner = 4
 }
 
+impl OPLevel {
+    pub fn from_number(level: i32) -> Option<Self> {
+        match level {
+            0 => Some(OPLevel::Player),
+            1 => Some(OPLevel::Moderator),
+            2 => Some(OPLevel::Gamemaster),
+            3 => Some(OPLevel::Admin),
+            4 => Some(OPLevel::Owner),
+            _ => None,
+        }
+    }
+}
+
 impl Player {
     pub fn new(
         uuid: Uuid,
         username: String,
         gamemode: Gamemode,
         dimension: Dimension,
-        op_level: OPLevel,
+        permissions: Permissions,
     ) -> Self {
         Self {
             uuid,
@@ -83,6 +115,7 @@ impl Player {
             previous_gamemode: None,
             dimension,
             selected_slot: 0,
+            inventory: vec![None; INVENTORY_SIZE],
             position: Position { x: 10.0, y: 64.0, z: 20.0, on_ground: true },
             look: Look {
                 yaw: 0.0,
@@ -106,14 +139,97 @@ impl Player {
                 smoker_filter: false
             },
             unlocked_recipes: vec![],
-            op_level,
+            permissions,
             properties: HashMap::new(),
             last_keepalive_sent: None,
             latency: None,
             displayname: None,
+            tracked_entities: HashSet::new(),
+            pending_teleports: VecDeque::new(),
+            view_distance: 2,
+            loaded_chunks: LoadedChunks::new(),
+        }
+    }
+
+    // Fills up stacks of the same item before empty slots, returns the slots that changed and how many
+    // items did not fit
+    pub fn add_items(&mut self, item_id: i32, mut count: u32) -> (Vec<usize>, u32) {
+        let mut changed = vec![];
+        for (index, slot) in self.inventory.iter_mut().enumerate() {
+            if let Some(stack) = slot.as_mut().filter(|stack| stack.item_id == item_id) {
+                let added = count.min((MAX_STACK_SIZE - stack.count) as u32);
+                if added > 0 {
+                    stack.count += added as u8;
+                    count -= added;
+                    changed.push(index);
+                }
+            }
+        }
+        for (index, slot) in self.inventory.iter_mut().enumerate() {
+            if count == 0 {
+                break;
+            }
+            if slot.is_none() {
+                let added = count.min(MAX_STACK_SIZE as u32);
+                *slot = Some(ItemStack { item_id, count: added as u8 });
+                count -= added;
+                changed.push(index);
+            }
+        }
+        (changed, count)
+    }
+
+    // Where an inventory slot is in the window of the player inventory, there the hotbar is at the bottom
+    pub fn window_slot(index: usize) -> i16 {
+        if index < 9 {
+            36 + index as i16
+        } else {
+            index as i16
         }
     }
 
+    // Gives out an id for a teleport that is about to be sent, movement of the player is ignored
+    // until the client confirms it
+    pub fn start_teleport(&mut self) -> i32 {
+        let teleport_id = random();
+        self.pending_teleports.push_back(teleport_id);
+        teleport_id
+    }
+
+    // The client confirms teleports in the order they were sent
+    pub fn confirm_teleport(&mut self, teleport_id: i32) -> Result<(), ErrorType> {
+        match self.pending_teleports.front() {
+            Some(expected_id) if *expected_id == teleport_id => {
+                self.pending_teleports.pop_front();
+                Ok(())
+            }
+            Some(expected_id) => Err(ErrorType::Recoverable(format!(
+                "Got teleport confirmation for {}, but expected {}",
+                teleport_id, expected_id
+            ))),
+            None => Err(ErrorType::Recoverable(format!(
+                "Got teleport confirmation for {} without having sent one",
+                teleport_id
+            ))),
+        }
+    }
+
+    pub fn is_teleporting(&self) -> bool {
+        !self.pending_teleports.is_empty()
+    }
+
+    // The name like vanilla shows it in messages, clicking it starts a private message
+    pub fn display_name(&self) -> Chat {
+        Chat::new(self.username.clone())
+            .insertion(self.username.clone())
+            .click(ClickEvent::SuggestCommand(format!("/tell {} ", self.username)))
+            .hover(HoverEvent::entity(
+                "minecraft:player",
+                self.uuid,
+                Some(Chat::new(self.username.clone())),
+            ))
+    }
+
     pub fn offline_player_uuid(username: &String) -> Uuid {
         let username_bytes = format!("OfflinePlayer:{}", username)
             .bytes()
@@ -121,3 +237,30 @@ impl Player {
         Uuid::new_v3(&Uuid::NAMESPACE_URL, &username_bytes)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_add_items() {
+        let mut player = Player::new(
+            Uuid::nil(),
+            "Alice".to_string(),
+            Gamemode::Survival,
+            Dimension::dummy(),
+            Permissions::new(OPLevel::Player),
+        );
+        assert_eq!(player.add_items(9, 70), (vec![0, 1], 0));
+        assert_eq!(player.inventory[1], Some(ItemStack { item_id: 9, count: 6 }));
+
+        // The started stack is filled up before a new one is started
+        assert_eq!(player.add_items(9, 60), (vec![1, 2], 0));
+        assert_eq!(player.inventory[1], Some(ItemStack { item_id: 9, count: 64 }));
+        assert_eq!(player.inventory[2], Some(ItemStack { item_id: 9, count: 2 }));
+
+        assert_eq!(player.add_items(1, 64 * 40), ((3..INVENTORY_SIZE).collect(), 64 * 7));
+        assert_eq!(Player::window_slot(0), 36);
+        assert_eq!(Player::window_slot(9), 9);
+    }
+}
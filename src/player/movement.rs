@@ -0,0 +1,248 @@
+use super::{Ability, Gamemode, Player};
+
+use crate::packets::serverbound::*;
+use crate::world::World;
+
+use std::fmt;
+use std::time::Instant;
+
+// Speed limits in blocks per tick, with some slack for lag and sprint jumping
+const MAX_WALKING_SPEED: f64 = 1.0;
+const MAX_UPWARDS_SPEED: f64 = 1.0;
+const MAX_SPECTATOR_SPEED: f64 = 10.0;
+// The flying speed of a player is 0.05 by default, which allows 2.5 blocks per tick
+const FLYING_SPEED_FACTOR: f64 = 50.0;
+
+// Vanilla kicks players that float this many ticks without being allowed to fly
+const MAX_FLOATING_TICKS: f64 = 80.0;
+
+// The world border can not go any further than this
+const MAX_HORIZONTAL_COORDINATE: f64 = 30_000_000.0;
+const MAX_VERTICAL_COORDINATE: f64 = 20_000_000.0;
+
+const PLAYER_WIDTH: f64 = 0.6;
+const PLAYER_HEIGHT: f64 = 1.8;
+// A hitbox that ends exactly on a block border only touches the next block, it is not inside it
+const COLLISION_EPSILON: f64 = 1e-7;
+
+// A movement as sent by the client in one of the four movement packets, whatever the packet did
+// not contain is None
+#[derive(Debug, Clone)]
+pub struct Movement {
+    pub position: Option<(f64, f64, f64)>,
+    pub look: Option<(f32, f32)>,
+    pub on_ground: bool,
+}
+
+impl Movement {
+    pub fn apply_to(&self, player: &mut Player) {
+        if let Some((x, y, z)) = self.position {
+            player.position.x = x;
+            player.position.y = y;
+            player.position.z = z;
+        }
+        if let Some((yaw, pitch)) = self.look {
+            player.look.yaw = yaw;
+            player.look.pitch = pitch;
+        }
+        player.position.on_ground = self.on_ground;
+    }
+}
+
+#[derive(Debug)]
+pub enum MovementViolation {
+    InvalidValue,
+    OutOfWorld,
+    TooFast(f64),
+    Flying,
+    InsideBlock,
+}
+
+impl fmt::Display for MovementViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MovementViolation::InvalidValue => write!(f, "sent an invalid position or rotation"),
+            MovementViolation::OutOfWorld => write!(f, "moved out of the world"),
+            MovementViolation::TooFast(distance) => {
+                write!(f, "moved too quickly ({:.2} blocks)", distance)
+            }
+            MovementViolation::Flying => write!(f, "is flying without being allowed to"),
+            MovementViolation::InsideBlock => write!(f, "moved into a block"),
+        }
+    }
+}
+
+// Checks the movement of one player, this keeps the state that is only needed while they are
+// connected
+#[derive(Debug, PartialEq)]
+pub struct MovementChecker {
+    last_movement: Option<Instant>,
+    floating_ticks: f64,
+}
+
+impl MovementChecker {
+    pub fn new() -> Self {
+        Self {
+            last_movement: None,
+            floating_ticks: 0.0,
+        }
+    }
+
+    // Checks the movement against where the player is now, the player itself is not changed
+    pub fn check(
+        &mut self,
+        movement: &Movement,
+        player: &Player,
+        world: &World,
+    ) -> Result<(), MovementViolation> {
+        // The client sends a movement packet every tick while moving, and at least once a second
+        let now = Instant::now();
+        let ticks = match self.last_movement {
+            Some(last_movement) => ((now - last_movement).as_millis() / 50).clamp(1, 20) as f64,
+            None => 1.0,
+        };
+        self.last_movement = Some(now);
+
+        let is_spectator = matches!(player.gamemode, Gamemode::Spectator);
+        let may_fly = is_spectator || player.abilities.has_ability(Ability::AllowFlying);
+        let is_flying = may_fly && player.abilities.has_ability(Ability::Flying);
+
+        if let Some((yaw, pitch)) = movement.look {
+            if !(yaw.is_finite() && pitch.is_finite()) {
+                return Err(MovementViolation::InvalidValue);
+            }
+        }
+
+        let mut delta_y = 0.0;
+        if let Some((x, y, z)) = movement.position {
+            if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+                return Err(MovementViolation::InvalidValue);
+            }
+            if x.abs() > MAX_HORIZONTAL_COORDINATE
+                || z.abs() > MAX_HORIZONTAL_COORDINATE
+                || y.abs() > MAX_VERTICAL_COORDINATE
+            {
+                return Err(MovementViolation::OutOfWorld);
+            }
+
+            let delta_x = x - player.position.x;
+            delta_y = y - player.position.y;
+            let delta_z = z - player.position.z;
+
+            let max_speed = if is_spectator {
+                MAX_SPECTATOR_SPEED
+            } else if is_flying {
+                player.flying_speed as f64 * FLYING_SPEED_FACTOR
+            } else {
+                MAX_WALKING_SPEED
+            };
+            let max_upwards_speed = if is_flying { max_speed } else { MAX_UPWARDS_SPEED };
+
+            let horizontal_distance = (delta_x * delta_x + delta_z * delta_z).sqrt();
+            if horizontal_distance > max_speed * ticks {
+                return Err(MovementViolation::TooFast(horizontal_distance));
+            }
+            if delta_y > max_upwards_speed * ticks {
+                return Err(MovementViolation::TooFast(delta_y));
+            }
+
+            // Players that are already stuck (for example because a block was placed in them)
+            // are allowed to move out
+            if !is_spectator
+                && collides(world, x, y, z)
+                && !collides(world, player.position.x, player.position.y, player.position.z)
+            {
+                return Err(MovementViolation::InsideBlock);
+            }
+        }
+
+        if may_fly || movement.on_ground || delta_y < 0.0 {
+            self.floating_ticks = 0.0;
+        } else {
+            self.floating_ticks += ticks;
+            if self.floating_ticks > MAX_FLOATING_TICKS {
+                self.floating_ticks = 0.0;
+                return Err(MovementViolation::Flying);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Whether the hitbox of a player standing at x, y, z overlaps with a solid block
+fn collides(world: &World, x: f64, y: f64, z: f64) -> bool {
+    hitbox_collides(|block_x, block_y, block_z| world.is_solid_block(block_x, block_y, block_z), x, y, z)
+}
+
+fn hitbox_collides(is_solid_block: impl Fn(i32, i32, i32) -> bool, x: f64, y: f64, z: f64) -> bool {
+    let min_x = (x - PLAYER_WIDTH / 2.0).floor() as i32;
+    let max_x = (x + PLAYER_WIDTH / 2.0 - COLLISION_EPSILON).floor() as i32;
+    let min_y = y.floor() as i32;
+    let max_y = (y + PLAYER_HEIGHT - COLLISION_EPSILON).floor() as i32;
+    let min_z = (z - PLAYER_WIDTH / 2.0).floor() as i32;
+    let max_z = (z + PLAYER_WIDTH / 2.0 - COLLISION_EPSILON).floor() as i32;
+
+    (min_x..=max_x).any(|block_x| {
+        (min_y..=max_y).any(|block_y| {
+            (min_z..=max_z).any(|block_z| is_solid_block(block_x, block_y, block_z))
+        })
+    })
+}
+
+impl From<PlayerPositionAndRotationPacket> for Movement {
+    fn from(packet: PlayerPositionAndRotationPacket) -> Self {
+        Self {
+            position: Some((packet.x, packet.feet_y, packet.z)),
+            look: Some((packet.yaw, packet.pitch)),
+            on_ground: packet.on_ground,
+        }
+    }
+}
+
+impl From<PlayerPositionPacket> for Movement {
+    fn from(packet: PlayerPositionPacket) -> Self {
+        Self {
+            position: Some((packet.x, packet.feet_y, packet.z)),
+            look: None,
+            on_ground: packet.on_ground,
+        }
+    }
+}
+
+impl From<PlayerRotationPacket> for Movement {
+    fn from(packet: PlayerRotationPacket) -> Self {
+        Self {
+            position: None,
+            look: Some((packet.yaw, packet.pitch)),
+            on_ground: packet.on_ground,
+        }
+    }
+}
+
+impl From<PlayerMovementPacket> for Movement {
+    fn from(packet: PlayerMovementPacket) -> Self {
+        Self {
+            position: None,
+            look: None,
+            on_ground: packet.on_ground,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::hitbox_collides;
+
+    #[test]
+    fn test_collides() {
+        // A wall at x = 11 and a ceiling at y = 66
+        let wall = |x: i32, _: i32, _: i32| x == 11;
+        let ceiling = |_: i32, y: i32, _: i32| y == 66;
+
+        assert!(!hitbox_collides(wall, 10.7, 64.0, 0.5));
+        assert!(hitbox_collides(wall, 10.71, 64.0, 0.5));
+        assert!(!hitbox_collides(ceiling, 0.5, 64.2, 0.5));
+        assert!(hitbox_collides(ceiling, 0.5, 64.21, 0.5));
+    }
+}
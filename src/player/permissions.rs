@@ -0,0 +1,65 @@
+use std::collections::HashMap;
+
+use super::OPLevel;
+
+// What someone may do: their OP level, with permission nodes like mcrust.command.gamemode
+// granting or denying single things on top of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Permissions {
+    pub op_level: OPLevel,
+    pub nodes: HashMap<String, bool>,
+}
+
+impl Permissions {
+    pub fn new(op_level: OPLevel) -> Self {
+        Self {
+            op_level,
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub fn with_nodes(op_level: OPLevel, nodes: HashMap<String, bool>) -> Self {
+        Self { op_level, nodes }
+    }
+
+    // The most specific setting for a node, mcrust.command.gamemode is also set by
+    // mcrust.command.*, mcrust.* and *
+    pub fn node(&self, node: &str) -> Option<bool> {
+        if let Some(value) = self.nodes.get(node) {
+            return Some(*value);
+        }
+        let mut prefix = node;
+        while let Some((parent, _)) = prefix.rsplit_once('.') {
+            if let Some(value) = self.nodes.get(&format!("{}.*", parent)) {
+                return Some(*value);
+            }
+            prefix = parent;
+        }
+        self.nodes.get("*").copied()
+    }
+
+    // Nodes that are not set fall back to the level
+    pub fn allows(&self, node: Option<&str>, level: OPLevel) -> bool {
+        node.and_then(|node| self.node(node))
+            .unwrap_or(self.op_level >= level)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Permissions;
+    use crate::player::OPLevel;
+
+    #[test]
+    fn test_allows() {
+        let mut permissions = Permissions::new(OPLevel::Gamemaster);
+        assert!(permissions.allows(Some("mcrust.command.gamemode"), OPLevel::Gamemaster));
+        assert!(!permissions.allows(Some("mcrust.command.stop"), OPLevel::Owner));
+
+        permissions.nodes.insert("mcrust.command.*".to_string(), false);
+        permissions.nodes.insert("mcrust.command.stop".to_string(), true);
+        assert!(!permissions.allows(Some("mcrust.command.gamemode"), OPLevel::Gamemaster));
+        assert!(permissions.allows(Some("mcrust.command.stop"), OPLevel::Owner));
+        assert!(permissions.allows(None, OPLevel::Gamemaster));
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::server::ItemStack;
+
+use super::{Abilities, Gamemode, Look, Player, Position, RecipeBookState, INVENTORY_SIZE};
+
+// The part of a player that is kept between sessions
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PlayerData {
+    pub username: String,
+    pub gamemode: Gamemode,
+    pub previous_gamemode: Option<Gamemode>,
+    pub selected_slot: u8,
+    // Missing in data saved before players had an inventory
+    #[serde(default)]
+    pub inventory: Vec<Option<ItemStack>>,
+    pub position: Position,
+    pub look: Look,
+    pub abilities: u8,
+    pub flying_speed: f32,
+    pub fov_modifier: f32,
+    pub recipe_book_state: RecipeBookState,
+    pub unlocked_recipes: Vec<String>,
+}
+
+impl From<&Player> for PlayerData {
+    fn from(player: &Player) -> Self {
+        Self {
+            username: player.username.clone(),
+            gamemode: player.gamemode,
+            previous_gamemode: player.previous_gamemode,
+            selected_slot: player.selected_slot,
+            inventory: player.inventory.clone(),
+            position: player.position.clone(),
+            look: player.look.clone(),
+            abilities: player.abilities.value,
+            flying_speed: player.flying_speed,
+            fov_modifier: player.fov_modifier,
+            recipe_book_state: player.recipe_book_state.clone(),
+            unlocked_recipes: player.unlocked_recipes.clone(),
+        }
+    }
+}
+
+impl PlayerData {
+    pub fn apply_to(self, player: &mut Player) {
+        player.gamemode = self.gamemode;
+        player.previous_gamemode = self.previous_gamemode;
+        player.selected_slot = self.selected_slot;
+        player.inventory = self.inventory;
+        player.inventory.resize(INVENTORY_SIZE, None);
+        player.position = self.position;
+        player.look = self.look;
+        player.abilities = Abilities { value: self.abilities };
+        player.flying_speed = self.flying_speed;
+        player.fov_modifier = self.fov_modifier;
+        player.recipe_book_state = self.recipe_book_state;
+        player.unlocked_recipes = self.unlocked_recipes;
+    }
+}
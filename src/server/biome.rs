@@ -48,7 +48,7 @@ impl Into<NBTTag> for BiomeCategory {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 #[allow(dead_code)]
 pub enum PrecipitationType {
     None,
@@ -96,6 +96,23 @@ pub struct BiomeSettings {
     pub effects: BiomeEffects,
 }
 
+impl BiomeSettings {
+    // It gets colder higher up, cold enough and rain turns into snow
+    pub fn precipitation_at(&self, y: i32) -> PrecipitationType {
+        match self.precipitation {
+            PrecipitationType::None => PrecipitationType::None,
+            _ => {
+                let height_above_sea = (y - 64).max(0) as f32;
+                if self.temperature - height_above_sea * 0.05 / 30.0 < 0.15 {
+                    PrecipitationType::Snow
+                } else {
+                    PrecipitationType::Rain
+                }
+            }
+        }
+    }
+}
+
 impl Into<NBTTag> for BiomeSettings {
     fn into(self) -> NBTTag {
         let mut ret = HashMap::new();
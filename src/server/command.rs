@@ -1,6 +1,7 @@
 use std::convert::TryInto;
 
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ProtocolVersion;
 
 pub use super::command_parser::CommandParserType;
 
@@ -38,7 +39,7 @@ pub enum CommandNode {
 }
 
 impl CommandNode {
-    pub fn write(&self, writer: &mut PacketWriter) {
+    pub fn write(&self, writer: &mut PacketWriter, protocol: &ProtocolVersion) {
         // Write flags
         let mut flags = match self {
             CommandNode::Root(_, _, _) => 0b00,
@@ -93,7 +94,7 @@ impl CommandNode {
 
         // Write argument-specific parts
         if let CommandNode::Argument(_, _, _, _, parser_id, maybe_suggestions) = self {
-            parser_id.write(writer);
+            parser_id.write(writer, protocol);
 
             if let Some(suggestions) = maybe_suggestions {
                 writer.add_string(&suggestions.into());
@@ -101,3 +102,29 @@ impl CommandNode {
         }
     }
 }
+
+impl CommandNode {
+    pub fn is_executable(&self) -> bool {
+        match self {
+            CommandNode::Root(executable, _, _) |
+            CommandNode::Literal(executable, _, _, _) |
+            CommandNode::Argument(executable, _, _, _, _, _) => *executable,
+        }
+    }
+
+    pub fn children(&self) -> &Vec<i32> {
+        match self {
+            CommandNode::Root(_, children, _) |
+            CommandNode::Literal(_, children, _, _) |
+            CommandNode::Argument(_, children, _, _, _, _) => children,
+        }
+    }
+
+    pub fn redirect(&self) -> Option<i32> {
+        match self {
+            CommandNode::Root(_, _, redirect) |
+            CommandNode::Literal(_, _, redirect, _) |
+            CommandNode::Argument(_, _, redirect, _, _, _) => *redirect,
+        }
+    }
+}
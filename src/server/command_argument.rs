@@ -0,0 +1,861 @@
+use std::f64::consts::PI;
+
+use uuid::Uuid;
+
+use crate::chat::Chat;
+use crate::nbt::NBTTag;
+use crate::player::Gamemode;
+
+use super::command_parser::BrigadierStringArgument;
+use super::snbt_reader;
+use super::EntitySelector;
+use super::CommandError;
+use super::CommandParserType;
+use super::StringReader;
+
+pub const COLORS: [&str; 17] = [
+    "black", "dark_blue", "dark_green", "dark_aqua", "dark_red", "dark_purple", "gold", "gray",
+    "dark_gray", "blue", "green", "aqua", "red", "light_purple", "yellow", "white", "reset",
+];
+
+// A parsed argument, handlers get these out of the command context by name
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgumentValue {
+    Bool(bool),
+    Float(f32),
+    Double(f64),
+    Integer(i32),
+    Long(i64),
+    String(String),
+    Uuid(Uuid),
+    Gamemode(Gamemode),
+    // Always with a namespace, tags start with #
+    ResourceLocation(String),
+    Coordinates(Coordinates),
+    // Yaw, Pitch
+    Rotation(WorldCoordinate, WorldCoordinate),
+    Angle(WorldCoordinate),
+    IntRange(Option<i32>, Option<i32>),
+    FloatRange(Option<f64>, Option<f64>),
+    // Id, Properties, NBT
+    BlockState(String, Vec<(String, String)>, Option<NBTTag>),
+    // Id, NBT
+    ItemStack(String, Option<NBTTag>),
+    NBTTag(NBTTag),
+    Component(Chat),
+    EntitySelector(EntitySelector),
+}
+
+// One axis of a position, ~ makes it relative to whoever runs the command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldCoordinate {
+    pub relative: bool,
+    pub value: f64,
+}
+
+impl WorldCoordinate {
+    pub fn get(&self, origin: f64) -> f64 {
+        if self.relative {
+            origin + self.value
+        } else {
+            self.value
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Coordinates {
+    World(WorldCoordinate, WorldCoordinate, WorldCoordinate),
+    // ^left ^up ^forwards, relative to where the source is looking
+    Local(f64, f64, f64),
+}
+
+impl Coordinates {
+    // Origin is x, y, z and rotation is yaw, pitch in degrees
+    pub fn position(&self, origin: (f64, f64, f64), rotation: (f32, f32)) -> (f64, f64, f64) {
+        match self {
+            Coordinates::World(x, y, z) => (x.get(origin.0), y.get(origin.1), z.get(origin.2)),
+            Coordinates::Local(left, up, forwards) => {
+                let yaw = (rotation.0 as f64 + 90.0) * PI / 180.0;
+                let pitch = -rotation.1 as f64 * PI / 180.0;
+                let pitch_up = (-rotation.1 as f64 + 90.0) * PI / 180.0;
+                let forward = (yaw.cos() * pitch.cos(), pitch.sin(), yaw.sin() * pitch.cos());
+                let upward = (yaw.cos() * pitch_up.cos(), pitch_up.sin(), yaw.sin() * pitch_up.cos());
+                // Cross product of forward and up, turned around
+                let leftward = (
+                    -(forward.1 * upward.2 - forward.2 * upward.1),
+                    -(forward.2 * upward.0 - forward.0 * upward.2),
+                    -(forward.0 * upward.1 - forward.1 * upward.0),
+                );
+                (
+                    origin.0 + forward.0 * forwards + upward.0 * up + leftward.0 * left,
+                    origin.1 + forward.1 * forwards + upward.1 * up + leftward.1 * left,
+                    origin.2 + forward.2 * forwards + upward.2 * up + leftward.2 * left,
+                )
+            }
+        }
+    }
+
+    pub fn block_position(&self, origin: (f64, f64, f64), rotation: (f32, f32)) -> (i32, i32, i32) {
+        let (x, y, z) = self.position(origin, rotation);
+        (x.floor() as i32, y.floor() as i32, z.floor() as i32)
+    }
+}
+
+fn incomplete(reader: &mut StringReader, start: usize, amount: usize) -> CommandError {
+    reader.set_cursor(start);
+    CommandError::at(format!("Incomplete (expected {} coordinates)", amount), reader)
+}
+
+fn mixed_coordinates(reader: &StringReader) -> CommandError {
+    CommandError::at(
+        "Cannot mix world & local coordinates (everything must either use ^ or not)".to_string(),
+        reader,
+    )
+}
+
+// Numbers with a . are taken as they are, whole numbers are moved to the middle of the block
+fn read_world_coordinate(
+    reader: &mut StringReader,
+    center_correct: bool,
+) -> Result<WorldCoordinate, CommandError> {
+    if reader.can_read() && reader.peek() == '^' {
+        return Err(mixed_coordinates(reader));
+    }
+    if !reader.can_read() {
+        return Err(CommandError::at("Expected a coordinate".to_string(), reader));
+    }
+    let relative = reader.peek() == '~';
+    if relative {
+        reader.skip();
+    }
+    let start = reader.cursor();
+    let mut value = if reader.can_read() && reader.peek() != ' ' {
+        reader.read_double()?
+    } else {
+        0.0
+    };
+    let text: String = reader.read().chars().skip(start).collect();
+    if !relative && center_correct && !text.contains('.') {
+        value += 0.5;
+    }
+    Ok(WorldCoordinate { relative, value })
+}
+
+fn read_world_block_coordinate(reader: &mut StringReader) -> Result<WorldCoordinate, CommandError> {
+    if reader.can_read() && reader.peek() == '^' {
+        return Err(mixed_coordinates(reader));
+    }
+    if !reader.can_read() {
+        return Err(CommandError::at("Expected a block position".to_string(), reader));
+    }
+    if reader.peek() == '~' {
+        reader.skip();
+        let value = if reader.can_read() && reader.peek() != ' ' {
+            reader.read_double()?
+        } else {
+            0.0
+        };
+        return Ok(WorldCoordinate { relative: true, value });
+    }
+    Ok(WorldCoordinate { relative: false, value: reader.read_int()? as f64 })
+}
+
+fn read_local_coordinate(reader: &mut StringReader, start: usize) -> Result<f64, CommandError> {
+    if !reader.can_read() {
+        return Err(CommandError::at("Expected a coordinate".to_string(), reader));
+    }
+    if reader.peek() != '^' {
+        reader.set_cursor(start);
+        return Err(mixed_coordinates(reader));
+    }
+    reader.skip();
+    if reader.can_read() && reader.peek() != ' ' {
+        reader.read_double()
+    } else {
+        Ok(0.0)
+    }
+}
+
+// Reads `amount` values separated by single spaces
+fn read_separated<T>(
+    reader: &mut StringReader,
+    amount: usize,
+    mut read: impl FnMut(&mut StringReader, usize) -> Result<T, CommandError>,
+) -> Result<Vec<T>, CommandError> {
+    let start = reader.cursor();
+    let mut values = vec![];
+    for i in 0..amount {
+        if i > 0 {
+            if !reader.can_read() || reader.peek() != ' ' {
+                return Err(incomplete(reader, start, amount));
+            }
+            reader.skip();
+        }
+        values.push(read(reader, i)?);
+    }
+    Ok(values)
+}
+
+fn read_local_coordinates(reader: &mut StringReader) -> Result<Coordinates, CommandError> {
+    let start = reader.cursor();
+    let values = read_separated(reader, 3, |reader, _| read_local_coordinate(reader, start))?;
+    Ok(Coordinates::Local(values[0], values[1], values[2]))
+}
+
+fn read_block_pos(reader: &mut StringReader) -> Result<Coordinates, CommandError> {
+    if reader.can_read() && reader.peek() == '^' {
+        return read_local_coordinates(reader);
+    }
+    let values = read_separated(reader, 3, |reader, _| read_world_block_coordinate(reader))?;
+    Ok(Coordinates::World(values[0], values[1], values[2]))
+}
+
+fn read_vec3(reader: &mut StringReader) -> Result<Coordinates, CommandError> {
+    if reader.can_read() && reader.peek() == '^' {
+        return read_local_coordinates(reader);
+    }
+    // Only x and z are moved to the middle of the block
+    let values = read_separated(reader, 3, |reader, axis| read_world_coordinate(reader, axis != 1))?;
+    Ok(Coordinates::World(values[0], values[1], values[2]))
+}
+
+// Two dimensional positions keep the height of the source
+fn read_column(
+    reader: &mut StringReader,
+    read: impl Fn(&mut StringReader) -> Result<WorldCoordinate, CommandError>,
+) -> Result<Coordinates, CommandError> {
+    let values = read_separated(reader, 2, |reader, _| read(reader))?;
+    let y = WorldCoordinate { relative: true, value: 0.0 };
+    Ok(Coordinates::World(values[0], y, values[1]))
+}
+
+fn read_angle(reader: &mut StringReader) -> Result<WorldCoordinate, CommandError> {
+    if !reader.can_read() {
+        return Err(CommandError::at("Incomplete (expected 1 angle)".to_string(), reader));
+    }
+    let angle = read_world_coordinate(reader, false)?;
+    if !angle.value.is_finite() {
+        return Err(CommandError::at("Invalid angle".to_string(), reader));
+    }
+    Ok(angle)
+}
+
+fn is_allowed_in_resource_location(c: char) -> bool {
+    c.is_ascii_digit() || c.is_ascii_lowercase() || "_:/.-".contains(c)
+}
+
+// namespace:path, where the namespace is minecraft if it is left out
+pub fn read_resource_location(reader: &mut StringReader) -> Result<String, CommandError> {
+    let start = reader.cursor();
+    while reader.can_read() && is_allowed_in_resource_location(reader.peek()) {
+        reader.skip();
+    }
+    let text: String = reader.read().chars().skip(start).collect();
+    let (namespace, path) = match text.split_once(':') {
+        Some((namespace, path)) => (namespace, path),
+        None => ("minecraft", text.as_str()),
+    };
+    let namespace = if namespace.is_empty() { "minecraft" } else { namespace };
+    if text.is_empty() || namespace.contains('/') || path.contains(':') {
+        reader.set_cursor(start);
+        return Err(CommandError::at("Invalid ID".to_string(), reader));
+    }
+    Ok(format!("{}:{}", namespace, path))
+}
+
+// Resource locations that can also be a tag, like #minecraft:logs
+fn read_tag_or_resource_location(reader: &mut StringReader) -> Result<String, CommandError> {
+    if reader.can_read() && reader.peek() == '#' {
+        reader.skip();
+        return Ok(format!("#{}", read_resource_location(reader)?));
+    }
+    read_resource_location(reader)
+}
+
+// Reads a word and makes sure it is one of the options
+fn read_one_of(
+    reader: &mut StringReader,
+    options: &[&str],
+    error: impl Fn(&str) -> String,
+) -> Result<String, CommandError> {
+    let start = reader.cursor();
+    let value = reader.read_unquoted_string();
+    if !options.contains(&value.as_str()) {
+        reader.set_cursor(start);
+        return Err(CommandError::at(error(&value), reader));
+    }
+    Ok(value)
+}
+
+fn read_until_space(reader: &mut StringReader) -> String {
+    let start = reader.cursor();
+    while reader.can_read() && reader.peek() != ' ' {
+        reader.skip();
+    }
+    reader.read().chars().skip(start).collect()
+}
+
+// Checks what the argument allows before anything gets resolved, like vanilla does
+fn read_entity_selector(
+    reader: &mut StringReader,
+    single: bool,
+    only_players: bool,
+) -> Result<EntitySelector, CommandError> {
+    let start = reader.cursor();
+    let selector = EntitySelector::parse(reader)?;
+    if single && !selector.is_single() {
+        reader.set_cursor(start);
+        let message = if only_players {
+            "Only one player is allowed, but the provided selector allows more than one"
+        } else {
+            "Only one entity is allowed, but the provided selector allows more than one"
+        };
+        return Err(CommandError::at(message.to_string(), reader));
+    }
+    if only_players && selector.includes_entities() {
+        reader.set_cursor(start);
+        return Err(CommandError::at(
+            "Only players may be affected by this command, but the provided selector includes entities"
+                .to_string(),
+            reader,
+        ));
+    }
+    Ok(selector)
+}
+
+fn read_uuid(reader: &mut StringReader) -> Result<Uuid, CommandError> {
+    let start = reader.cursor();
+    while reader.can_read() && (reader.peek().is_ascii_hexdigit() || reader.peek() == '-') {
+        reader.skip();
+    }
+    let text: String = reader.read().chars().skip(start).collect();
+    Uuid::parse_str(&text).map_err(|_| {
+        reader.set_cursor(start);
+        CommandError::at("Invalid UUID".to_string(), reader)
+    })
+}
+
+// 5d, 20s or 100t, ticks without a unit
+fn read_time(reader: &mut StringReader, min: i32) -> Result<i32, CommandError> {
+    let value = reader.read_float()?;
+    let unit = reader.read_unquoted_string();
+    let ticks_per_unit = match unit.as_str() {
+        "d" => 24000.0,
+        "s" => 20.0,
+        "t" | "" => 1.0,
+        _ => return Err(CommandError::at("Invalid unit".to_string(), reader)),
+    };
+    let ticks = (value * ticks_per_unit).round() as i32;
+    if ticks < 0 {
+        return Err(CommandError::at("Tick count must be non-negative".to_string(), reader));
+    }
+    if ticks < min {
+        return Err(CommandError::at(
+            format!("The tick count must not be less than {}, found {}", min, ticks),
+            reader,
+        ));
+    }
+    Ok(ticks)
+}
+
+// 5, 1..5, ..5 or 1..
+pub fn read_range<T: std::str::FromStr + PartialOrd + Copy>(
+    reader: &mut StringReader,
+    kind: &str,
+) -> Result<(Option<T>, Option<T>), CommandError> {
+    if !reader.can_read() {
+        return Err(CommandError::at("Expected value or range of values".to_string(), reader));
+    }
+    let start = reader.cursor();
+    let read_bound = |reader: &mut StringReader| -> Result<Option<T>, CommandError> {
+        let bound_start = reader.cursor();
+        while reader.can_read() {
+            let c = reader.peek();
+            let is_range = c == '.' && reader.peek_at(1) == Some('.');
+            if !(c.is_ascii_digit() || c == '-' || c == '.') || is_range {
+                break;
+            }
+            reader.skip();
+        }
+        let text: String = reader.read().chars().skip(bound_start).collect();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        text.parse().map(Some).map_err(|_| {
+            reader.set_cursor(bound_start);
+            CommandError::at(format!("Invalid {} '{}'", kind, text), reader)
+        })
+    };
+
+    let min = read_bound(reader)?;
+    let max = if reader.can_read_length(2) && reader.peek() == '.' && reader.peek_at(1) == Some('.') {
+        reader.skip();
+        reader.skip();
+        read_bound(reader)?
+    } else {
+        min
+    };
+
+    match (&min, &max) {
+        (None, None) => {
+            reader.set_cursor(start);
+            Err(CommandError::at("Expected value or range of values".to_string(), reader))
+        }
+        (Some(min_value), Some(max_value)) if min_value > max_value => {
+            reader.set_cursor(start);
+            Err(CommandError::at("Min cannot be bigger than max".to_string(), reader))
+        }
+        _ => Ok((min, max)),
+    }
+}
+
+// A block id or tag with optional [property=value] and {nbt}
+fn read_block_state(reader: &mut StringReader, allow_tags: bool) -> Result<ArgumentValue, CommandError> {
+    let id = if allow_tags {
+        read_tag_or_resource_location(reader)?
+    } else {
+        read_resource_location(reader)?
+    };
+    let mut properties = vec![];
+    if reader.can_read() && reader.peek() == '[' {
+        reader.skip();
+        reader.skip_whitespace();
+        while reader.can_read() && reader.peek() != ']' {
+            let key = reader.read_string()?;
+            reader.skip_whitespace();
+            if !reader.can_read() || reader.peek() != '=' {
+                return Err(CommandError::at(
+                    format!("Expected value for property '{}' on block {}", key, id),
+                    reader,
+                ));
+            }
+            reader.skip();
+            reader.skip_whitespace();
+            let value = reader.read_string()?;
+            properties.push((key, value));
+            reader.skip_whitespace();
+            if reader.can_read() && reader.peek() == ',' {
+                reader.skip();
+                reader.skip_whitespace();
+                continue;
+            }
+            break;
+        }
+        if !reader.can_read() || reader.peek() != ']' {
+            return Err(CommandError::at(
+                "Expected closing ] for block state properties".to_string(),
+                reader,
+            ));
+        }
+        reader.skip();
+    }
+    let nbt = read_optional_nbt(reader)?;
+    Ok(ArgumentValue::BlockState(id, properties, nbt))
+}
+
+fn read_item_stack(reader: &mut StringReader, allow_tags: bool) -> Result<ArgumentValue, CommandError> {
+    let id = if allow_tags {
+        read_tag_or_resource_location(reader)?
+    } else {
+        read_resource_location(reader)?
+    };
+    let nbt = read_optional_nbt(reader)?;
+    Ok(ArgumentValue::ItemStack(id, nbt))
+}
+
+fn read_optional_nbt(reader: &mut StringReader) -> Result<Option<NBTTag>, CommandError> {
+    if reader.can_read() && reader.peek() == '{' {
+        return Ok(Some(snbt_reader::read_compound(reader)?));
+    }
+    Ok(None)
+}
+
+// Paths like Inventory[0].tag.display, brackets and quotes may contain spaces
+fn read_nbt_path(reader: &mut StringReader) -> Result<String, CommandError> {
+    let start = reader.cursor();
+    let mut depth = 0;
+    while reader.can_read() && (depth > 0 || reader.peek() != ' ') {
+        match reader.peek() {
+            '[' | '{' => depth += 1,
+            ']' | '}' => depth -= 1,
+            '"' | '\'' => {
+                reader.read_quoted_string()?;
+                continue;
+            }
+            _ => {}
+        }
+        reader.skip();
+    }
+    let path: String = reader.read().chars().skip(start).collect();
+    if path.is_empty() || depth != 0 {
+        reader.set_cursor(start);
+        return Err(CommandError::at("Invalid NBT path element".to_string(), reader));
+    }
+    Ok(path)
+}
+
+// The same numbers vanilla uses for slots in /replaceitem
+fn item_slot(name: &str) -> Option<i32> {
+    let numbered = |prefix: &str, count: i32, offset: i32| {
+        name.strip_prefix(prefix)
+            .and_then(|number| number.parse::<i32>().ok())
+            .filter(|number| (0..count).contains(number))
+            .map(|number| number + offset)
+    };
+    match name {
+        "weapon" | "weapon.mainhand" => Some(98),
+        "weapon.offhand" => Some(99),
+        "armor.feet" => Some(100),
+        "armor.legs" => Some(101),
+        "armor.chest" => Some(102),
+        "armor.head" => Some(103),
+        "horse.saddle" => Some(400),
+        "horse.armor" => Some(401),
+        "horse.chest" => Some(499),
+        _ => numbered("container.", 54, 0)
+            .or_else(|| numbered("hotbar.", 9, 0))
+            .or_else(|| numbered("inventory.", 27, 9))
+            .or_else(|| numbered("enderchest.", 27, 200))
+            .or_else(|| numbered("villager.", 8, 300))
+            .or_else(|| numbered("horse.", 15, 500)),
+    }
+}
+
+fn is_scoreboard_slot(name: &str) -> bool {
+    match name.strip_prefix("sidebar.team.") {
+        Some(color) => COLORS[..16].contains(&color),
+        None => ["list", "sidebar", "belowName"].contains(&name),
+    }
+}
+
+// Reads one JSON value and leaves the cursor right after it
+fn read_component(reader: &mut StringReader) -> Result<Chat, CommandError> {
+    let remaining = reader.remaining();
+    let mut values = serde_json::Deserializer::from_str(&remaining).into_iter::<Chat>();
+    match values.next() {
+        Some(Ok(value)) => {
+            let length = remaining[..values.byte_offset()].chars().count();
+            reader.set_cursor(reader.cursor() + length);
+            Ok(value)
+        }
+        Some(Err(e)) => Err(CommandError::at(format!("Invalid chat component: {}", e), reader)),
+        None => Err(CommandError::at("Invalid chat component: empty".to_string(), reader)),
+    }
+}
+
+// Checks a number against the bounds of its parser, the cursor goes back to the start on failure
+fn check_range<T: PartialOrd + std::fmt::Display>(
+    kind: &str,
+    value: T,
+    min: &Option<T>,
+    max: &Option<T>,
+    reader: &mut StringReader,
+    start: usize,
+) -> Result<T, CommandError> {
+    if let Some(min) = min {
+        if value < *min {
+            reader.set_cursor(start);
+            return Err(CommandError::at(
+                format!("{} must not be less than {}, found {}", kind, min, value),
+                reader,
+            ));
+        }
+    }
+    if let Some(max) = max {
+        if value > *max {
+            reader.set_cursor(start);
+            return Err(CommandError::at(
+                format!("{} must not be more than {}, found {}", kind, max, value),
+                reader,
+            ));
+        }
+    }
+    Ok(value)
+}
+
+impl CommandParserType {
+    pub fn parse(&self, reader: &mut StringReader) -> Result<ArgumentValue, CommandError> {
+        let start = reader.cursor();
+        match self {
+            CommandParserType::BrigadierBool() => Ok(ArgumentValue::Bool(reader.read_boolean()?)),
+            CommandParserType::BrigadierFloat(min, max) => {
+                let value = reader.read_float()?;
+                Ok(ArgumentValue::Float(check_range("Float", value, min, max, reader, start)?))
+            }
+            CommandParserType::BrigadierDouble(min, max) => {
+                let value = reader.read_double()?;
+                Ok(ArgumentValue::Double(check_range("Double", value, min, max, reader, start)?))
+            }
+            CommandParserType::BrigadierInteger(min, max) => {
+                let value = reader.read_int()?;
+                Ok(ArgumentValue::Integer(check_range("Integer", value, min, max, reader, start)?))
+            }
+            CommandParserType::BrigadierLong(min, max) => {
+                let value = reader.read_long()?;
+                Ok(ArgumentValue::Long(check_range("Long", value, min, max, reader, start)?))
+            }
+            CommandParserType::BrigadierString(BrigadierStringArgument::SingleWord) => {
+                Ok(ArgumentValue::String(reader.read_unquoted_string()))
+            }
+            CommandParserType::BrigadierString(BrigadierStringArgument::QuotablePhrase) => {
+                Ok(ArgumentValue::String(reader.read_string()?))
+            }
+            CommandParserType::BrigadierString(BrigadierStringArgument::GreedyPhrase)
+            | CommandParserType::MinecraftMessage() => {
+                let text = reader.remaining();
+                reader.set_cursor(start + text.chars().count());
+                Ok(ArgumentValue::String(text))
+            }
+            CommandParserType::MinecraftEntity(single, only_players) => {
+                Ok(ArgumentValue::EntitySelector(read_entity_selector(reader, *single, *only_players)?))
+            }
+            CommandParserType::MinecraftGameProfile() => {
+                Ok(ArgumentValue::EntitySelector(read_entity_selector(reader, false, true)?))
+            }
+            CommandParserType::MinecraftScoreHolder(_) => {
+                Ok(ArgumentValue::String(read_until_space(reader)))
+            }
+            CommandParserType::MinecraftBlockPos() => {
+                Ok(ArgumentValue::Coordinates(read_block_pos(reader)?))
+            }
+            CommandParserType::MinecraftColumnPos() => Ok(ArgumentValue::Coordinates(read_column(
+                reader,
+                read_world_block_coordinate,
+            )?)),
+            CommandParserType::MinecraftVec3() => Ok(ArgumentValue::Coordinates(read_vec3(reader)?)),
+            CommandParserType::MinecraftVec2() => Ok(ArgumentValue::Coordinates(read_column(
+                reader,
+                |reader| read_world_coordinate(reader, true),
+            )?)),
+            CommandParserType::MinecraftRotation() => {
+                let values = read_separated(reader, 2, |reader, _| read_world_coordinate(reader, false))?;
+                Ok(ArgumentValue::Rotation(values[0], values[1]))
+            }
+            CommandParserType::MinecraftAngle() => Ok(ArgumentValue::Angle(read_angle(reader)?)),
+            CommandParserType::MinecraftBlockState() => read_block_state(reader, false),
+            CommandParserType::MinecraftBlockPredicate() => read_block_state(reader, true),
+            CommandParserType::MinecraftItemStack() => read_item_stack(reader, false),
+            CommandParserType::MinecraftItemPredicate() => read_item_stack(reader, true),
+            CommandParserType::MinecraftColor() => Ok(ArgumentValue::String(read_one_of(
+                reader,
+                &COLORS,
+                |color| format!("Unknown color '{}'", color),
+            )?)),
+            CommandParserType::MinecraftComponent() => {
+                Ok(ArgumentValue::Component(read_component(reader)?))
+            }
+            CommandParserType::MinecraftNBT() => Ok(ArgumentValue::NBTTag(snbt_reader::read_compound(reader)?)),
+            CommandParserType::MinecraftNBTTag() => Ok(ArgumentValue::NBTTag(snbt_reader::read_value(reader)?)),
+            CommandParserType::MinecraftNBTPath() => Ok(ArgumentValue::String(read_nbt_path(reader)?)),
+            CommandParserType::MinecraftObjective() => {
+                let name = reader.read_unquoted_string();
+                if name.chars().count() > 16 {
+                    reader.set_cursor(start);
+                    return Err(CommandError::at(
+                        "Objective names cannot be longer than 16 characters".to_string(),
+                        reader,
+                    ));
+                }
+                Ok(ArgumentValue::String(name))
+            }
+            CommandParserType::MinecraftObjectiveCriteria() => {
+                Ok(ArgumentValue::String(read_until_space(reader)))
+            }
+            CommandParserType::MinecraftOperation() => {
+                let operation = read_until_space(reader);
+                let operations = ["=", "+=", "-=", "*=", "/=", "%=", "<", ">", "><"];
+                if operation.is_empty() {
+                    return Err(CommandError::at("Expected operation".to_string(), reader));
+                }
+                if !operations.contains(&operation.as_str()) {
+                    reader.set_cursor(start);
+                    return Err(CommandError::at("Invalid operation".to_string(), reader));
+                }
+                Ok(ArgumentValue::String(operation))
+            }
+            CommandParserType::MinecraftParticle() => {
+                Ok(ArgumentValue::ResourceLocation(read_resource_location(reader)?))
+            }
+            CommandParserType::MinecraftScoreboardSlot() => {
+                let slot = reader.read_unquoted_string();
+                if !is_scoreboard_slot(&slot) {
+                    reader.set_cursor(start);
+                    return Err(CommandError::at(format!("Unknown display slot '{}'", slot), reader));
+                }
+                Ok(ArgumentValue::String(slot))
+            }
+            CommandParserType::MinecraftSwizzle() => {
+                let swizzle = reader.read_unquoted_string();
+                let mut seen = vec![];
+                for axis in swizzle.chars() {
+                    if !"xyz".contains(axis) || seen.contains(&axis) {
+                        reader.set_cursor(start);
+                        return Err(CommandError::at(
+                            "Invalid swizzle, expected combination of 'x', 'y' and 'z'".to_string(),
+                            reader,
+                        ));
+                    }
+                    seen.push(axis);
+                }
+                Ok(ArgumentValue::String(swizzle))
+            }
+            CommandParserType::MinecraftTeam() => Ok(ArgumentValue::String(reader.read_unquoted_string())),
+            CommandParserType::MinecraftItemSlot() => {
+                let name = reader.read_unquoted_string();
+                match item_slot(&name) {
+                    Some(slot) => Ok(ArgumentValue::Integer(slot)),
+                    None => {
+                        reader.set_cursor(start);
+                        Err(CommandError::at(format!("Unknown slot '{}'", name), reader))
+                    }
+                }
+            }
+            CommandParserType::MinecraftResourceLocation()
+            | CommandParserType::MinecraftDimension()
+            | CommandParserType::MinecraftResource(_)
+            | CommandParserType::MinecraftResourceKey(_) => {
+                Ok(ArgumentValue::ResourceLocation(read_resource_location(reader)?))
+            }
+            CommandParserType::MinecraftFunction()
+            | CommandParserType::MinecraftResourceOrTag(_)
+            | CommandParserType::MinecraftResourceOfTagKey(_) => {
+                Ok(ArgumentValue::ResourceLocation(read_tag_or_resource_location(reader)?))
+            }
+            CommandParserType::MinecraftEntityAnchor() => Ok(ArgumentValue::String(read_one_of(
+                reader,
+                &["feet", "eyes"],
+                |anchor| format!("Invalid entity anchor position {}", anchor),
+            )?)),
+            CommandParserType::MinecraftIntRange() => {
+                let (min, max) = read_range(reader, "integer")?;
+                Ok(ArgumentValue::IntRange(min, max))
+            }
+            CommandParserType::MinecraftFloatRange() => {
+                let (min, max) = read_range(reader, "double")?;
+                Ok(ArgumentValue::FloatRange(min, max))
+            }
+            CommandParserType::MinecraftGamemode() => {
+                let name = reader.read_unquoted_string();
+                match Gamemode::from_name(&name) {
+                    Some(gamemode) => Ok(ArgumentValue::Gamemode(gamemode)),
+                    None => {
+                        reader.set_cursor(start);
+                        Err(CommandError::at(format!("Unknown game mode: {}", name), reader))
+                    }
+                }
+            }
+            CommandParserType::MinecraftTime(min) => Ok(ArgumentValue::Integer(read_time(reader, *min)?)),
+            CommandParserType::MinecraftTemplateMirror() => Ok(ArgumentValue::String(read_one_of(
+                reader,
+                &["none", "front_back", "left_right"],
+                |value| format!("Invalid value \"{}\"", value),
+            )?)),
+            CommandParserType::MinecraftTemplateRotation() => Ok(ArgumentValue::String(read_one_of(
+                reader,
+                &["none", "clockwise_90", "180", "counterclockwise_90"],
+                |value| format!("Invalid value \"{}\"", value),
+            )?)),
+            CommandParserType::MinecraftHeightmap() => Ok(ArgumentValue::String(read_one_of(
+                reader,
+                &["world_surface", "motion_blocking", "motion_blocking_no_leaves", "ocean_floor"],
+                |value| format!("Invalid value \"{}\"", value),
+            )?)),
+            CommandParserType::MinecraftUUID() => Ok(ArgumentValue::Uuid(read_uuid(reader)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ArgumentValue, Coordinates, WorldCoordinate};
+    use crate::player::Gamemode;
+    use crate::server::{CommandParserType, StringReader};
+
+    fn parse(parser: CommandParserType, input: &str) -> Result<ArgumentValue, String> {
+        parser.parse(&mut StringReader::new(input)).map_err(|e| e.message)
+    }
+
+    fn absolute(value: f64) -> WorldCoordinate {
+        WorldCoordinate { relative: false, value }
+    }
+
+    fn relative(value: f64) -> WorldCoordinate {
+        WorldCoordinate { relative: true, value }
+    }
+
+    #[test]
+    fn test_coordinates() {
+        assert_eq!(
+            parse(CommandParserType::MinecraftBlockPos(), "1 ~ ~-2"),
+            Ok(ArgumentValue::Coordinates(Coordinates::World(absolute(1.0), relative(0.0), relative(-2.0))))
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftVec3(), "1 2 3.25"),
+            Ok(ArgumentValue::Coordinates(Coordinates::World(absolute(1.5), absolute(2.0), absolute(3.25))))
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftVec3(), "^ ^1 ^"),
+            Ok(ArgumentValue::Coordinates(Coordinates::Local(0.0, 1.0, 0.0)))
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftBlockPos(), "1 2"),
+            Err("Incomplete (expected 3 coordinates)".to_string())
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftBlockPos(), "^ 1 2"),
+            Err("Cannot mix world & local coordinates (everything must either use ^ or not)".to_string())
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftBlockPos(), "1.5 2 3"),
+            Err("Invalid integer '1.5'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_local_coordinates() {
+        // Looking south, forwards is +z and left is +x
+        let (x, y, z) = Coordinates::Local(1.0, 2.0, 3.0).position((0.0, 0.0, 0.0), (0.0, 0.0));
+        assert!((x - 1.0).abs() < 1e-9 && (y - 2.0).abs() < 1e-9 && (z - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_simple_types() {
+        assert_eq!(
+            parse(CommandParserType::MinecraftGamemode(), "creative"),
+            Ok(ArgumentValue::Gamemode(Gamemode::Creative))
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftGamemode(), "hard"),
+            Err("Unknown game mode: hard".to_string())
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftColor(), "pink"),
+            Err("Unknown color 'pink'".to_string())
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftResourceLocation(), "stone"),
+            Ok(ArgumentValue::ResourceLocation("minecraft:stone".to_string()))
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftResourceLocation(), "a:b:c"),
+            Err("Invalid ID".to_string())
+        );
+        assert_eq!(parse(CommandParserType::MinecraftTime(0), "1.5d"), Ok(ArgumentValue::Integer(36000)));
+        assert_eq!(parse(CommandParserType::MinecraftTime(0), "3x"), Err("Invalid unit".to_string()));
+        assert_eq!(
+            parse(CommandParserType::MinecraftTime(0), "-5s"),
+            Err("Tick count must be non-negative".to_string())
+        );
+        assert_eq!(
+            parse(CommandParserType::MinecraftUUID(), "not-a-uuid"),
+            Err("Invalid UUID".to_string())
+        );
+        assert_eq!(parse(CommandParserType::MinecraftIntRange(), "..5"), Ok(ArgumentValue::IntRange(None, Some(5))));
+        assert_eq!(parse(CommandParserType::MinecraftIntRange(), "3"), Ok(ArgumentValue::IntRange(Some(3), Some(3))));
+        assert_eq!(
+            parse(CommandParserType::MinecraftIntRange(), "5..1"),
+            Err("Min cannot be bigger than max".to_string())
+        );
+    }
+}
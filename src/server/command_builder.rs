@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use crate::player::OPLevel;
+
+use super::command_parser::BrigadierStringArgument;
+use super::CommandContext;
+use super::CommandError;
+use super::CommandHandler;
+use super::CommandParserType;
+use super::CommandSuggestionType;
+
+#[derive(Clone)]
+pub enum CommandBuilderKind {
+    Literal(String),
+    Argument(String, CommandParserType, Option<CommandSuggestionType>),
+}
+
+// Describes a node and everything below it, the dispatcher turns this into CommandNodes:
+// literal("time").then(literal("set").then(argument("time", integer()).executes(...)))
+#[derive(Clone)]
+pub struct CommandBuilder {
+    pub kind: CommandBuilderKind,
+    pub children: Vec<CommandBuilder>,
+    pub handler: Option<CommandHandler>,
+    // The lowest level that may use this node
+    pub requirement: OPLevel,
+    // A node like mcrust.command.gamemode, granting or denying it overrides the level
+    pub permission: Option<String>,
+}
+
+pub fn literal(name: &str) -> CommandBuilder {
+    CommandBuilder::new(CommandBuilderKind::Literal(name.to_string()))
+}
+
+pub fn argument(name: &str, parser: CommandParserType) -> CommandBuilder {
+    CommandBuilder::new(CommandBuilderKind::Argument(name.to_string(), parser, None))
+}
+
+impl CommandBuilder {
+    fn new(kind: CommandBuilderKind) -> Self {
+        Self {
+            kind,
+            children: vec![],
+            handler: None,
+            requirement: OPLevel::Player,
+            permission: None,
+        }
+    }
+
+    pub fn then(mut self, child: CommandBuilder) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn executes<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&mut CommandContext) -> Result<i32, CommandError> + Send + Sync + 'static,
+    {
+        self.handler = Some(Arc::new(handler));
+        self
+    }
+
+    pub fn requires(mut self, level: OPLevel) -> Self {
+        self.requirement = level;
+        self
+    }
+
+    pub fn permission(mut self, node: &str) -> Self {
+        self.permission = Some(node.to_string());
+        self
+    }
+
+    // Only arguments can have suggestions
+    pub fn suggests(mut self, suggestion_type: CommandSuggestionType) -> Self {
+        if let CommandBuilderKind::Argument(_, _, suggestions) = &mut self.kind {
+            *suggestions = Some(suggestion_type);
+        }
+        self
+    }
+
+    // The client sends a Tab-Complete packet for these instead of completing them by itself
+    pub fn suggested_by_server(self) -> Self {
+        self.suggests(CommandSuggestionType::MinecraftAskServer)
+    }
+}
+
+// Shorthands for the argument types
+
+pub fn boolean() -> CommandParserType {
+    CommandParserType::BrigadierBool()
+}
+
+pub fn integer_between(min: i32, max: i32) -> CommandParserType {
+    CommandParserType::BrigadierInteger(Some(min), Some(max))
+}
+
+pub fn integer_min(min: i32) -> CommandParserType {
+    CommandParserType::BrigadierInteger(Some(min), None)
+}
+
+pub fn word() -> CommandParserType {
+    CommandParserType::BrigadierString(BrigadierStringArgument::SingleWord)
+}
+
+pub fn color() -> CommandParserType {
+    CommandParserType::MinecraftColor()
+}
+
+// Entity arguments take names, UUIDs and selectors
+
+pub fn entity() -> CommandParserType {
+    CommandParserType::MinecraftEntity(true, false)
+}
+
+pub fn entities() -> CommandParserType {
+    CommandParserType::MinecraftEntity(false, false)
+}
+
+pub fn players() -> CommandParserType {
+    CommandParserType::MinecraftEntity(false, true)
+}
+
+pub fn game_profile() -> CommandParserType {
+    CommandParserType::MinecraftGameProfile()
+}
+
+pub fn vec3() -> CommandParserType {
+    CommandParserType::MinecraftVec3()
+}
+
+pub fn rotation() -> CommandParserType {
+    CommandParserType::MinecraftRotation()
+}
+
+pub fn message() -> CommandParserType {
+    CommandParserType::MinecraftMessage()
+}
+
+pub fn item_stack() -> CommandParserType {
+    CommandParserType::MinecraftItemStack()
+}
+
+pub fn time() -> CommandParserType {
+    CommandParserType::MinecraftTime(0)
+}
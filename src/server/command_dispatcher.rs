@@ -0,0 +1,716 @@
+use std::collections::HashMap;
+use std::convert::TryInto;
+use std::sync::Arc;
+
+use crate::chat::Chat;
+use crate::nbt::NBTTag;
+use crate::player::Look;
+use crate::player::OPLevel;
+use crate::player::Permissions;
+use crate::player::Player;
+use crate::player::Position;
+use crate::world::World;
+use crate::Server;
+
+use uuid::Uuid;
+
+use super::ArgumentValue;
+use super::CommandBuilder;
+use super::CommandBuilderKind;
+use super::CommandError;
+use super::CommandNode;
+use super::EntitySelector;
+use super::SelectorTarget;
+use super::ServerData;
+use super::StringReader;
+use super::Suggestions;
+use super::{argument_candidates, matches_input};
+
+pub type CommandHandler =
+    Arc<dyn Fn(&mut CommandContext) -> Result<i32, CommandError> + Send + Sync>;
+
+// Who ran a command
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandSource {
+    Player(i32),
+    Console,
+}
+
+// Everything a command handler gets to work with
+pub struct CommandContext<'a> {
+    pub source: CommandSource,
+    pub server: &'a Server,
+    pub server_data: &'a mut ServerData,
+    arguments: HashMap<String, ArgumentValue>,
+    // Messages for whoever ran the command
+    pub feedback: Vec<Chat>,
+}
+
+impl<'a> CommandContext<'a> {
+    pub fn new(source: CommandSource, server: &'a Server, server_data: &'a mut ServerData) -> Self {
+        Self {
+            source,
+            server,
+            server_data,
+            arguments: HashMap::new(),
+            feedback: vec![],
+        }
+    }
+
+    pub fn send_feedback(&mut self, message: Chat) {
+        self.feedback.push(message);
+    }
+
+    pub fn world(&self) -> Result<&World, CommandError> {
+        self.server_data
+            .settings
+            .worlds
+            .get(&self.server_data.settings.selected_world)
+            .ok_or_else(|| CommandError::new("Invalid selected world".to_string()))
+    }
+
+    pub fn world_mut(&mut self) -> Result<&mut World, CommandError> {
+        let selected_world = self.server_data.settings.selected_world.clone();
+        self.server_data
+            .settings
+            .worlds
+            .get_mut(&selected_world)
+            .ok_or_else(|| CommandError::new("Invalid selected world".to_string()))
+    }
+
+    // Looks at a player in the selected world without keeping it locked
+    pub fn read_player<T>(&self, player_eid: i32, f: impl FnOnce(&Player) -> T) -> Result<T, CommandError> {
+        let world = self.world()?;
+        let entity_arc = world
+            .get_entity(player_eid)
+            .map_err(|e| CommandError::new(format!("Could not find player: {:?}", e)))?
+            .ok_or_else(|| CommandError::new("Player does not exist".to_string()))?;
+        let entity = entity_arc
+            .read()
+            .map_err(|e| CommandError::new(format!("Could not lock player for reading: {}", e)))?;
+        let player = entity
+            .as_player()
+            .map_err(|e| CommandError::new(format!("Could not load player: {:?}", e)))?;
+        Ok(f(player))
+    }
+
+    pub fn write_player<T>(
+        &self,
+        player_eid: i32,
+        f: impl FnOnce(&mut Player) -> T,
+    ) -> Result<T, CommandError> {
+        let world = self.world()?;
+        let entity_arc = world
+            .get_entity(player_eid)
+            .map_err(|e| CommandError::new(format!("Could not find player: {:?}", e)))?
+            .ok_or_else(|| CommandError::new("Player does not exist".to_string()))?;
+        let mut entity = entity_arc
+            .write()
+            .map_err(|e| CommandError::new(format!("Could not lock player for writing: {}", e)))?;
+        let player = entity
+            .as_player_mut()
+            .map_err(|e| CommandError::new(format!("Could not load player: {:?}", e)))?;
+        Ok(f(player))
+    }
+
+    pub fn entity_name(&self, eid: i32) -> Result<String, CommandError> {
+        let entity_arc = self
+            .world()?
+            .get_entity(eid)
+            .map_err(|e| CommandError::new(format!("Could not find entity: {:?}", e)))?
+            .ok_or_else(|| CommandError::new("Entity does not exist".to_string()))?;
+        let entity = entity_arc
+            .read()
+            .map_err(|e| CommandError::new(format!("Could not lock entity for reading: {}", e)))?;
+        Ok(entity.name())
+    }
+
+    // The name of the player that ran the command, the console calls itself Server
+    pub fn source_name(&self) -> Result<String, CommandError> {
+        match self.source {
+            CommandSource::Player(player_eid) => {
+                self.read_player(player_eid, |player| player.username.clone())
+            }
+            CommandSource::Console => Ok("Server".to_string()),
+        }
+    }
+
+    pub fn source_eid(&self) -> Option<i32> {
+        match self.source {
+            CommandSource::Player(player_eid) => Some(player_eid),
+            CommandSource::Console => None,
+        }
+    }
+
+    // Where the command runs and which way it faces, the console sits at the spawn
+    pub fn source_position(&self) -> Result<(Position, Look), CommandError> {
+        match self.source {
+            CommandSource::Player(player_eid) => {
+                self.read_player(player_eid, |player| (player.position.clone(), player.look.clone()))
+            }
+            CommandSource::Console => {
+                let spawn_chunk = &self.world()?.spawn_chunk;
+                let position = Position {
+                    x: (spawn_chunk.x * 16 + 8) as f64,
+                    y: 64.0,
+                    z: (spawn_chunk.z * 16 + 8) as f64,
+                    on_ground: true,
+                };
+                Ok((position, Look { yaw: 0.0, pitch: 0.0 }))
+            }
+        }
+    }
+
+    // For commands that only make sense for a player
+    pub fn source_player(&self) -> Result<i32, CommandError> {
+        self.source_eid()
+            .ok_or_else(|| CommandError::new("A player is required to run this command here".to_string()))
+    }
+
+    // The console can do everything
+    pub fn permissions(&self) -> Result<Permissions, CommandError> {
+        match self.source {
+            CommandSource::Player(player_eid) => {
+                self.read_player(player_eid, |player| player.permissions.clone())
+            }
+            CommandSource::Console => Ok(Permissions::new(OPLevel::Owner)),
+        }
+    }
+
+    // Everyone online in the selected world
+    pub fn player_names(&self) -> Vec<String> {
+        let world = match self.server_data.settings.worlds.get(&self.server_data.settings.selected_world) {
+            Some(world) => world,
+            None => return vec![],
+        };
+        match world.get_players() {
+            Ok(players) => players.into_iter().map(|player| player.username).collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    pub fn argument(&self, name: &str) -> Result<&ArgumentValue, CommandError> {
+        self.arguments.get(name).ok_or_else(|| {
+            CommandError::new(format!("No such argument '{}' exists on this command", name))
+        })
+    }
+
+    pub fn get_bool(&self, name: &str) -> Result<bool, CommandError> {
+        match self.argument(name)? {
+            ArgumentValue::Bool(value) => Ok(*value),
+            x => Err(CommandError::new(format!("Argument '{}' is not a bool: {:?}", name, x))),
+        }
+    }
+
+    pub fn get_integer(&self, name: &str) -> Result<i32, CommandError> {
+        match self.argument(name)? {
+            ArgumentValue::Integer(value) => Ok(*value),
+            x => Err(CommandError::new(format!("Argument '{}' is not an integer: {:?}", name, x))),
+        }
+    }
+
+    pub fn get_item_stack(&self, name: &str) -> Result<(String, Option<NBTTag>), CommandError> {
+        match self.argument(name)? {
+            ArgumentValue::ItemStack(id, nbt) => Ok((id.clone(), nbt.clone())),
+            x => Err(CommandError::new(format!("Argument '{}' is not an item: {:?}", name, x))),
+        }
+    }
+
+    // All entities an entity argument selects, it is an error if there are none
+    pub fn get_entities(&self, name: &str) -> Result<Vec<i32>, CommandError> {
+        let entities = self.get_selector(name)?.resolve(self)?;
+        if entities.is_empty() {
+            return Err(CommandError::new("No entity was found".to_string()));
+        }
+        Ok(entities)
+    }
+
+    pub fn get_entity(&self, name: &str) -> Result<i32, CommandError> {
+        Ok(self.get_entities(name)?[0])
+    }
+
+    // Like get_entities, but anything that is not a player is left out
+    pub fn get_players(&self, name: &str) -> Result<Vec<i32>, CommandError> {
+        let world = self.world()?;
+        let mut players = vec![];
+        for eid in self.get_selector(name)?.resolve(self)? {
+            let is_player = match world.get_entity(eid) {
+                Ok(Some(entity_arc)) => entity_arc
+                    .read()
+                    .map(|entity| entity.as_player().is_ok())
+                    .unwrap_or(false),
+                _ => false,
+            };
+            if is_player {
+                players.push(eid);
+            }
+        }
+        if players.is_empty() {
+            return Err(CommandError::new("No player was found".to_string()));
+        }
+        Ok(players)
+    }
+
+    pub fn get_player(&self, name: &str) -> Result<i32, CommandError> {
+        Ok(self.get_players(name)?[0])
+    }
+
+    // The UUIDs and names a game profile argument stands for. A player that is not online can
+    // still be named, but only an offline mode server knows their UUID without asking Mojang.
+    pub fn get_game_profiles(&self, name: &str) -> Result<Vec<(Uuid, String)>, CommandError> {
+        let selector = self.get_selector(name)?;
+        if let SelectorTarget::Name(username) = &selector.target {
+            if !self.player_names().contains(username) {
+                if self.server_data.settings.online {
+                    return Err(CommandError::new("Unknown player".to_string()));
+                }
+                return Ok(vec![(Player::offline_player_uuid(username), username.clone())]);
+            }
+        }
+        let mut profiles = vec![];
+        for player_eid in self.get_players(name)? {
+            profiles.push(self.read_player(player_eid, |player| (player.uuid, player.username.clone()))?);
+        }
+        Ok(profiles)
+    }
+
+    // The entity id of a player if they are online
+    pub fn player_eid(&self, uuid: Uuid) -> Option<i32> {
+        self.server_data.player_eids.read().ok()?.get(&uuid).copied()
+    }
+
+    // The UUIDs and entity ids of everyone online
+    pub fn online_players(&self) -> Vec<(Uuid, i32)> {
+        match self.server_data.player_eids.read() {
+            Ok(player_eids) => player_eids.iter().map(|(uuid, eid)| (*uuid, *eid)).collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    // Where a position argument points to, relative coordinates start at the source
+    pub fn get_position(&self, name: &str) -> Result<(f64, f64, f64), CommandError> {
+        let (position, look) = self.source_position()?;
+        match self.argument(name)? {
+            ArgumentValue::Coordinates(coordinates) => Ok(coordinates.position(
+                (position.x, position.y, position.z),
+                (look.yaw, look.pitch),
+            )),
+            x => Err(CommandError::new(format!("Argument '{}' is not a position: {:?}", name, x))),
+        }
+    }
+
+    // Yaw and pitch, relative ones start at where the source is looking
+    pub fn get_rotation(&self, name: &str) -> Result<(f32, f32), CommandError> {
+        let (_, look) = self.source_position()?;
+        match self.argument(name)? {
+            ArgumentValue::Rotation(yaw, pitch) => {
+                Ok((yaw.get(look.yaw as f64) as f32, pitch.get(look.pitch as f64) as f32))
+            }
+            x => Err(CommandError::new(format!("Argument '{}' is not a rotation: {:?}", name, x))),
+        }
+    }
+
+    fn get_selector(&self, name: &str) -> Result<&EntitySelector, CommandError> {
+        match self.argument(name)? {
+            ArgumentValue::EntitySelector(selector) => Ok(selector),
+            x => Err(CommandError::new(format!("Argument '{}' is not an entity: {:?}", name, x))),
+        }
+    }
+
+    pub fn get_string(&self, name: &str) -> Result<String, CommandError> {
+        match self.argument(name)? {
+            ArgumentValue::String(value) => Ok(value.clone()),
+            x => Err(CommandError::new(format!("Argument '{}' is not a string: {:?}", name, x))),
+        }
+    }
+}
+
+// The outcome of parsing, the node that has to be executed and the arguments on the way there
+#[derive(Debug)]
+pub struct ParsedCommand {
+    pub node: usize,
+    pub arguments: HashMap<String, ArgumentValue>,
+}
+
+// Parses commands against the same node tree the client gets in Declare Commands
+pub struct CommandDispatcher {
+    nodes: Vec<CommandNode>,
+    root: usize,
+    handlers: HashMap<usize, CommandHandler>,
+    // Nodes that need more than OPLevel::Player or have a permission node
+    requirements: HashMap<usize, (OPLevel, Option<String>)>,
+}
+
+impl CommandDispatcher {
+    pub fn new() -> Self {
+        Self {
+            nodes: vec![CommandNode::Root(false, vec![], None)],
+            root: 0,
+            handlers: HashMap::new(),
+            requirements: HashMap::new(),
+        }
+    }
+
+    // Adds a command below the root
+    pub fn register(&mut self, command: CommandBuilder) {
+        let index = self.add_node(command);
+        if let CommandNode::Root(_, children, _) = &mut self.nodes[self.root] {
+            children.push(index.try_into().unwrap());
+        }
+    }
+
+    // Flattens the builder into the node list, returns the index of its node
+    fn add_node(&mut self, builder: CommandBuilder) -> usize {
+        let index = self.nodes.len();
+        // Takes the spot until the children are in
+        self.nodes.push(CommandNode::Root(false, vec![], None));
+
+        let children = builder
+            .children
+            .into_iter()
+            .map(|child| self.add_node(child).try_into().unwrap())
+            .collect();
+        let executable = builder.handler.is_some();
+        self.nodes[index] = match builder.kind {
+            CommandBuilderKind::Literal(name) => CommandNode::Literal(executable, children, None, name),
+            CommandBuilderKind::Argument(name, parser, suggestions) => {
+                CommandNode::Argument(executable, children, None, name, parser, suggestions)
+            }
+        };
+
+        if let Some(handler) = builder.handler {
+            self.handlers.insert(index, handler);
+        }
+        if builder.requirement != OPLevel::Player || builder.permission.is_some() {
+            self.requirements.insert(index, (builder.requirement, builder.permission));
+        }
+        index
+    }
+
+    fn can_use(&self, node: usize, permissions: &Permissions) -> bool {
+        match self.requirements.get(&node) {
+            Some((level, permission)) => permissions.allows(permission.as_deref(), *level),
+            None => true,
+        }
+    }
+
+    // The nodes for Declare Commands, a client does not get to see what it is not allowed to use
+    pub fn nodes_for(&self, permissions: &Permissions) -> (Vec<CommandNode>, i32) {
+        let mut new_indices = HashMap::new();
+        let mut order = vec![];
+        let mut stack = vec![self.root];
+        while let Some(node) = stack.pop() {
+            if new_indices.contains_key(&node) {
+                continue;
+            }
+            new_indices.insert(node, order.len() as i32);
+            order.push(node);
+            let redirect = self.nodes[node].redirect();
+            let usable = self.nodes[node]
+                .children()
+                .iter()
+                .chain(redirect.iter())
+                .map(|child| *child as usize)
+                .filter(|child| self.can_use(*child, permissions));
+            stack.extend(usable);
+        }
+
+        let remap = |children: &Vec<i32>, redirect: &Option<i32>| {
+            let children = children
+                .iter()
+                .filter_map(|child| new_indices.get(&(*child as usize)).copied())
+                .collect::<Vec<i32>>();
+            let redirect = redirect.and_then(|redirect| new_indices.get(&(redirect as usize)).copied());
+            (children, redirect)
+        };
+        let nodes = order
+            .iter()
+            .map(|node| match &self.nodes[*node] {
+                CommandNode::Root(executable, children, redirect) => {
+                    let (children, redirect) = remap(children, redirect);
+                    CommandNode::Root(*executable, children, redirect)
+                }
+                CommandNode::Literal(executable, children, redirect, name) => {
+                    let (children, redirect) = remap(children, redirect);
+                    CommandNode::Literal(*executable, children, redirect, name.clone())
+                }
+                CommandNode::Argument(executable, children, redirect, name, parser, suggestions) => {
+                    let (children, redirect) = remap(children, redirect);
+                    CommandNode::Argument(
+                        *executable,
+                        children,
+                        redirect,
+                        name.clone(),
+                        parser.clone(),
+                        suggestions.clone(),
+                    )
+                }
+            })
+            .collect();
+        (nodes, new_indices[&self.root])
+    }
+
+    // Input is without the leading slash
+    pub fn execute(&self, input: &str, context: &mut CommandContext) -> Result<i32, CommandError> {
+        let parsed = self.parse(input, &context.permissions()?)?;
+        let handler = self.handlers.get(&parsed.node).ok_or_else(|| {
+            CommandError::at_cursor(
+                "Unknown or incomplete command, see below for error".to_string(),
+                &StringReader::new(input),
+                input.chars().count(),
+            )
+        })?;
+        context.arguments = parsed.arguments;
+        handler(context)
+    }
+
+    pub fn parse(&self, input: &str, permissions: &Permissions) -> Result<ParsedCommand, CommandError> {
+        let reader = StringReader::new(input);
+        let (node, reader, arguments) =
+            self.parse_children(self.root, &reader, &HashMap::new(), permissions)?;
+        if !self.nodes[node].is_executable() {
+            return Err(CommandError::unknown_command(&reader));
+        }
+        Ok(ParsedCommand { node, arguments })
+    }
+
+    // Suggestions for the last, unfinished argument of the input
+    pub fn suggest(&self, input: &str, context: &CommandContext) -> Result<Suggestions, CommandError> {
+        let permissions = context.permissions()?;
+        let mut reader = StringReader::new(input);
+        let mut node = self.root;
+
+        // Follow the input as far as there are whole arguments with a space behind them
+        'arguments: loop {
+            for child in self.relevant_children(node, &reader, &permissions) {
+                let mut child_reader = reader.clone();
+                let parsed = self.parse_node(child, &mut child_reader, &mut HashMap::new());
+                if parsed.is_ok() && child_reader.can_read() && child_reader.peek() == ' ' {
+                    child_reader.skip();
+                    reader = child_reader;
+                    node = child;
+                    continue 'arguments;
+                }
+            }
+            break;
+        }
+
+        let typed = reader.remaining();
+        let mut matches: Vec<String> = self
+            .usable_children(node, &permissions)
+            .into_iter()
+            .flat_map(|child| match &self.nodes[child] {
+                CommandNode::Literal(_, _, _, name) => vec![name.clone()],
+                CommandNode::Argument(_, _, _, _, parser, suggestion_type) => {
+                    argument_candidates(parser, suggestion_type, context)
+                }
+                CommandNode::Root(..) => vec![],
+            })
+            .filter(|candidate| matches_input(candidate, &typed))
+            .collect();
+        matches.sort();
+        matches.dedup();
+
+        Ok(Suggestions {
+            start: reader.cursor(),
+            length: typed.chars().count(),
+            matches,
+        })
+    }
+
+    // Tries every child that could match what comes next until one of them takes the rest of the
+    // input. The reader is at the start of the next argument.
+    fn parse_children(
+        &self,
+        node: usize,
+        reader: &StringReader,
+        arguments: &HashMap<String, ArgumentValue>,
+        permissions: &Permissions,
+    ) -> Result<(usize, StringReader, HashMap<String, ArgumentValue>), CommandError> {
+        let mut errors = vec![];
+        for child in self.relevant_children(node, reader, permissions) {
+            let mut child_reader = reader.clone();
+            let mut child_arguments = arguments.clone();
+            if let Err(e) = self.parse_node(child, &mut child_reader, &mut child_arguments) {
+                errors.push(e);
+                continue;
+            }
+            if !child_reader.can_read() {
+                return Ok((child, child_reader, child_arguments));
+            }
+            if child_reader.peek() != ' ' {
+                errors.push(CommandError::expected_separator(&child_reader));
+                continue;
+            }
+            child_reader.skip();
+            match self.parse_children(child, &child_reader, &child_arguments, permissions) {
+                Ok(result) => return Ok(result),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        if errors.len() == 1 {
+            Err(errors.remove(0))
+        } else if node == self.root {
+            Err(CommandError::unknown_command(reader))
+        } else {
+            Err(CommandError::unknown_argument(reader))
+        }
+    }
+
+    // The children of a redirect are the ones of the node it points to
+    fn usable_children(&self, node: usize, permissions: &Permissions) -> Vec<usize> {
+        let node = match self.nodes[node].redirect() {
+            Some(redirect) => &self.nodes[redirect as usize],
+            None => &self.nodes[node],
+        };
+        node.children()
+            .iter()
+            .map(|child| *child as usize)
+            .filter(|child| self.can_use(*child, permissions))
+            .collect()
+    }
+
+    // A literal that matches the next word wins over all arguments
+    fn relevant_children(&self, node: usize, reader: &StringReader, permissions: &Permissions) -> Vec<usize> {
+        let next_word = next_word(reader);
+        let children = self.usable_children(node, permissions);
+
+        let literal = children.iter().find(|child| match &self.nodes[**child] {
+            CommandNode::Literal(_, _, _, name) => *name == next_word,
+            _ => false,
+        });
+        if let Some(literal) = literal {
+            return vec![*literal];
+        }
+        children
+            .into_iter()
+            .filter(|child| matches!(self.nodes[*child], CommandNode::Argument(..)))
+            .collect()
+    }
+
+    fn parse_node(
+        &self,
+        node: usize,
+        reader: &mut StringReader,
+        arguments: &mut HashMap<String, ArgumentValue>,
+    ) -> Result<(), CommandError> {
+        match &self.nodes[node] {
+            CommandNode::Root(..) => Ok(()),
+            CommandNode::Literal(_, _, _, name) => {
+                if next_word(reader) != *name {
+                    return Err(CommandError::unknown_argument(reader));
+                }
+                reader.set_cursor(reader.cursor() + name.chars().count());
+                Ok(())
+            }
+            CommandNode::Argument(_, _, _, name, parser, _) => {
+                let value = parser.parse(reader)?;
+                arguments.insert(name.clone(), value);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn next_word(reader: &StringReader) -> String {
+    reader.remaining().split(' ').next().unwrap_or("").to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::CommandDispatcher;
+    use crate::packets::packet_writer::PacketWriter;
+    use crate::packets::protocol::ProtocolVersion;
+    use crate::player::{OPLevel, Permissions};
+    use crate::server::{argument, integer_between, literal, register_vanilla_commands, ArgumentValue, CommandNode};
+    use crate::server::{CommandParserType, CommandSuggestionType};
+
+    fn dispatcher() -> CommandDispatcher {
+        let mut dispatcher = CommandDispatcher::new();
+        dispatcher.register(literal("test").executes(|_| Ok(1)));
+        dispatcher.register(
+            literal("number").then(argument("value", integer_between(0, 10)).executes(|_| Ok(1))),
+        );
+        dispatcher.register(literal("secret").requires(OPLevel::Admin).executes(|_| Ok(1)));
+        dispatcher
+    }
+
+    #[test]
+    fn test_parse() {
+        let dispatcher = dispatcher();
+        assert_eq!(dispatcher.parse("test", &Permissions::new(OPLevel::Player)).unwrap().node, 1);
+
+        let parsed = dispatcher.parse("number 7", &Permissions::new(OPLevel::Player)).unwrap();
+        assert_eq!(parsed.node, 3);
+        assert_eq!(parsed.arguments["value"], ArgumentValue::Integer(7));
+    }
+
+    #[test]
+    fn test_errors() {
+        let dispatcher = dispatcher();
+        let error = dispatcher.parse("number", &Permissions::new(OPLevel::Player)).unwrap_err();
+        assert_eq!(error.message, "Unknown or incomplete command, see below for error");
+        assert_eq!(error.context().unwrap(), "number<--[HERE]");
+
+        let error = dispatcher.parse("number 11", &Permissions::new(OPLevel::Player)).unwrap_err();
+        assert_eq!(error.message, "Integer must not be more than 10, found 11");
+        assert_eq!(error.cursor, Some(7));
+
+        assert_eq!(
+            dispatcher.parse("test foo", &Permissions::new(OPLevel::Player)).unwrap_err().message,
+            "Incorrect argument for command"
+        );
+        assert_eq!(
+            dispatcher.parse("nope", &Permissions::new(OPLevel::Player)).unwrap_err().message,
+            "Unknown or incomplete command, see below for error"
+        );
+    }
+
+    #[test]
+    fn test_requirements() {
+        let dispatcher = dispatcher();
+        assert!(dispatcher.parse("secret", &Permissions::new(OPLevel::Gamemaster)).is_err());
+        assert!(dispatcher.parse("secret", &Permissions::new(OPLevel::Owner)).is_ok());
+
+        let (nodes, root) = dispatcher.nodes_for(&Permissions::new(OPLevel::Player));
+        assert_eq!(nodes.len(), 4);
+        assert!(matches!(&nodes[root as usize], CommandNode::Root(_, children, _) if children.len() == 2));
+        assert_eq!(dispatcher.nodes_for(&Permissions::new(OPLevel::Owner)).0.len(), 5);
+    }
+
+    #[test]
+    fn test_server_suggestions() {
+        let mut dispatcher = CommandDispatcher::new();
+        register_vanilla_commands(&mut dispatcher);
+        let (nodes, _) = dispatcher.nodes_for(&Permissions::new(OPLevel::Owner));
+
+        // Players and profiles are only known to the server, so the client has to ask it
+        let mut asked = 0;
+        for node in nodes.iter() {
+            if let CommandNode::Argument(_, _, _, _, parser, suggestions) = node {
+                if !matches!(parser, CommandParserType::MinecraftEntity(..) | CommandParserType::MinecraftGameProfile()) {
+                    continue;
+                }
+                assert!(matches!(suggestions, Some(CommandSuggestionType::MinecraftAskServer)));
+                let mut writer = PacketWriter::new(0x10);
+                node.write(&mut writer, &ProtocolVersion::latest());
+                // The first byte is the packet id, then come the flags
+                assert_eq!(writer.data()[1] & 0x10, 0x10);
+                assert!(writer.data().ends_with(b"minecraft:ask_server"));
+                asked += 1;
+            }
+        }
+        assert!(asked > 0);
+    }
+
+    #[test]
+    fn test_time_argument() {
+        // Anything after the identifier would be read as the next node by a 1.16 client
+        for version in [ProtocolVersion::oldest(), ProtocolVersion::latest()] {
+            let mut writer = PacketWriter::new(0x10);
+            CommandParserType::MinecraftTime(0).write(&mut writer, &version);
+            assert!(writer.data().ends_with(b"minecraft:time"));
+        }
+    }
+}
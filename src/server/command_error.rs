@@ -0,0 +1,91 @@
+use std::fmt;
+
+use crate::chat::Chat;
+
+use super::StringReader;
+
+// How many characters before the error are shown
+const CONTEXT_AMOUNT: usize = 10;
+
+// A command that could not be parsed or failed while running, the messages are the ones vanilla
+// uses so players see what they are used to
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommandError {
+    pub message: String,
+    // The input and where in it things went wrong, if the error is about the input
+    pub input: Option<String>,
+    pub cursor: Option<usize>,
+}
+
+impl CommandError {
+    pub fn new(message: String) -> Self {
+        Self {
+            message,
+            input: None,
+            cursor: None,
+        }
+    }
+
+    pub fn at(message: String, reader: &StringReader) -> Self {
+        Self::at_cursor(message, reader, reader.cursor())
+    }
+
+    pub fn at_cursor(message: String, reader: &StringReader, cursor: usize) -> Self {
+        Self {
+            message,
+            input: Some(reader.string()),
+            cursor: Some(cursor),
+        }
+    }
+
+    pub fn unknown_command(reader: &StringReader) -> Self {
+        Self::at("Unknown or incomplete command, see below for error".to_string(), reader)
+    }
+
+    pub fn unknown_argument(reader: &StringReader) -> Self {
+        Self::at("Incorrect argument for command".to_string(), reader)
+    }
+
+    pub fn expected_separator(reader: &StringReader) -> Self {
+        Self::at(
+            "Expected whitespace to end one argument, but found trailing data".to_string(),
+            reader,
+        )
+    }
+
+    // The input up to the error, like "...ommand foo<--[HERE]"
+    pub fn context(&self) -> Option<String> {
+        let (input, cursor) = match (&self.input, self.cursor) {
+            (Some(input), Some(cursor)) => (input, cursor),
+            _ => return None,
+        };
+        let chars: Vec<char> = input.chars().collect();
+        let cursor = cursor.min(chars.len());
+        let start = cursor.saturating_sub(CONTEXT_AMOUNT);
+        let mut context = String::new();
+        if start > 0 {
+            context.push_str("...");
+        }
+        context.extend(&chars[start..cursor]);
+        context.push_str("<--[HERE]");
+        Some(context)
+    }
+
+    // The lines a player gets to see
+    pub fn to_chat(&self) -> Vec<Chat> {
+        let mut lines = vec![Chat::new(self.message.clone()).color("red".to_string())];
+        if let Some(context) = self.context() {
+            lines.push(Chat::new(context).color("gray".to_string()));
+        }
+        lines
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.context() {
+            Some(context) => write!(f, "{}\n{}", self.message, context),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
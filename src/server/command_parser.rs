@@ -1,5 +1,6 @@
 #![allow(dead_code)]
 use crate::packets::packet_writer::PacketWriter;
+use crate::packets::protocol::ProtocolVersion;
 
 #[derive(Copy, Clone, Debug)]
 pub enum BrigadierStringArgument {
@@ -135,7 +136,7 @@ impl CommandParserType {
 }
 
 impl CommandParserType {
-    pub fn write(&self, writer: &mut PacketWriter) {
+    pub fn write(&self, writer: &mut PacketWriter, protocol: &ProtocolVersion) {
         writer.add_string(&self.to_identifier());
         match self {
             CommandParserType::BrigadierBool() => {},
@@ -255,8 +256,11 @@ impl CommandParserType {
             CommandParserType::MinecraftFloatRange() => {},
             CommandParserType::MinecraftDimension() => {},
             CommandParserType::MinecraftGamemode() => {},
+            // Older clients check the minimum by themselves, the server always does
             CommandParserType::MinecraftTime(min) => {
-                writer.add_signed_int(*min);
+                if protocol.mapping.time_argument_has_min() {
+                    writer.add_signed_int(*min);
+                }
             },
             CommandParserType::MinecraftResourceOrTag(registry) |
             CommandParserType::MinecraftResourceOfTagKey(registry) |
@@ -0,0 +1,99 @@
+use super::CommandContext;
+use super::CommandParserType;
+use super::CommandSuggestionType;
+use super::COLORS;
+
+// What the client gets to pick from, `start` and `length` are the part of the input the matches
+// replace
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestions {
+    pub start: usize,
+    pub length: usize,
+    pub matches: Vec<String>,
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+// Everything that could go into an argument, before looking at what has been typed
+pub fn argument_candidates(
+    parser: &CommandParserType,
+    suggestion_type: &Option<CommandSuggestionType>,
+    context: &CommandContext,
+) -> Vec<String> {
+    match suggestion_type {
+        Some(CommandSuggestionType::MinecraftAllRecipes) => {
+            return context.server_data.recipes.iter().map(|recipe| recipe.id.clone()).collect();
+        }
+        Some(CommandSuggestionType::MinecraftAvailableBiomes) => {
+            return context.server_data.dimension_codec.biomes.keys().cloned().collect();
+        }
+        // TODO: there are no sound or entity type registries yet
+        Some(CommandSuggestionType::MinecraftAvailableSounds)
+        | Some(CommandSuggestionType::MinecraftSummonableEntities) => return vec![],
+        Some(CommandSuggestionType::MinecraftAskServer) | None => {}
+    }
+
+    match parser {
+        CommandParserType::BrigadierBool() => strings(&["true", "false"]),
+        CommandParserType::MinecraftEntity(_, only_players) => {
+            let selectors: &[&str] = if *only_players {
+                &["@a", "@p", "@r", "@s"]
+            } else {
+                &["@a", "@e", "@p", "@r", "@s"]
+            };
+            let mut candidates = strings(selectors);
+            candidates.extend(context.player_names());
+            candidates
+        }
+        CommandParserType::MinecraftGameProfile() => {
+            let mut candidates = strings(&["@a", "@p", "@r", "@s"]);
+            candidates.extend(context.player_names());
+            candidates
+        }
+        CommandParserType::MinecraftScoreHolder(_) => context.player_names(),
+        CommandParserType::MinecraftBlockPos() | CommandParserType::MinecraftVec3() => {
+            strings(&["~", "~ ~", "~ ~ ~"])
+        }
+        CommandParserType::MinecraftColumnPos()
+        | CommandParserType::MinecraftVec2()
+        | CommandParserType::MinecraftRotation() => strings(&["~", "~ ~"]),
+        CommandParserType::MinecraftAngle() => strings(&["~"]),
+        CommandParserType::MinecraftColor() => strings(&COLORS),
+        CommandParserType::MinecraftGamemode() => {
+            strings(&["survival", "creative", "adventure", "spectator"])
+        }
+        CommandParserType::MinecraftEntityAnchor() => strings(&["feet", "eyes"]),
+        CommandParserType::MinecraftOperation() => {
+            strings(&["=", "+=", "-=", "*=", "/=", "%=", "<", ">", "><"])
+        }
+        CommandParserType::MinecraftScoreboardSlot() => strings(&["list", "sidebar", "belowName"]),
+        CommandParserType::MinecraftSwizzle() => strings(&["x", "xy", "xz", "xyz", "y", "yz", "z"]),
+        CommandParserType::MinecraftDimension() => {
+            context.server_data.dimension_codec.dimensions.keys().cloned().collect()
+        }
+        CommandParserType::MinecraftTemplateMirror() => strings(&["none", "front_back", "left_right"]),
+        CommandParserType::MinecraftTemplateRotation() => {
+            strings(&["none", "clockwise_90", "180", "counterclockwise_90"])
+        }
+        CommandParserType::MinecraftHeightmap() => strings(&[
+            "world_surface",
+            "motion_blocking",
+            "motion_blocking_no_leaves",
+            "ocean_floor",
+        ]),
+        _ => vec![],
+    }
+}
+
+// Case does not matter and the minecraft namespace can be left out
+pub fn matches_input(candidate: &str, typed: &str) -> bool {
+    let candidate = candidate.to_lowercase();
+    let typed = typed.to_lowercase();
+    candidate.starts_with(&typed)
+        || candidate
+            .strip_prefix("minecraft:")
+            .map(|path| path.starts_with(&typed))
+            .unwrap_or(false)
+}
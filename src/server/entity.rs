@@ -1,5 +1,7 @@
 use crate::player::Player;
 
+use uuid::Uuid;
+
 use crate::error_type::ErrorType;
 
 #[derive(Clone)]
@@ -19,4 +21,29 @@ impl Entity {
             Entity::PlayerEntity(p) => Ok(p),
         }
     }
+
+    // The id of the entity type, what selectors filter on with type=
+    pub fn type_name(&self) -> &str {
+        match self {
+            Entity::PlayerEntity(_) => "minecraft:player",
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Entity::PlayerEntity(p) => p.username.clone(),
+        }
+    }
+
+    pub fn uuid(&self) -> Uuid {
+        match self {
+            Entity::PlayerEntity(p) => p.uuid,
+        }
+    }
+
+    pub fn position(&self) -> (f64, f64, f64) {
+        match self {
+            Entity::PlayerEntity(p) => (p.position.x, p.position.y, p.position.z),
+        }
+    }
 }
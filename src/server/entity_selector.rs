@@ -0,0 +1,435 @@
+use std::cmp::Ordering;
+
+use rand::seq::SliceRandom;
+use uuid::Uuid;
+
+use crate::player::Gamemode;
+
+use super::command_argument::{read_range, read_resource_location};
+use super::CommandContext;
+use super::CommandError;
+use super::StringReader;
+
+// Player names are at most this long, longer input is taken as a UUID
+const MAX_NAME_LENGTH: usize = 16;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelectorTarget {
+    // @p
+    NearestPlayer,
+    // @a
+    AllPlayers,
+    // @r
+    RandomPlayer,
+    // @s
+    Source,
+    // @e
+    AllEntities,
+    Name(String),
+    Uuid(Uuid),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SelectorSort {
+    Nearest,
+    Furthest,
+    Random,
+    Arbitrary,
+}
+
+// @a[name=!Steve,distance=..5,limit=2,sort=nearest] and friends. Options with a bool can be
+// negated with !, the bool is true when they are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntitySelector {
+    pub target: SelectorTarget,
+    pub names: Vec<(String, bool)>,
+    pub distance: Option<(Option<f64>, Option<f64>)>,
+    // Only what the options say, the selector type has its own defaults
+    pub limit: Option<usize>,
+    pub sort: Option<SelectorSort>,
+    pub gamemodes: Vec<(Gamemode, bool)>,
+    pub entity_types: Vec<(String, bool)>,
+    // Where distances are measured from, the source when left out
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub z: Option<f64>,
+    // Size of the box entities have to be in, starting at x, y, z
+    pub dx: Option<f64>,
+    pub dy: Option<f64>,
+    pub dz: Option<f64>,
+}
+
+impl EntitySelector {
+    fn new(target: SelectorTarget) -> Self {
+        Self {
+            target,
+            names: vec![],
+            distance: None,
+            limit: None,
+            sort: None,
+            gamemodes: vec![],
+            entity_types: vec![],
+            x: None,
+            y: None,
+            z: None,
+            dx: None,
+            dy: None,
+            dz: None,
+        }
+    }
+
+    pub fn parse(reader: &mut StringReader) -> Result<Self, CommandError> {
+        let start = reader.cursor();
+        if !reader.can_read() || reader.peek() != '@' {
+            return Self::parse_name_or_uuid(reader, start);
+        }
+
+        reader.skip();
+        if !reader.can_read() {
+            return Err(CommandError::at("Missing selector type".to_string(), reader));
+        }
+        let target = match reader.next() {
+            'p' => SelectorTarget::NearestPlayer,
+            'a' => SelectorTarget::AllPlayers,
+            'r' => SelectorTarget::RandomPlayer,
+            's' => SelectorTarget::Source,
+            'e' => SelectorTarget::AllEntities,
+            x => {
+                reader.set_cursor(start);
+                return Err(CommandError::at(format!("Unknown selector type '@{}'", x), reader));
+            }
+        };
+        let mut selector = Self::new(target);
+        if reader.can_read() && reader.peek() == '[' {
+            reader.skip();
+            selector.parse_options(reader)?;
+        }
+        Ok(selector)
+    }
+
+    fn parse_name_or_uuid(reader: &mut StringReader, start: usize) -> Result<Self, CommandError> {
+        while reader.can_read() && reader.peek() != ' ' {
+            reader.skip();
+        }
+        let text: String = reader.read().chars().skip(start).collect();
+        if !text.is_empty() && text.chars().count() <= MAX_NAME_LENGTH {
+            return Ok(Self::new(SelectorTarget::Name(text)));
+        }
+        match Uuid::parse_str(&text) {
+            Ok(uuid) => Ok(Self::new(SelectorTarget::Uuid(uuid))),
+            Err(_) => {
+                reader.set_cursor(start);
+                Err(CommandError::at("Invalid name or UUID".to_string(), reader))
+            }
+        }
+    }
+
+    fn parse_options(&mut self, reader: &mut StringReader) -> Result<(), CommandError> {
+        reader.skip_whitespace();
+        while reader.can_read() && reader.peek() != ']' {
+            reader.skip_whitespace();
+            let option_start = reader.cursor();
+            let option = reader.read_string()?;
+            reader.skip_whitespace();
+            if !reader.can_read() || reader.peek() != '=' {
+                reader.set_cursor(option_start);
+                return Err(CommandError::at(format!("Expected value for option '{}'", option), reader));
+            }
+            reader.skip();
+            reader.skip_whitespace();
+            self.parse_option(&option, reader, option_start)?;
+            reader.skip_whitespace();
+            if reader.can_read() && reader.peek() == ',' {
+                reader.skip();
+                continue;
+            }
+            break;
+        }
+        if !reader.can_read() || reader.peek() != ']' {
+            return Err(CommandError::at("Expected end of options".to_string(), reader));
+        }
+        reader.skip();
+        Ok(())
+    }
+
+    fn parse_option(&mut self, option: &str, reader: &mut StringReader, start: usize) -> Result<(), CommandError> {
+        let inapplicable = |reader: &mut StringReader| {
+            reader.set_cursor(start);
+            Err(CommandError::at(format!("Option '{}' isn't applicable here", option), reader))
+        };
+        match option {
+            "name" => {
+                let negated = read_negation(reader);
+                let name = reader.read_string()?;
+                if !negated && self.names.iter().any(|(_, negated)| !negated) {
+                    return inapplicable(reader);
+                }
+                self.names.push((name, negated));
+            }
+            "distance" => {
+                if self.distance.is_some() {
+                    return inapplicable(reader);
+                }
+                let value_start = reader.cursor();
+                let (min, max) = read_range::<f64>(reader, "double")?;
+                if min.map(|min| min < 0.0).unwrap_or(false) || max.map(|max| max < 0.0).unwrap_or(false) {
+                    reader.set_cursor(value_start);
+                    return Err(CommandError::at("Distance cannot be negative".to_string(), reader));
+                }
+                self.distance = Some((min, max));
+            }
+            "limit" => {
+                if self.limit.is_some() || self.target == SelectorTarget::Source {
+                    return inapplicable(reader);
+                }
+                let value_start = reader.cursor();
+                let limit = reader.read_int()?;
+                if limit < 1 {
+                    reader.set_cursor(value_start);
+                    return Err(CommandError::at("Limit must be at least 1".to_string(), reader));
+                }
+                self.limit = Some(limit as usize);
+            }
+            "sort" => {
+                if self.sort.is_some() || self.target == SelectorTarget::Source {
+                    return inapplicable(reader);
+                }
+                let value_start = reader.cursor();
+                let sort = reader.read_unquoted_string();
+                self.sort = Some(match sort.as_str() {
+                    "nearest" => SelectorSort::Nearest,
+                    "furthest" => SelectorSort::Furthest,
+                    "random" => SelectorSort::Random,
+                    "arbitrary" => SelectorSort::Arbitrary,
+                    _ => {
+                        reader.set_cursor(value_start);
+                        return Err(CommandError::at(
+                            format!("Invalid or unknown sort type '{}'", sort),
+                            reader,
+                        ));
+                    }
+                });
+            }
+            "gamemode" => {
+                let value_start = reader.cursor();
+                let negated = read_negation(reader);
+                let name = reader.read_unquoted_string();
+                let gamemode = Gamemode::from_name(&name).ok_or_else(|| {
+                    reader.set_cursor(value_start);
+                    CommandError::at(format!("Invalid or unknown game mode '{}'", name), reader)
+                })?;
+                if !negated && self.gamemodes.iter().any(|(_, negated)| !negated) {
+                    return inapplicable(reader);
+                }
+                self.gamemodes.push((gamemode, negated));
+            }
+            "type" => {
+                let negated = read_negation(reader);
+                let entity_type = read_resource_location(reader)?;
+                if !negated && self.entity_types.iter().any(|(_, negated)| !negated) {
+                    return inapplicable(reader);
+                }
+                self.entity_types.push((entity_type, negated));
+            }
+            "x" | "y" | "z" | "dx" | "dy" | "dz" => {
+                let value = reader.read_double()?;
+                let field = match option {
+                    "x" => &mut self.x,
+                    "y" => &mut self.y,
+                    "z" => &mut self.z,
+                    "dx" => &mut self.dx,
+                    "dy" => &mut self.dy,
+                    _ => &mut self.dz,
+                };
+                if field.is_some() {
+                    return inapplicable(reader);
+                }
+                *field = Some(value);
+            }
+            _ => {
+                reader.set_cursor(start);
+                return Err(CommandError::at(format!("Unknown option '{}'", option), reader));
+            }
+        }
+        Ok(())
+    }
+
+    // @p, @r and @s pick one entity unless the limit option says otherwise, @s can't have one
+    fn limit(&self) -> Option<usize> {
+        self.limit.or(match self.target {
+            SelectorTarget::NearestPlayer
+            | SelectorTarget::RandomPlayer
+            | SelectorTarget::Source
+            | SelectorTarget::Name(_)
+            | SelectorTarget::Uuid(_) => Some(1),
+            SelectorTarget::AllPlayers | SelectorTarget::AllEntities => None,
+        })
+    }
+
+    fn sort(&self) -> SelectorSort {
+        self.sort.unwrap_or(match self.target {
+            SelectorTarget::NearestPlayer => SelectorSort::Nearest,
+            SelectorTarget::RandomPlayer => SelectorSort::Random,
+            _ => SelectorSort::Arbitrary,
+        })
+    }
+
+    pub fn is_single(&self) -> bool {
+        self.limit() == Some(1)
+    }
+
+    // Whether anything but players could be selected
+    pub fn includes_entities(&self) -> bool {
+        self.target == SelectorTarget::AllEntities
+            && !self
+                .entity_types
+                .iter()
+                .any(|(entity_type, negated)| !negated && entity_type == "minecraft:player")
+    }
+
+    // The entity ids that match, in the order the selector sorts them
+    pub fn resolve(&self, context: &CommandContext) -> Result<Vec<i32>, CommandError> {
+        let world = context.world()?;
+        let source_eid = context.source_eid();
+        let (source_position, _) = context.source_position()?;
+        let origin = (
+            self.x.unwrap_or(source_position.x),
+            self.y.unwrap_or(source_position.y),
+            self.z.unwrap_or(source_position.z),
+        );
+
+        let entities = world
+            .entities
+            .read()
+            .map_err(|e| CommandError::new(format!("Could not lock entities for reading: {}", e)))?;
+        let mut found = vec![];
+        for (eid, entity_arc) in entities.iter() {
+            let entity = entity_arc
+                .read()
+                .map_err(|e| CommandError::new(format!("Could not lock entity for reading: {}", e)))?;
+            let position = entity.position();
+            let is_player = entity.as_player().is_ok();
+
+            let target_matches = match &self.target {
+                SelectorTarget::NearestPlayer | SelectorTarget::AllPlayers | SelectorTarget::RandomPlayer => is_player,
+                SelectorTarget::Source => Some(*eid) == source_eid,
+                SelectorTarget::AllEntities => true,
+                SelectorTarget::Name(name) => is_player && entity.name() == *name,
+                SelectorTarget::Uuid(uuid) => entity.uuid() == *uuid,
+            };
+            let names_match = self
+                .names
+                .iter()
+                .all(|(name, negated)| (entity.name() == *name) != *negated);
+            let gamemodes_match = self.gamemodes.iter().all(|(gamemode, negated)| {
+                let has_gamemode = entity.as_player().map(|p| p.gamemode == *gamemode).unwrap_or(false);
+                has_gamemode != *negated
+            });
+            let types_match = self
+                .entity_types
+                .iter()
+                .all(|(entity_type, negated)| (entity.type_name() == entity_type) != *negated);
+            let distance = distance(origin, position);
+            let distance_matches = match self.distance {
+                Some((min, max)) => {
+                    min.map(|min| distance >= min).unwrap_or(true) && max.map(|max| distance <= max).unwrap_or(true)
+                }
+                None => true,
+            };
+            let in_volume = self.in_volume(origin, position);
+
+            if target_matches && names_match && gamemodes_match && types_match && distance_matches && in_volume {
+                found.push((*eid, distance));
+            }
+        }
+
+        match self.sort() {
+            SelectorSort::Nearest => found.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal)),
+            SelectorSort::Furthest => found.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal)),
+            SelectorSort::Random => found.shuffle(&mut rand::thread_rng()),
+            SelectorSort::Arbitrary => {}
+        }
+
+        if let Some(limit) = self.limit() {
+            found.truncate(limit);
+        }
+        Ok(found.into_iter().map(|(eid, _)| eid).collect())
+    }
+
+    // Without dx, dy or dz there is no box
+    fn in_volume(&self, origin: (f64, f64, f64), position: (f64, f64, f64)) -> bool {
+        if self.dx.is_none() && self.dy.is_none() && self.dz.is_none() {
+            return true;
+        }
+        let axis = |origin: f64, size: Option<f64>, value: f64| {
+            let size = size.unwrap_or(0.0);
+            let (min, max) = if size < 0.0 { (origin + size, origin + 1.0) } else { (origin, origin + size + 1.0) };
+            value >= min && value < max
+        };
+        axis(origin.0, self.dx, position.0) && axis(origin.1, self.dy, position.1) && axis(origin.2, self.dz, position.2)
+    }
+}
+
+fn read_negation(reader: &mut StringReader) -> bool {
+    reader.skip_whitespace();
+    if reader.can_read() && reader.peek() == '!' {
+        reader.skip();
+        reader.skip_whitespace();
+        return true;
+    }
+    false
+}
+
+fn distance(a: (f64, f64, f64), b: (f64, f64, f64)) -> f64 {
+    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2) + (a.2 - b.2).powi(2)).sqrt()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{EntitySelector, SelectorSort, SelectorTarget};
+    use crate::player::Gamemode;
+    use crate::server::StringReader;
+
+    fn parse(input: &str) -> Result<EntitySelector, String> {
+        EntitySelector::parse(&mut StringReader::new(input)).map_err(|e| e.message)
+    }
+
+    #[test]
+    fn test_parse() {
+        let selector = parse("@a[name=!Steve,distance=..5,limit=2,sort=furthest,gamemode=creative]").unwrap();
+        assert_eq!(selector.target, SelectorTarget::AllPlayers);
+        assert_eq!(selector.names, vec![("Steve".to_string(), true)]);
+        assert_eq!(selector.distance, Some((None, Some(5.0))));
+        assert_eq!(selector.limit, Some(2));
+        assert_eq!(selector.sort, Some(SelectorSort::Furthest));
+        assert_eq!(selector.gamemodes, vec![(Gamemode::Creative, false)]);
+
+        let selector = parse("@e[type=player,x=1,dx=2]").unwrap();
+        assert_eq!(selector.entity_types, vec![("minecraft:player".to_string(), false)]);
+        assert!(!selector.includes_entities());
+        assert_eq!((selector.x, selector.dx), (Some(1.0), Some(2.0)));
+
+        assert_eq!(parse("Alice").unwrap().target, SelectorTarget::Name("Alice".to_string()));
+        assert!(parse("@p").unwrap().is_single());
+
+        let selector = parse("@p[limit=3]").unwrap();
+        assert_eq!(selector.limit, Some(3));
+        assert!(!selector.is_single());
+        assert!(!parse("@r[limit=2]").unwrap().is_single());
+        assert_eq!(parse("@p[sort=furthest]").unwrap().sort(), SelectorSort::Furthest);
+        assert_eq!(parse("@r").unwrap().sort(), SelectorSort::Random);
+        assert!(parse("@a[limit=1]").unwrap().is_single());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(parse("@x"), Err("Unknown selector type '@x'".to_string()));
+        assert_eq!(parse("@a[foo=1]"), Err("Unknown option 'foo'".to_string()));
+        assert_eq!(parse("@a[limit=0]"), Err("Limit must be at least 1".to_string()));
+        assert_eq!(parse("@s[limit=2]"), Err("Option 'limit' isn't applicable here".to_string()));
+        assert_eq!(parse("@s[sort=nearest]"), Err("Option 'sort' isn't applicable here".to_string()));
+        assert_eq!(parse("@a[limit=1,limit=2]"), Err("Option 'limit' isn't applicable here".to_string()));
+        assert_eq!(parse("@a[distance=-1]"), Err("Distance cannot be negative".to_string()));
+        assert_eq!(parse("@a[name=x"), Err("Expected end of options".to_string()));
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use super::Slot;
+
+// The start of the item registry of 1.16, in id order. Items past these can not be named until the
+// registry is read from the reports of the vanilla server
+const ITEMS: [&str; 45] = [
+    "air", "stone", "granite", "polished_granite", "diorite", "polished_diorite", "andesite",
+    "polished_andesite", "grass_block", "dirt", "coarse_dirt", "podzol", "crimson_nylium", "warped_nylium",
+    "cobblestone", "oak_planks", "spruce_planks", "birch_planks", "jungle_planks", "acacia_planks",
+    "dark_oak_planks", "crimson_planks", "warped_planks", "oak_sapling", "spruce_sapling", "birch_sapling",
+    "jungle_sapling", "acacia_sapling", "dark_oak_sapling", "bedrock", "sand", "red_sand", "gravel",
+    "gold_ore", "iron_ore", "coal_ore", "nether_gold_ore", "oak_log", "spruce_log", "birch_log",
+    "jungle_log", "acacia_log", "dark_oak_log", "crimson_stem", "warped_stem",
+];
+
+pub const MAX_STACK_SIZE: u8 = 64;
+
+// Air is not an item that can be held
+pub fn item_id(name: &str) -> Option<i32> {
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    ITEMS.iter().position(|item| *item == name).filter(|id| *id != 0).map(|id| id as i32)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ItemStack {
+    pub item_id: i32,
+    pub count: u8,
+}
+
+impl ItemStack {
+    pub fn to_slot(stack: Option<ItemStack>) -> Slot {
+        match stack {
+            Some(stack) => Slot {
+                present: true,
+                item_id: Some(stack.item_id),
+                count: Some(stack.count),
+                nbt: None,
+            },
+            None => Slot { present: false, item_id: None, count: None, nbt: None },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_item_id() {
+        assert_eq!(item_id("minecraft:dirt"), Some(9));
+        assert_eq!(item_id("cobblestone"), Some(14));
+        assert_eq!(item_id("minecraft:warped_stem"), Some(44));
+        assert_eq!(item_id("minecraft:air"), None);
+        assert_eq!(item_id("minecraft:diamond"), None);
+    }
+}
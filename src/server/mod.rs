@@ -2,33 +2,71 @@ mod biome;
 mod dimension;
 mod dimension_codec;
 mod entity;
+mod entity_selector;
+mod item;
 mod server_settings;
 mod recipe;
 mod command;
+mod command_argument;
+mod command_builder;
+mod command_dispatcher;
+mod command_error;
 mod command_parser;
+mod command_suggestions;
+mod protocol_check;
+mod scheduler;
+mod snbt_reader;
+mod string_reader;
+mod timestamp;
+mod user_list;
+mod vanilla_commands;
 
 pub use biome::*;
 pub use dimension::*;
 pub use dimension_codec::*;
 pub use entity::*;
+pub use entity_selector::*;
+pub use item::*;
 pub use server_settings::*;
 pub use recipe::*;
 pub use command::*;
+pub use command_builder::*;
+pub use command_dispatcher::*;
+pub use command_error::*;
+pub use command_suggestions::*;
+pub use command_argument::*;
+pub use protocol_check::*;
+pub use scheduler::*;
+pub use string_reader::*;
+pub use timestamp::*;
+pub use user_list::*;
 
+use vanilla_commands::register_vanilla_commands;
+
+use crate::chat::Chat;
+use crate::console;
 use crate::error_type::ErrorType;
 use crate::nbt::NBTTag;
 use crate::packets::packet_writer::PacketWriter;
 use crate::player::OPLevel;
 use crate::player::Player;
+use crate::player::PlayerData;
 use crate::world::World;
 
 use std::collections::HashMap;
 use std::convert::TryInto;
+use std::fs;
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::RwLock;
 
+use serde::de::DeserializeOwned;
+use serde::Serialize;
 use uuid::Uuid;
 
+pub const NOT_WHITELISTED: &str = "You are not white-listed on this server!";
+
 #[derive(Debug, Clone)]
 pub struct Tag {
     pub name: String,
@@ -58,8 +96,12 @@ pub struct ServerData {
     pub dimension_codec: DimensionCodec,
     pub recipes: Vec<Recipe>,
     pub tags: Tags,
-    pub command_nodes: Vec<CommandNode>,
-    pub command_root_node: i32,
+    pub commands: Arc<CommandDispatcher>,
+    pub ops: OpsList,
+    pub permission_nodes: PermissionNodes,
+    pub banned_players: BannedPlayers,
+    pub banned_ips: BannedIps,
+    pub whitelist: Whitelist,
 }
 
 impl ServerData {
@@ -72,28 +114,74 @@ impl ServerData {
         let only_biome = Biome::dummy();
         dimension_codec.add_biome(only_biome);
 
-        let (command_nodes, command_root_node) = Self::load_commands();
+        let mut settings = ServerSettings::dummy();
+        for world in settings.worlds.values_mut() {
+            if let Err(e) = world.load_level_data() {
+                console::log(&format!("Could not load level data of {}: {:?}", world.name, e));
+            }
+            if let Err(e) = world.load_spawn_chunks() {
+                console::log(&format!("Could not load spawn chunks of {}: {:?}", world.name, e));
+            }
+        }
+
+        let permission_nodes = PermissionNodes::load(PathBuf::from("permissions.json")).unwrap_or_else(|e| {
+            console::log(&format!("Could not load permission nodes: {:?}", e));
+            PermissionNodes::empty()
+        });
 
         Self {
-            settings: ServerSettings::dummy(),
+            settings,
             player_eids: Arc::new(RwLock::new(HashMap::new())),
             dimension_codec,
             recipes: Self::load_recipes(),
             tags: Self::load_tags(),
-            command_nodes,
-            command_root_node,
+            commands: Arc::new(Self::load_commands()),
+            ops: Self::load_user_list("ops.json"),
+            permission_nodes,
+            banned_players: Self::load_user_list("banned-players.json"),
+            banned_ips: Self::load_user_list("banned-ips.json"),
+            whitelist: Self::load_user_list("whitelist.json"),
+        }
+    }
+
+    // A list that cannot be read starts out empty, saving it will overwrite the file
+    fn load_user_list<T: Serialize + DeserializeOwned>(file: &str) -> UserList<T> {
+        UserList::load(PathBuf::from(file)).unwrap_or_else(|e| {
+            console::log(&format!("Could not load {}: {:?}", file, e));
+            UserList::empty(PathBuf::from(file))
+        })
+    }
+
+    // Why someone may not join, checked before their player is created
+    pub fn login_refusal(&self, uuid: Uuid, address: Option<IpAddr>) -> Option<String> {
+        if let Some(ban) = self.banned_players.get(uuid) {
+            return Some(ban.disconnect_message());
+        }
+        if let Some(ban) = address.and_then(|address| self.banned_ips.get(&address.to_string())) {
+            return Some(ban.disconnect_message());
+        }
+        if !self.may_join_whitelist(uuid) {
+            return Some(NOT_WHITELISTED.to_string());
         }
+        None
+    }
+
+    // Operators can always join
+    pub fn may_join_whitelist(&self, uuid: Uuid) -> bool {
+        !self.settings.whitelist || self.whitelist.contains(uuid) || self.ops.level(uuid) != OPLevel::Player
     }
 
     pub fn load_or_create_player(&self, username: &String, uuid: Uuid) -> Result<i32, ErrorType> {
-        // TODO: persistent player storage
-        let player = Player::new(
+        let mut player = Player::new(
             uuid,
             username.to_string(),
             self.settings.default_gamemode.clone(),
             self.dimension_codec.dimensions["mcrust:the_only_dimension"].clone(),
-            OPLevel::Owner,
+            self.permission_nodes.permissions_for(uuid, self.ops.level(uuid)),
         );
+        if let Some(player_data) = self.load_player_data(uuid)? {
+            player_data.apply_to(&mut player);
+        }
         let world: &World = self
             .settings
             .worlds
@@ -112,6 +200,75 @@ impl ServerData {
         Ok(eid)
     }
 
+    // Removes a player from the world, returns the removed player if it existed
+    pub fn remove_player(&self, eid: i32) -> Result<Option<Player>, ErrorType> {
+        let world: &World = self
+            .settings
+            .worlds
+            .get(&self.settings.selected_world)
+            .ok_or(ErrorType::Fatal("Invalid selected".to_string()))?;
+        let entity_arc = match world.remove_entity(eid)? {
+            Some(entity_arc) => entity_arc,
+            None => return Ok(None),
+        };
+        world.untrack_entity(eid)?;
+        world.chunk_provider.release_all(eid)?;
+        let entity = entity_arc.read().map_err(|e| {
+            ErrorType::Fatal(format!(
+                "Could not lock removed player for reading: {}",
+                e
+            ))
+        })?;
+        let player = entity.as_player()?.clone();
+
+        self.player_eids
+            .write()
+            .map_err(|e| {
+                ErrorType::Fatal(format!(
+                    "Could not lock player eid mapping: {}",
+                    e
+                ))
+            })?
+            .remove(&player.uuid);
+
+        Ok(Some(player))
+    }
+
+    fn player_data_path(&self, uuid: Uuid) -> PathBuf {
+        self.settings
+            .data_directory
+            .join("playerdata")
+            .join(format!("{}.json", uuid.to_hyphenated()))
+    }
+
+    fn load_player_data(&self, uuid: Uuid) -> Result<Option<PlayerData>, ErrorType> {
+        let path = self.player_data_path(uuid);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            ErrorType::Recoverable(format!("Could not read {}: {}", path.display(), e))
+        })?;
+        let player_data = serde_json::from_str(&contents).map_err(|e| {
+            ErrorType::Recoverable(format!("Invalid player data in {}: {}", path.display(), e))
+        })?;
+        Ok(Some(player_data))
+    }
+
+    pub fn save_player(&self, player: &Player) -> Result<(), ErrorType> {
+        let path = self.player_data_path(player.uuid);
+        if let Some(directory) = path.parent() {
+            fs::create_dir_all(directory).map_err(|e| {
+                ErrorType::Recoverable(format!("Could not create {}: {}", directory.display(), e))
+            })?;
+        }
+        let contents = serde_json::to_string(&PlayerData::from(player))
+            .map_err(|e| ErrorType::Recoverable(format!("Could not serialize player: {}", e)))?;
+        fs::write(&path, contents).map_err(|e| {
+            ErrorType::Recoverable(format!("Could not write {}: {}", path.display(), e))
+        })
+    }
+
     pub fn load_recipes() -> Vec<Recipe> {
         // TODO implement this
         return vec![
@@ -194,16 +351,33 @@ impl ServerData {
         }
     }
 
-    fn load_commands() -> (Vec<CommandNode>, i32) {
-        return (
-            vec![
-                CommandNode::Root(false, vec![1, 2], None),
-                CommandNode::Literal(true, vec![], None, "test".to_string()),
-                CommandNode::Literal(false, vec![3], None, "command".to_string()),
-                CommandNode::Argument(true, vec![4], None, "argument".to_string(), CommandParserType::MinecraftColor(), None),
-                CommandNode::Argument(true, vec![], None, "argument2".to_string(), CommandParserType::BrigadierInteger(Some(0), Some(10)), None)
-            ],
-            0
+    fn load_commands() -> CommandDispatcher {
+        let mut dispatcher = CommandDispatcher::new();
+        register_vanilla_commands(&mut dispatcher);
+        dispatcher.register(literal("test").executes(|context| {
+            let name = context.source_name()?;
+            context.send_feedback(Chat::new(format!("Test command executed by {}", name)));
+            Ok(1)
+        }));
+        dispatcher.register(
+            literal("command").then(
+                argument("argument", color())
+                    .executes(|context| {
+                        let color = context.get_string("argument")?;
+                        context.send_feedback(Chat::new(format!("Color is {}", color)));
+                        Ok(1)
+                    })
+                    .then(argument("argument2", integer_between(0, 10)).executes(|context| {
+                        let color = context.get_string("argument")?;
+                        let number = context.get_integer("argument2")?;
+                        context.send_feedback(Chat::new(format!(
+                            "Color is {}, number is {}",
+                            color, number
+                        )));
+                        Ok(number)
+                    })),
+            ),
         );
+        dispatcher
     }
 }
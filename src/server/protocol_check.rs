@@ -0,0 +1,25 @@
+use super::ServerSettings;
+
+use crate::packets::protocol::ProtocolVersion;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProtocolCheck {
+    Accepted,
+    OutdatedClient,
+    OutdatedServer,
+}
+
+// Decides if a client with the given protocol version may log in. This is a plain function so it
+// can be swapped out in the settings.
+pub type ProtocolCheckFn = fn(&ServerSettings, isize) -> ProtocolCheck;
+
+// Accept every protocol version we have a packet mapping for
+pub fn supported_protocol_check(_settings: &ServerSettings, protocol_version: isize) -> ProtocolCheck {
+    if ProtocolVersion::from_number(protocol_version).is_some() {
+        ProtocolCheck::Accepted
+    } else if protocol_version < ProtocolVersion::oldest().number {
+        ProtocolCheck::OutdatedClient
+    } else {
+        ProtocolCheck::OutdatedServer
+    }
+}
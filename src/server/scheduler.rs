@@ -0,0 +1,73 @@
+use crate::error_type::ErrorType;
+use crate::Server;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+pub type Task = Box<dyn FnOnce(&Server) + Send>;
+
+// Work that has to happen on the game tick instead of on a connection thread
+pub struct Scheduler {
+    current_tick: AtomicU64,
+    tasks: Mutex<Vec<(u64, Task)>>,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            current_tick: AtomicU64::new(0),
+            tasks: Mutex::new(vec![]),
+        }
+    }
+
+    pub fn current_tick(&self) -> u64 {
+        self.current_tick.load(Ordering::SeqCst)
+    }
+
+    // Runs the task at the start of the next tick
+    pub fn schedule(&self, task: Task) -> Result<(), ErrorType> {
+        self.schedule_in(1, task)
+    }
+
+    pub fn schedule_in(&self, ticks: u64, task: Task) -> Result<(), ErrorType> {
+        let due_tick = self.current_tick() + ticks.max(1);
+        self.tasks
+            .lock()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock scheduled tasks: {}", e)))?
+            .push((due_tick, task));
+        Ok(())
+    }
+
+    // Moves on to the next tick and gives back the tasks that are due
+    pub fn next_tick(&self) -> Result<(u64, Vec<Task>), ErrorType> {
+        let tick = self.current_tick.fetch_add(1, Ordering::SeqCst) + 1;
+        let mut tasks = self
+            .tasks
+            .lock()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock scheduled tasks: {}", e)))?;
+        let (due, later) = tasks.drain(..).partition(|(due_tick, _)| *due_tick <= tick);
+        *tasks = later;
+        Ok((tick, due.into_iter().map(|(_, task)| task).collect()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Scheduler;
+
+    #[test]
+    fn test_scheduler() {
+        let scheduler = Scheduler::new();
+        scheduler.schedule(Box::new(|_| {})).unwrap();
+        scheduler.schedule_in(3, Box::new(|_| {})).unwrap();
+        // Tasks are never due on the tick they are scheduled in
+        scheduler.schedule_in(0, Box::new(|_| {})).unwrap();
+
+        let (tick, due) = scheduler.next_tick().unwrap();
+        assert_eq!((tick, due.len()), (1, 2));
+        assert!(scheduler.next_tick().unwrap().1.is_empty());
+        let (tick, due) = scheduler.next_tick().unwrap();
+        assert_eq!((tick, due.len()), (3, 1));
+        assert_eq!(scheduler.current_tick(), 3);
+    }
+}
@@ -1,40 +1,65 @@
+use super::supported_protocol_check;
+use super::ProtocolCheck;
+use super::ProtocolCheckFn;
+
 use crate::player::Gamemode;
+use crate::player::OPLevel;
 use crate::world::World;
 
 use std::collections::HashMap;
+use std::path::PathBuf;
 
 #[derive(Clone)]
 pub struct ServerSettings {
     pub version: String,
+    pub game_version: String,
     pub protocol_version: usize,
+    pub protocol_check: ProtocolCheckFn,
     pub max_players: i32,
     pub motd: String,
     pub online: bool,
     pub is_hardcore: bool,
     pub default_gamemode: Gamemode,
+    // The level /op gives when none is specified
+    pub op_permission_level: OPLevel,
+    // Only players on the whitelist may join, enforcing it also kicks those who are not
+    pub whitelist: bool,
+    pub enforce_whitelist: bool,
     pub worlds: HashMap<String, World>,
     pub selected_world: String,
     pub view_distance: i32,
+    pub data_directory: PathBuf,
 }
 
 impl ServerSettings {
     pub fn dummy() -> Self {
+        let data_directory = PathBuf::from("world");
         let mut worlds = HashMap::new();
-        let only_world = World::dummy();
+        let only_world = World::dummy(&data_directory);
         let selected_world = only_world.name.clone();
         worlds.insert(only_world.name.clone(), only_world);
 
         Self {
             version: format!("MCRust 0.1.0"),
+            game_version: "1.16.5".to_string(),
             protocol_version: 754,
+            protocol_check: supported_protocol_check,
             max_players: 20,
             motd: format!("Hello from Rust"),
             online: false,
             is_hardcore: false,
             default_gamemode: Gamemode::Survival,
+            op_permission_level: OPLevel::Owner,
+            whitelist: false,
+            enforce_whitelist: false,
             worlds,
             selected_world,
             view_distance: 16,
+            data_directory,
         }
     }
+
+    pub fn check_protocol(&self, protocol_version: isize) -> ProtocolCheck {
+        (self.protocol_check)(self, protocol_version)
+    }
 }
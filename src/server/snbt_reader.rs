@@ -0,0 +1,265 @@
+use crate::nbt::{NBTTag, NamedNBTTag};
+
+use super::CommandError;
+use super::StringReader;
+
+// Reads stringified NBT like {display:{Name:'"x"'},Count:3b} the way vanilla's TagParser does
+
+pub fn read_compound(reader: &mut StringReader) -> Result<NBTTag, CommandError> {
+    reader.skip_whitespace();
+    reader.expect('{')?;
+    reader.skip_whitespace();
+    let mut entries = vec![];
+    while reader.can_read() && reader.peek() != '}' {
+        let start = reader.cursor();
+        let key = read_key(reader)?;
+        if key.is_empty() {
+            reader.set_cursor(start);
+            return Err(CommandError::at("Expected key".to_string(), reader));
+        }
+        reader.skip_whitespace();
+        reader.expect(':')?;
+        entries.push(NamedNBTTag::new(&key, read_value(reader)?));
+        if !has_element_separator(reader) {
+            break;
+        }
+        if !reader.can_read() {
+            return Err(CommandError::at("Expected key".to_string(), reader));
+        }
+    }
+    reader.skip_whitespace();
+    reader.expect('}')?;
+    Ok(NBTTag::Compound(entries))
+}
+
+pub fn read_value(reader: &mut StringReader) -> Result<NBTTag, CommandError> {
+    reader.skip_whitespace();
+    if !reader.can_read() {
+        return Err(CommandError::at("Expected value".to_string(), reader));
+    }
+    match reader.peek() {
+        '{' => read_compound(reader),
+        '[' => read_list(reader),
+        _ => read_typed_value(reader),
+    }
+}
+
+fn read_key(reader: &mut StringReader) -> Result<String, CommandError> {
+    reader.skip_whitespace();
+    if !reader.can_read() {
+        return Err(CommandError::at("Expected key".to_string(), reader));
+    }
+    reader.read_string()
+}
+
+fn has_element_separator(reader: &mut StringReader) -> bool {
+    reader.skip_whitespace();
+    if reader.can_read() && reader.peek() == ',' {
+        reader.skip();
+        reader.skip_whitespace();
+        return true;
+    }
+    false
+}
+
+fn read_typed_value(reader: &mut StringReader) -> Result<NBTTag, CommandError> {
+    reader.skip_whitespace();
+    let start = reader.cursor();
+    if StringReader::is_quoted_string_start(reader.peek()) {
+        return Ok(NBTTag::String(reader.read_quoted_string()?));
+    }
+    let value = reader.read_unquoted_string();
+    if value.is_empty() {
+        reader.set_cursor(start);
+        return Err(CommandError::at("Expected value".to_string(), reader));
+    }
+    Ok(typed_value(&value))
+}
+
+fn is_integer(s: &str) -> bool {
+    let digits = s.strip_prefix(|c| c == '-' || c == '+').unwrap_or(s);
+    digits == "0" || (!digits.is_empty() && !digits.starts_with('0') && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+fn is_decimal(s: &str) -> bool {
+    let mantissa = s.split(['e', 'E']).next().unwrap_or("");
+    s.chars().any(|c| c.is_ascii_digit())
+        && s.chars().all(|c| c.is_ascii_digit() || "+-.eE".contains(c))
+        && (mantissa.contains('.') || mantissa.len() < s.len())
+}
+
+// Unquoted values are numbers when they look like one, strings otherwise
+fn typed_value(s: &str) -> NBTTag {
+    let (body, suffix) = s.split_at(s.len() - 1);
+    let parsed = match suffix {
+        "b" | "B" if is_integer(body) => body.parse::<i8>().ok().map(|x| NBTTag::Byte(x as u8)),
+        "s" | "S" if is_integer(body) => body.parse().ok().map(NBTTag::Short),
+        "l" | "L" if is_integer(body) => body.parse().ok().map(NBTTag::Long),
+        "f" | "F" if is_integer(body) || is_decimal(body) => body.parse().ok().map(NBTTag::Float),
+        "d" | "D" if is_integer(body) || is_decimal(body) => body.parse().ok().map(NBTTag::Double),
+        _ if is_integer(s) => s.parse().ok().map(NBTTag::Int),
+        _ if is_decimal(s) => s.parse().ok().map(NBTTag::Double),
+        _ => None,
+    };
+    match (parsed, s) {
+        (Some(tag), _) => tag,
+        (None, "true") => NBTTag::Byte(1),
+        (None, "false") => NBTTag::Byte(0),
+        (None, _) => NBTTag::String(s.to_string()),
+    }
+}
+
+fn read_list(reader: &mut StringReader) -> Result<NBTTag, CommandError> {
+    let is_array = reader.can_read_length(3)
+        && !reader.peek_at(1).map(StringReader::is_quoted_string_start).unwrap_or(false)
+        && reader.peek_at(2) == Some(';');
+    if is_array {
+        return read_array(reader);
+    }
+
+    reader.expect('[')?;
+    reader.skip_whitespace();
+    if !reader.can_read() {
+        return Err(CommandError::at("Expected value".to_string(), reader));
+    }
+    let mut elements: Vec<NBTTag> = vec![];
+    while reader.peek() != ']' {
+        let start = reader.cursor();
+        let element = read_value(reader)?;
+        if let Some(first) = elements.first() {
+            if first.type_id() != element.type_id() {
+                reader.set_cursor(start);
+                return Err(CommandError::at(
+                    format!(
+                        "Can't insert {} into list of {}",
+                        type_name(&element),
+                        type_name(first)
+                    ),
+                    reader,
+                ));
+            }
+        }
+        elements.push(element);
+        if !has_element_separator(reader) {
+            break;
+        }
+        if !reader.can_read() {
+            return Err(CommandError::at("Expected value".to_string(), reader));
+        }
+    }
+    reader.skip_whitespace();
+    reader.expect(']')?;
+    Ok(NBTTag::List(elements))
+}
+
+fn read_array(reader: &mut StringReader) -> Result<NBTTag, CommandError> {
+    reader.expect('[')?;
+    let start = reader.cursor();
+    let array_type = reader.next();
+    if !"BIL".contains(array_type) {
+        reader.set_cursor(start);
+        return Err(CommandError::at(format!("Invalid array type '{}'", array_type), reader));
+    }
+    reader.skip();
+    reader.skip_whitespace();
+    if !reader.can_read() {
+        return Err(CommandError::at("Expected value".to_string(), reader));
+    }
+    let mut elements = vec![];
+    while reader.peek() != ']' {
+        let element_start = reader.cursor();
+        let element = read_value(reader)?;
+        let fits = matches!(
+            (array_type, &element),
+            ('B', NBTTag::Byte(_)) | ('I', NBTTag::Byte(_)) | ('I', NBTTag::Short(_)) | ('I', NBTTag::Int(_))
+                | ('L', NBTTag::Byte(_)) | ('L', NBTTag::Short(_)) | ('L', NBTTag::Int(_)) | ('L', NBTTag::Long(_))
+        );
+        if !fits {
+            reader.set_cursor(element_start);
+            return Err(CommandError::at(
+                format!(
+                    "Can't insert {} into {}",
+                    type_name(&element),
+                    type_name(&array_of(array_type, vec![]))
+                ),
+                reader,
+            ));
+        }
+        elements.push(element);
+        if !has_element_separator(reader) {
+            break;
+        }
+        if !reader.can_read() {
+            return Err(CommandError::at("Expected value".to_string(), reader));
+        }
+    }
+    reader.skip_whitespace();
+    reader.expect(']')?;
+    Ok(array_of(array_type, elements))
+}
+
+fn array_of(array_type: char, elements: Vec<NBTTag>) -> NBTTag {
+    let as_long = |tag: &NBTTag| match tag {
+        NBTTag::Byte(x) => *x as i8 as i64,
+        NBTTag::Short(x) => *x as i64,
+        NBTTag::Int(x) => *x as i64,
+        NBTTag::Long(x) => *x,
+        _ => 0,
+    };
+    match array_type {
+        'B' => NBTTag::ByteArray(elements.iter().map(|x| as_long(x) as u8).collect()),
+        'I' => NBTTag::IntArray(elements.iter().map(|x| as_long(x) as i32).collect()),
+        _ => NBTTag::LongArray(elements.iter().map(as_long).collect()),
+    }
+}
+
+fn type_name(tag: &NBTTag) -> &'static str {
+    match tag {
+        NBTTag::End => "TAG_End",
+        NBTTag::Byte(_) => "TAG_Byte",
+        NBTTag::Short(_) => "TAG_Short",
+        NBTTag::Int(_) => "TAG_Int",
+        NBTTag::Long(_) => "TAG_Long",
+        NBTTag::Float(_) => "TAG_Float",
+        NBTTag::Double(_) => "TAG_Double",
+        NBTTag::ByteArray(_) => "TAG_Byte_Array",
+        NBTTag::String(_) => "TAG_String",
+        NBTTag::List(_) => "TAG_List",
+        NBTTag::Compound(_) => "TAG_Compound",
+        NBTTag::IntArray(_) => "TAG_Int_Array",
+        NBTTag::LongArray(_) => "TAG_Long_Array",
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read_compound, read_value};
+    use crate::nbt::{NBTTag, NamedNBTTag};
+    use crate::server::StringReader;
+
+    #[test]
+    fn test_read() {
+        let mut reader = StringReader::new("{Count:3b, Name:\"a b\", Pos:[1.5d,2d], Ids:[I;1,2], On:true}");
+        assert_eq!(
+            read_compound(&mut reader).unwrap(),
+            NBTTag::Compound(vec![
+                NamedNBTTag::new("Count", NBTTag::Byte(3)),
+                NamedNBTTag::new("Name", NBTTag::String("a b".to_string())),
+                NamedNBTTag::new("Pos", NBTTag::List(vec![NBTTag::Double(1.5), NBTTag::Double(2.0)])),
+                NamedNBTTag::new("Ids", NBTTag::IntArray(vec![1, 2])),
+                NamedNBTTag::new("On", NBTTag::Byte(1)),
+            ])
+        );
+        assert_eq!(read_value(&mut StringReader::new("12")).unwrap(), NBTTag::Int(12));
+        assert_eq!(read_value(&mut StringReader::new("1e3f")).unwrap(), NBTTag::Float(1000.0));
+        assert_eq!(read_value(&mut StringReader::new("012")).unwrap(), NBTTag::String("012".to_string()));
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = read_compound(&mut StringReader::new("{a:1")).unwrap_err();
+        assert_eq!(error.message, "Expected '}'");
+        let error = read_value(&mut StringReader::new("[1,\"a\"]")).unwrap_err();
+        assert_eq!(error.message, "Can't insert TAG_String into list of TAG_Int");
+    }
+}
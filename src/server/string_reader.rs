@@ -0,0 +1,232 @@
+use super::CommandError;
+
+// Walks through command input the way Brigadier's StringReader does
+#[derive(Debug, Clone)]
+pub struct StringReader {
+    input: Vec<char>,
+    cursor: usize,
+}
+
+impl StringReader {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.chars().collect(),
+            cursor: 0,
+        }
+    }
+
+    pub fn string(&self) -> String {
+        self.input.iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.input.len());
+    }
+
+    pub fn read(&self) -> String {
+        self.input[..self.cursor].iter().collect()
+    }
+
+    pub fn remaining(&self) -> String {
+        self.input[self.cursor..].iter().collect()
+    }
+
+    pub fn can_read_length(&self, length: usize) -> bool {
+        self.cursor + length <= self.input.len()
+    }
+
+    pub fn can_read(&self) -> bool {
+        self.can_read_length(1)
+    }
+
+    pub fn peek(&self) -> char {
+        self.input[self.cursor]
+    }
+
+    pub fn peek_at(&self, offset: usize) -> Option<char> {
+        self.input.get(self.cursor + offset).copied()
+    }
+
+    pub fn next(&mut self) -> char {
+        let c = self.input[self.cursor];
+        self.cursor += 1;
+        c
+    }
+
+    pub fn skip(&mut self) {
+        self.cursor += 1;
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        while self.can_read() && self.peek().is_whitespace() {
+            self.skip();
+        }
+    }
+
+    pub fn is_allowed_number(c: char) -> bool {
+        c.is_ascii_digit() || c == '.' || c == '-'
+    }
+
+    pub fn is_quoted_string_start(c: char) -> bool {
+        c == '"' || c == '\''
+    }
+
+    pub fn is_allowed_in_unquoted_string(c: char) -> bool {
+        c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.' || c == '+'
+    }
+
+    fn read_number(&mut self) -> String {
+        let start = self.cursor;
+        while self.can_read() && Self::is_allowed_number(self.peek()) {
+            self.skip();
+        }
+        self.input[start..self.cursor].iter().collect()
+    }
+
+    // Reads a number, on failure the cursor goes back to where the number started
+    fn read_parsed<T: std::str::FromStr>(&mut self, kind: &str) -> Result<T, CommandError> {
+        let start = self.cursor;
+        let number = self.read_number();
+        if number.is_empty() {
+            return Err(CommandError::at(format!("Expected {}", kind), self));
+        }
+        number.parse().map_err(|_| {
+            self.cursor = start;
+            CommandError::at(format!("Invalid {} '{}'", kind, number), self)
+        })
+    }
+
+    pub fn read_int(&mut self) -> Result<i32, CommandError> {
+        self.read_parsed("integer")
+    }
+
+    pub fn read_long(&mut self) -> Result<i64, CommandError> {
+        self.read_parsed("long")
+    }
+
+    pub fn read_float(&mut self) -> Result<f32, CommandError> {
+        self.read_parsed("float")
+    }
+
+    pub fn read_double(&mut self) -> Result<f64, CommandError> {
+        self.read_parsed("double")
+    }
+
+    pub fn read_unquoted_string(&mut self) -> String {
+        let start = self.cursor;
+        while self.can_read() && Self::is_allowed_in_unquoted_string(self.peek()) {
+            self.skip();
+        }
+        self.input[start..self.cursor].iter().collect()
+    }
+
+    pub fn read_quoted_string(&mut self) -> Result<String, CommandError> {
+        if !self.can_read() {
+            return Ok(String::new());
+        }
+        let quote = self.peek();
+        if !Self::is_quoted_string_start(quote) {
+            return Err(CommandError::at("Expected quote to start a string".to_string(), self));
+        }
+        self.skip();
+        self.read_string_until(quote)
+    }
+
+    pub fn read_string_until(&mut self, terminator: char) -> Result<String, CommandError> {
+        let mut result = String::new();
+        let mut escaped = false;
+        while self.can_read() {
+            let c = self.next();
+            if escaped {
+                if c == terminator || c == '\\' {
+                    result.push(c);
+                    escaped = false;
+                } else {
+                    self.cursor -= 1;
+                    return Err(CommandError::at(
+                        format!("Invalid escape sequence '\\{}' in quoted string", c),
+                        self,
+                    ));
+                }
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == terminator {
+                return Ok(result);
+            } else {
+                result.push(c);
+            }
+        }
+        Err(CommandError::at("Unclosed quoted string".to_string(), self))
+    }
+
+    pub fn read_string(&mut self) -> Result<String, CommandError> {
+        if !self.can_read() {
+            return Ok(String::new());
+        }
+        let next = self.peek();
+        if Self::is_quoted_string_start(next) {
+            self.skip();
+            return self.read_string_until(next);
+        }
+        Ok(self.read_unquoted_string())
+    }
+
+    pub fn read_boolean(&mut self) -> Result<bool, CommandError> {
+        let start = self.cursor;
+        let value = self.read_string()?;
+        match value.as_str() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            "" => Err(CommandError::at("Expected bool".to_string(), self)),
+            _ => {
+                self.cursor = start;
+                Err(CommandError::at(
+                    format!("Invalid boolean, expected 'true' or 'false' but found '{}'", value),
+                    self,
+                ))
+            }
+        }
+    }
+
+    pub fn expect(&mut self, c: char) -> Result<(), CommandError> {
+        if !self.can_read() || self.peek() != c {
+            return Err(CommandError::at(format!("Expected '{}'", c), self));
+        }
+        self.skip();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StringReader;
+
+    #[test]
+    fn test_read_values() {
+        let mut reader = StringReader::new("12 -3.5 \"a \\\"b\\\"\" word true");
+        assert_eq!(reader.read_int().unwrap(), 12);
+        reader.skip();
+        assert_eq!(reader.read_double().unwrap(), -3.5);
+        reader.skip();
+        assert_eq!(reader.read_string().unwrap(), "a \"b\"");
+        reader.skip();
+        assert_eq!(reader.read_string().unwrap(), "word");
+        reader.skip();
+        assert!(reader.read_boolean().unwrap());
+        assert!(!reader.can_read());
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut reader = StringReader::new("1.2.3");
+        assert_eq!(reader.read_int().unwrap_err().message, "Invalid integer '1.2.3'");
+        assert_eq!(reader.cursor(), 0);
+
+        let mut reader = StringReader::new("\"open");
+        assert_eq!(reader.read_string().unwrap_err().message, "Unclosed quoted string");
+    }
+}
@@ -0,0 +1,147 @@
+// Dates like vanilla writes them in the ban lists, "2021-03-04 12:34:56 +0000". Times are kept
+// as seconds since the Unix epoch.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+// Always in UTC
+pub fn format_timestamp(timestamp: i64) -> String {
+    let days = timestamp.div_euclid(86400);
+    let seconds = timestamp.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} +0000",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+pub fn parse_timestamp(text: &str) -> Option<i64> {
+    let mut parts = text.split_whitespace();
+    let date: Vec<i64> = parts.next()?.split('-').map(|x| x.parse().ok()).collect::<Option<_>>()?;
+    let time: Vec<i64> = parts.next()?.split(':').map(|x| x.parse().ok()).collect::<Option<_>>()?;
+    if date.len() != 3 || time.len() != 3 || parts.clone().count() > 1 {
+        return None;
+    }
+    let offset = match parts.next() {
+        // Anything but ASCII could put a byte index inside a character
+        Some(offset) if offset.len() == 5 && offset.is_ascii() => {
+            let sign = match &offset[..1] {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            let hours: i64 = offset[1..3].parse().ok()?;
+            let minutes: i64 = offset[3..5].parse().ok()?;
+            if !(0..=23).contains(&hours) || !(0..=59).contains(&minutes) {
+                return None;
+            }
+            sign * (hours * 3600 + minutes * 60)
+        }
+        Some(_) => return None,
+        None => 0,
+    };
+    if !(1..=12).contains(&date[1]) || !(1..=31).contains(&date[2]) {
+        return None;
+    }
+    if !(0..=23).contains(&time[0]) || !(0..=59).contains(&time[1]) || !(0..=59).contains(&time[2]) {
+        return None;
+    }
+    // The year is not bounded, so dates too far away to be kept as seconds are rejected
+    let days = days_from_civil(date[0], date[1], date[2])?;
+    days.checked_mul(86400)?
+        .checked_add(time[0] * 3600 + time[1] * 60 + time[2])?
+        .checked_sub(offset)
+}
+
+// How long something like 30m, 12h, 7d or 1w2d is, in seconds
+pub fn parse_duration(text: &str) -> Option<i64> {
+    let mut total: i64 = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            'w' => 604800,
+            _ => return None,
+        };
+        let amount: i64 = number.parse().ok()?;
+        total = amount.checked_mul(unit)?.checked_add(total)?;
+        number.clear();
+    }
+    if !number.is_empty() || total == 0 {
+        return None;
+    }
+    Some(total)
+}
+
+// From https://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> Option<i64> {
+    let year = if month <= 2 { year.checked_sub(1)? } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month_index = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era.checked_mul(146097)?.checked_add(day_of_era - 719468)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_timestamp, parse_duration, parse_timestamp};
+
+    #[test]
+    fn test_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00 +0000");
+        assert_eq!(format_timestamp(1614861296), "2021-03-04 12:34:56 +0000");
+        assert_eq!(parse_timestamp("2021-03-04 12:34:56 +0000"), Some(1614861296));
+        assert_eq!(parse_timestamp("2021-03-04 13:34:56 +0100"), Some(1614861296));
+        assert_eq!(parse_timestamp("2000-02-29 00:00:00 +0000").map(format_timestamp),
+            Some("2000-02-29 00:00:00 +0000".to_string()));
+        assert_eq!(parse_timestamp("forever"), None);
+        assert_eq!(parse_timestamp("2021-03-04 12:34:56 +0é0"), None);
+        assert_eq!(parse_timestamp("2021-03-04 24:00:00 +0000"), None);
+        assert_eq!(parse_timestamp("2021-03-04 12:60:00 +0000"), None);
+        assert_eq!(parse_timestamp("2021-03-04 12:34:56 +-100"), None);
+        assert_eq!(parse_timestamp("9223372036854775807-03-04 12:34:56 +0000"), None);
+        assert_eq!(parse_timestamp("-9223372036854775808-01-01 00:00:00 +0000"), None);
+        assert_eq!(parse_timestamp("292277026596-12-04 15:30:07 +0000"), Some(i64::MAX));
+        assert_eq!(parse_timestamp("292277026596-12-04 15:30:08 +0000"), None);
+
+        assert_eq!(parse_duration("30m"), Some(1800));
+        assert_eq!(parse_duration("1w2d"), Some(777600));
+        assert_eq!(parse_duration("12"), None);
+        assert_eq!(parse_duration("0d"), None);
+        assert_eq!(parse_duration("9223372036854775807s1s"), None);
+        assert_eq!(parse_duration("99999999999999999w"), None);
+    }
+}
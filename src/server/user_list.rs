@@ -0,0 +1,366 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{format_timestamp, now, parse_timestamp};
+
+use crate::error_type::ErrorType;
+use crate::player::OPLevel;
+use crate::player::Permissions;
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+// A JSON file in the server directory that lists players, in the same format vanilla uses
+pub struct UserList<T> {
+    path: PathBuf,
+    pub entries: Vec<T>,
+}
+
+impl<T: Serialize + DeserializeOwned> UserList<T> {
+    // A file that does not exist yet is an empty list
+    pub fn load(path: PathBuf) -> Result<Self, ErrorType> {
+        let entries = if path.exists() {
+            let contents = fs::read_to_string(&path).map_err(|e| {
+                ErrorType::Recoverable(format!("Could not read {}: {}", path.display(), e))
+            })?;
+            serde_json::from_str(&contents).map_err(|e| {
+                ErrorType::Recoverable(format!("Invalid list in {}: {}", path.display(), e))
+            })?
+        } else {
+            vec![]
+        };
+        Ok(Self { path, entries })
+    }
+
+    pub fn empty(path: PathBuf) -> Self {
+        Self { path, entries: vec![] }
+    }
+
+    // Picks up changes made to the file by hand
+    pub fn reload(&mut self) -> Result<(), ErrorType> {
+        *self = Self::load(self.path.clone())?;
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<(), ErrorType> {
+        let contents = serde_json::to_string_pretty(&self.entries)
+            .map_err(|e| ErrorType::Recoverable(format!("Could not serialize {}: {}", self.path.display(), e)))?;
+        fs::write(&self.path, contents).map_err(|e| {
+            ErrorType::Recoverable(format!("Could not write {}: {}", self.path.display(), e))
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OpEntry {
+    pub uuid: String,
+    pub name: String,
+    pub level: i32,
+    pub bypasses_player_limit: bool,
+}
+
+pub type OpsList = UserList<OpEntry>;
+
+impl OpsList {
+    // Players that are not on the list are level 0
+    pub fn level(&self, uuid: Uuid) -> OPLevel {
+        let uuid = uuid.to_hyphenated().to_string();
+        self.entries
+            .iter()
+            .find(|entry| entry.uuid == uuid)
+            .and_then(|entry| OPLevel::from_number(entry.level))
+            .unwrap_or(OPLevel::Player)
+    }
+
+    // Returns false if the player already had this level
+    pub fn set(&mut self, uuid: Uuid, name: &str, level: OPLevel) -> bool {
+        let uuid = uuid.to_hyphenated().to_string();
+        if let Some(entry) = self.entries.iter_mut().find(|entry| entry.uuid == uuid) {
+            if entry.level == level as i32 {
+                return false;
+            }
+            entry.level = level as i32;
+            entry.name = name.to_string();
+            return true;
+        }
+        self.entries.push(OpEntry {
+            uuid,
+            name: name.to_string(),
+            level: level as i32,
+            bypasses_player_limit: false,
+        });
+        true
+    }
+
+    // Returns false if the player was not an operator
+    pub fn remove(&mut self, uuid: Uuid) -> bool {
+        let uuid = uuid.to_hyphenated().to_string();
+        let length = self.entries.len();
+        self.entries.retain(|entry| entry.uuid != uuid);
+        self.entries.len() != length
+    }
+}
+
+// What a ban expires as when it does not
+pub const FOREVER: &str = "forever";
+
+// Bans that have an expiry date in the past do not count anymore
+fn has_expired(expires: &str) -> bool {
+    match parse_timestamp(expires) {
+        Some(expires) => expires <= now(),
+        None => false,
+    }
+}
+
+fn expiry_date(expires: Option<i64>) -> String {
+    match expires {
+        Some(expires) => format_timestamp(expires),
+        None => FOREVER.to_string(),
+    }
+}
+
+fn ban_message(start: &str, reason: &str, expires: &str) -> String {
+    let mut message = format!("{}\nReason: {}", start, reason);
+    if expires != FOREVER {
+        message.push_str(&format!("\nYour ban will be removed on {}", expires));
+    }
+    message
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BanEntry {
+    pub uuid: String,
+    pub name: String,
+    pub created: String,
+    pub source: String,
+    pub expires: String,
+    pub reason: String,
+}
+
+impl BanEntry {
+    pub fn new(uuid: Uuid, name: &str, source: &str, expires: Option<i64>, reason: &str) -> Self {
+        Self {
+            uuid: uuid.to_hyphenated().to_string(),
+            name: name.to_string(),
+            created: format_timestamp(now()),
+            source: source.to_string(),
+            expires: expiry_date(expires),
+            reason: reason.to_string(),
+        }
+    }
+
+    // What the player sees when they try to join
+    pub fn disconnect_message(&self) -> String {
+        ban_message("You are banned from this server.", &self.reason, &self.expires)
+    }
+}
+
+pub type BannedPlayers = UserList<BanEntry>;
+
+impl BannedPlayers {
+    // Bans that have not expired yet
+    pub fn active(&self) -> Vec<&BanEntry> {
+        self.entries.iter().filter(|entry| !has_expired(&entry.expires)).collect()
+    }
+
+    pub fn get(&self, uuid: Uuid) -> Option<&BanEntry> {
+        let uuid = uuid.to_hyphenated().to_string();
+        self.entries
+            .iter()
+            .find(|entry| entry.uuid == uuid && !has_expired(&entry.expires))
+    }
+
+    // Returns false if the player already was banned, a ban that expired gets replaced
+    pub fn add(&mut self, entry: BanEntry) -> bool {
+        if self.entries.iter().any(|ban| ban.uuid == entry.uuid && !has_expired(&ban.expires)) {
+            return false;
+        }
+        self.entries.retain(|ban| ban.uuid != entry.uuid);
+        self.entries.push(entry);
+        true
+    }
+
+    // Returns false if the player was not banned
+    pub fn remove(&mut self, uuid: Uuid) -> bool {
+        let banned = self.get(uuid).is_some();
+        let uuid = uuid.to_hyphenated().to_string();
+        self.entries.retain(|entry| entry.uuid != uuid);
+        banned
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IpBanEntry {
+    pub ip: String,
+    pub created: String,
+    pub source: String,
+    pub expires: String,
+    pub reason: String,
+}
+
+impl IpBanEntry {
+    pub fn new(ip: &str, source: &str, expires: Option<i64>, reason: &str) -> Self {
+        Self {
+            ip: ip.to_string(),
+            created: format_timestamp(now()),
+            source: source.to_string(),
+            expires: expiry_date(expires),
+            reason: reason.to_string(),
+        }
+    }
+
+    pub fn disconnect_message(&self) -> String {
+        ban_message("Your IP address is banned from this server.", &self.reason, &self.expires)
+    }
+}
+
+pub type BannedIps = UserList<IpBanEntry>;
+
+impl BannedIps {
+    pub fn active(&self) -> Vec<&IpBanEntry> {
+        self.entries.iter().filter(|entry| !has_expired(&entry.expires)).collect()
+    }
+
+    pub fn get(&self, ip: &str) -> Option<&IpBanEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.ip == ip && !has_expired(&entry.expires))
+    }
+
+    // Returns false if the address already was banned
+    pub fn add(&mut self, entry: IpBanEntry) -> bool {
+        if self.get(&entry.ip).is_some() {
+            return false;
+        }
+        self.entries.retain(|ban| ban.ip != entry.ip);
+        self.entries.push(entry);
+        true
+    }
+
+    // Returns false if the address was not banned
+    pub fn remove(&mut self, ip: &str) -> bool {
+        let banned = self.get(ip).is_some();
+        self.entries.retain(|entry| entry.ip != ip);
+        banned
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WhitelistEntry {
+    pub uuid: String,
+    pub name: String,
+}
+
+pub type Whitelist = UserList<WhitelistEntry>;
+
+impl Whitelist {
+    pub fn contains(&self, uuid: Uuid) -> bool {
+        let uuid = uuid.to_hyphenated().to_string();
+        self.entries.iter().any(|entry| entry.uuid == uuid)
+    }
+
+    // Returns false if the player already was on the whitelist
+    pub fn add(&mut self, uuid: Uuid, name: &str) -> bool {
+        if self.contains(uuid) {
+            return false;
+        }
+        self.entries.push(WhitelistEntry {
+            uuid: uuid.to_hyphenated().to_string(),
+            name: name.to_string(),
+        });
+        true
+    }
+
+    // Returns false if the player was not on the whitelist
+    pub fn remove(&mut self, uuid: Uuid) -> bool {
+        let uuid = uuid.to_hyphenated().to_string();
+        let length = self.entries.len();
+        self.entries.retain(|entry| entry.uuid != uuid);
+        self.entries.len() != length
+    }
+}
+
+// permissions.json, which maps a UUID to the permission nodes it is granted or denied.
+// Nothing changes it while the server runs, so it is only read.
+pub struct PermissionNodes {
+    pub nodes: HashMap<String, HashMap<String, bool>>,
+}
+
+impl PermissionNodes {
+    pub fn load(path: PathBuf) -> Result<Self, ErrorType> {
+        let nodes = if path.exists() {
+            let contents = fs::read_to_string(&path).map_err(|e| {
+                ErrorType::Recoverable(format!("Could not read {}: {}", path.display(), e))
+            })?;
+            serde_json::from_str(&contents).map_err(|e| {
+                ErrorType::Recoverable(format!("Invalid permissions in {}: {}", path.display(), e))
+            })?
+        } else {
+            HashMap::new()
+        };
+        Ok(Self { nodes })
+    }
+
+    pub fn empty() -> Self {
+        Self { nodes: HashMap::new() }
+    }
+
+    pub fn permissions_for(&self, uuid: Uuid, op_level: OPLevel) -> Permissions {
+        let nodes = self
+            .nodes
+            .get(&uuid.to_hyphenated().to_string())
+            .cloned()
+            .unwrap_or_default();
+        Permissions::with_nodes(op_level, nodes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BanEntry, BannedPlayers, OpsList};
+    use crate::player::OPLevel;
+    use crate::server::now;
+
+    use std::path::PathBuf;
+
+    use uuid::Uuid;
+
+    #[test]
+    fn test_ops_list() {
+        let mut ops = OpsList::empty(PathBuf::from("ops.json"));
+        let uuid = Uuid::new_v4();
+        assert_eq!(ops.level(uuid), OPLevel::Player);
+        assert!(ops.set(uuid, "Alice", OPLevel::Owner));
+        assert!(!ops.set(uuid, "Alice", OPLevel::Owner));
+        assert_eq!(ops.level(uuid), OPLevel::Owner);
+
+        let json = serde_json::to_string(&ops.entries[0]).unwrap();
+        assert!(json.contains("\"bypassesPlayerLimit\":false"));
+
+        assert!(ops.remove(uuid));
+        assert!(!ops.remove(uuid));
+        assert_eq!(ops.level(uuid), OPLevel::Player);
+    }
+
+    #[test]
+    fn test_bans() {
+        let mut bans = BannedPlayers::empty(PathBuf::from("banned-players.json"));
+        let uuid = Uuid::new_v4();
+        assert!(bans.add(BanEntry::new(uuid, "Alice", "Server", Some(now() - 10), "Gone")));
+        assert!(bans.get(uuid).is_none());
+        assert!(!bans.remove(uuid));
+
+        assert!(bans.add(BanEntry::new(uuid, "Alice", "Server", None, "Griefing")));
+        assert!(!bans.add(BanEntry::new(uuid, "Alice", "Server", None, "Again")));
+        assert_eq!(bans.entries.len(), 1);
+        assert_eq!(
+            bans.get(uuid).unwrap().disconnect_message(),
+            "You are banned from this server.\nReason: Griefing"
+        );
+        assert!(bans.remove(uuid));
+        assert!(bans.entries.is_empty());
+    }
+}
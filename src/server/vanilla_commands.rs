@@ -0,0 +1,936 @@
+use std::net::IpAddr;
+
+use uuid::Uuid;
+
+use crate::chat::{Chat, ChatPosition};
+use crate::console;
+use crate::error_type::ErrorType;
+use crate::packets::clientbound::*;
+use crate::player::{Ability, Gamemode, OPLevel, Player};
+use crate::world::{Difficulty, WeatherType, GAME_RULES};
+use crate::Server;
+
+use super::{argument, boolean, entities, entity, game_profile, integer_between, integer_min, item_stack, literal};
+use super::{item_id, message, now, parse_duration, players, rotation, time, vec3, word};
+use super::{BanEntry, IpBanEntry, ItemStack, PrecipitationType, MAX_STACK_SIZE, NOT_WHITELISTED};
+use super::CommandBuilder;
+use super::CommandContext;
+use super::CommandDispatcher;
+use super::CommandError;
+
+// Weather durations are given in seconds
+const MAX_WEATHER_DURATION: i32 = 1_000_000;
+const DEFAULT_BAN_REASON: &str = "Banned by an operator.";
+
+pub fn register_vanilla_commands(dispatcher: &mut CommandDispatcher) {
+    dispatcher.register(gamemode_command());
+    dispatcher.register(teleport_command("tp"));
+    dispatcher.register(teleport_command("teleport"));
+    dispatcher.register(give_command());
+    dispatcher.register(kick_command());
+    dispatcher.register(say_command());
+    for name in ["msg", "tell", "w"] {
+        dispatcher.register(msg_command(name));
+    }
+    dispatcher.register(list_command());
+    dispatcher.register(time_command());
+    dispatcher.register(weather_command());
+    dispatcher.register(difficulty_command());
+    dispatcher.register(gamerule_command());
+    dispatcher.register(stop_command());
+    dispatcher.register(op_command());
+    dispatcher.register(deop_command());
+    dispatcher.register(ban_command());
+    dispatcher.register(tempban_command());
+    dispatcher.register(ban_ip_command());
+    dispatcher.register(tempban_ip_command());
+    dispatcher.register(pardon_command());
+    dispatcher.register(pardon_ip_command());
+    dispatcher.register(banlist_command());
+    dispatcher.register(whitelist_command());
+}
+
+fn send_message(server: &Server, player_eid: i32, message: Chat) {
+    server.send_to_player(
+        player_eid,
+        ClientboundPacket::ChatMessage(ChatMessagePacket {
+            message,
+            sender: Uuid::nil(),
+            position: ChatPosition::SystemMessage,
+        }),
+    );
+}
+
+// Like Java prints doubles, which is what the vanilla messages show
+fn format_double(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e7 {
+        format!("{:.1}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+// The 1.16 client does not know the gamemode argument type, so every gamemode is a literal
+fn gamemode_command() -> CommandBuilder {
+    let mut command = literal("gamemode")
+        .requires(OPLevel::Gamemaster)
+        .permission("mcrust.command.gamemode");
+    for gamemode in [Gamemode::Survival, Gamemode::Creative, Gamemode::Adventure, Gamemode::Spectator] {
+        command = command.then(
+            literal(gamemode.name())
+                .executes(move |context| {
+                    let player_eid = context.source_player()?;
+                    set_gamemode(context, vec![player_eid], gamemode)
+                })
+                .then(argument("target", players()).suggested_by_server().executes(move |context| {
+                    let targets = context.get_players("target")?;
+                    set_gamemode(context, targets, gamemode)
+                })),
+        );
+    }
+    command
+}
+
+fn set_gamemode(context: &mut CommandContext, targets: Vec<i32>, gamemode: Gamemode) -> Result<i32, CommandError> {
+    let mut changed = 0;
+    for player_eid in targets {
+        let updated = context.write_player(player_eid, |player| {
+            if player.gamemode == gamemode {
+                return None;
+            }
+            player.previous_gamemode = Some(player.gamemode);
+            player.gamemode = gamemode;
+            player.abilities = gamemode.abilities(player.abilities.has_ability(Ability::Flying));
+            Some((
+                player.uuid,
+                player.username.clone(),
+                PlayerAbilitiesPacket::from_player(player),
+            ))
+        })?;
+        let (uuid, username, abilities) = match updated {
+            Some(updated) => updated,
+            None => continue,
+        };
+
+        context.server.send_to_player(
+            player_eid,
+            ClientboundPacket::ChangeGameState(ChangeGameStatePacket {
+                change: GameStateChange::ChangeGamemode(gamemode),
+            }),
+        );
+        context.server.send_to_player(player_eid, ClientboundPacket::PlayerAbilities(abilities));
+        context.server.send_to_all(ClientboundPacket::PlayerInfo(PlayerInfoPacket::UpdateGamemode(vec![(
+            uuid, gamemode,
+        )])));
+
+        if context.source_eid() == Some(player_eid) {
+            context.send_feedback(Chat::new(format!("Set own game mode to {}", gamemode.display_name())));
+        } else {
+            send_message(
+                context.server,
+                player_eid,
+                Chat::new(format!("Your game mode has been updated to {}", gamemode.display_name())),
+            );
+            context.send_feedback(Chat::new(format!(
+                "Set {}'s game mode to {}",
+                username,
+                gamemode.display_name()
+            )));
+        }
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+fn teleport_command(name: &str) -> CommandBuilder {
+    literal(name)
+        .requires(OPLevel::Gamemaster)
+        .permission("mcrust.command.teleport")
+        .then(argument("location", vec3()).executes(|context| {
+            let player_eid = context.source_player()?;
+            let position = context.get_position("location")?;
+            teleport_to_position(context, vec![player_eid], position, None)
+        }))
+        .then(argument("destination", entity()).suggested_by_server().executes(|context| {
+            let player_eid = context.source_player()?;
+            teleport_to_entity(context, vec![player_eid])
+        }))
+        .then(
+            argument("targets", entities())
+                .suggested_by_server()
+                .then(
+                    argument("location", vec3())
+                        .executes(|context| {
+                            let targets = context.get_entities("targets")?;
+                            let position = context.get_position("location")?;
+                            teleport_to_position(context, targets, position, None)
+                        })
+                        .then(argument("rotation", rotation()).executes(|context| {
+                            let targets = context.get_entities("targets")?;
+                            let position = context.get_position("location")?;
+                            let rotation = context.get_rotation("rotation")?;
+                            teleport_to_position(context, targets, position, Some(rotation))
+                        })),
+                )
+                .then(argument("destination", entity()).suggested_by_server().executes(|context| {
+                    let targets = context.get_entities("targets")?;
+                    teleport_to_entity(context, targets)
+                })),
+        )
+}
+
+fn teleport_to_position(
+    context: &mut CommandContext,
+    targets: Vec<i32>,
+    position: (f64, f64, f64),
+    rotation: Option<(f32, f32)>,
+) -> Result<i32, CommandError> {
+    let count = targets.len();
+    let name = context.entity_name(targets[0])?;
+    for target in targets {
+        teleport(context, target, position, rotation)?;
+    }
+
+    let (x, y, z) = (format_double(position.0), format_double(position.1), format_double(position.2));
+    context.send_feedback(Chat::new(if count == 1 {
+        format!("Teleported {} to {}, {}, {}", name, x, y, z)
+    } else {
+        format!("Teleported {} entities to {}, {}, {}", count, x, y, z)
+    }));
+    Ok(count as i32)
+}
+
+fn teleport_to_entity(context: &mut CommandContext, targets: Vec<i32>) -> Result<i32, CommandError> {
+    let destination = context.get_entity("destination")?;
+    let (position, rotation) = context.read_player(destination, |player| {
+        (
+            (player.position.x, player.position.y, player.position.z),
+            (player.look.yaw, player.look.pitch),
+        )
+    })?;
+    let count = targets.len();
+    let name = context.entity_name(targets[0])?;
+    for target in targets {
+        teleport(context, target, position, Some(rotation))?;
+    }
+
+    let destination_name = context.entity_name(destination)?;
+    context.send_feedback(Chat::new(if count == 1 {
+        format!("Teleported {} to {}", name, destination_name)
+    } else {
+        format!("Teleported {} entities to {}", count, destination_name)
+    }));
+    Ok(count as i32)
+}
+
+// The chunks around the new position are sent once the client confirms the teleport and moves
+fn teleport(
+    context: &CommandContext,
+    player_eid: i32,
+    position: (f64, f64, f64),
+    rotation: Option<(f32, f32)>,
+) -> Result<(), CommandError> {
+    let (previous, packet) = context.write_player(player_eid, |player| {
+        let previous = (player.position.clone(), player.look.clone());
+        player.position.x = position.0;
+        player.position.y = position.1;
+        player.position.z = position.2;
+        if let Some((yaw, pitch)) = rotation {
+            player.look.yaw = yaw;
+            player.look.pitch = pitch.clamp(-90.0, 90.0);
+        }
+        let teleport_id = player.start_teleport();
+        (previous, PlayerPositionAndLookPacket::from_player(player, teleport_id))
+    })?;
+    context
+        .server
+        .send_to_player(player_eid, ClientboundPacket::PlayerPositionAndLook(packet));
+
+    let tracking_packets = context
+        .world()?
+        .update_player_tracking(player_eid, Some(previous), context.server_data.settings.view_distance)
+        .map_err(|e| CommandError::new(format!("Could not update who sees the player: {:?}", e)))?;
+    for (recipient_eid, packet) in tracking_packets {
+        context.server.send_to_player(recipient_eid, packet);
+    }
+    Ok(())
+}
+
+fn give_command() -> CommandBuilder {
+    literal("give").requires(OPLevel::Gamemaster).permission("mcrust.command.give").then(
+        argument("targets", players()).suggested_by_server().then(
+            argument("item", item_stack())
+                .executes(|context| give(context, 1))
+                .then(argument("count", integer_min(1)).executes(|context| {
+                    let count = context.get_integer("count")?;
+                    give(context, count)
+                })),
+        ),
+    )
+}
+
+// Vanilla drops what does not fit into the inventory, that is lost here until there are item entities
+fn give(context: &mut CommandContext, count: i32) -> Result<i32, CommandError> {
+    let targets = context.get_players("targets")?;
+    let (name, nbt) = context.get_item_stack("item")?;
+    let item_id = item_id(&name).ok_or_else(|| CommandError::new(format!("Unknown item '{}'", name)))?;
+    if nbt.is_some() {
+        return Err(CommandError::new("Items with NBT can not be given yet".to_string()));
+    }
+    let max_count = MAX_STACK_SIZE as i32 * 100;
+    if count > max_count {
+        return Err(CommandError::new(format!("Can't give more than {} of {}", max_count, name)));
+    }
+
+    for target in targets.iter() {
+        let packets = context.write_player(*target, |player| {
+            let (changed, _) = player.add_items(item_id, count as u32);
+            changed
+                .into_iter()
+                .map(|index| SetSlotPacket {
+                    window_id: 0,
+                    slot: Player::window_slot(index),
+                    item: ItemStack::to_slot(player.inventory[index]),
+                })
+                .collect::<Vec<_>>()
+        })?;
+        for packet in packets {
+            context.server.send_to_player(*target, ClientboundPacket::SetSlot(packet));
+        }
+    }
+
+    let item = if name.contains(':') { name } else { format!("minecraft:{}", name) };
+    let feedback = if targets.len() == 1 {
+        format!("Gave {} [{}] to {}", count, item, context.entity_name(targets[0])?)
+    } else {
+        format!("Gave {} [{}] to {} players", count, item, targets.len())
+    };
+    context.send_feedback(Chat::new(feedback));
+    Ok(targets.len() as i32)
+}
+
+fn kick_command() -> CommandBuilder {
+    literal("kick").requires(OPLevel::Admin).permission("mcrust.command.kick").then(
+        argument("targets", players())
+            .suggested_by_server()
+            .executes(|context| kick(context, "Kicked by an operator".to_string()))
+            .then(argument("reason", message()).executes(|context| {
+                let reason = context.get_string("reason")?;
+                kick(context, reason)
+            })),
+    )
+}
+
+fn kick(context: &mut CommandContext, reason: String) -> Result<i32, CommandError> {
+    let targets = context.get_players("targets")?;
+    for player_eid in targets.iter() {
+        let username = context.entity_name(*player_eid)?;
+        context.server.disconnect_player(*player_eid, Chat::new(reason.clone()));
+        context.send_feedback(Chat::new(format!("Kicked {}: {}", username, reason)));
+    }
+    Ok(targets.len() as i32)
+}
+
+fn say_command() -> CommandBuilder {
+    literal("say")
+        .requires(OPLevel::Gamemaster)
+        .permission("mcrust.command.say")
+        .then(argument("message", message()).executes(|context| {
+            let message = context.get_string("message")?;
+            let name = context.source_name()?;
+            let message = format!("[{}] {}", name, message);
+            console::log(&message);
+            context.server.send_to_all(ClientboundPacket::ChatMessage(ChatMessagePacket {
+                message: Chat::new(message),
+                sender: Uuid::nil(),
+                position: ChatPosition::SystemMessage,
+            }));
+            Ok(1)
+        }))
+}
+
+fn msg_command(name: &str) -> CommandBuilder {
+    literal(name).permission("mcrust.command.msg").then(
+        argument("targets", players()).suggested_by_server().then(argument("message", message()).executes(|context| {
+            let targets = context.get_players("targets")?;
+            let message = context.get_string("message")?;
+            let name = context.source_name()?;
+            for player_eid in targets.iter() {
+                let username = context.entity_name(*player_eid)?;
+                send_message(
+                    context.server,
+                    *player_eid,
+                    Chat::new(format!("{} whispers to you: {}", name, message))
+                        .color("gray".to_string())
+                        .italic(true),
+                );
+                context.send_feedback(
+                    Chat::new(format!("You whisper to {}: {}", username, message))
+                        .color("gray".to_string())
+                        .italic(true),
+                );
+            }
+            Ok(targets.len() as i32)
+        })),
+    )
+}
+
+fn list_command() -> CommandBuilder {
+    literal("list").permission("mcrust.command.list").executes(|context| {
+        let names = context.player_names();
+        let max_players = context.server_data.settings.max_players;
+        context.send_feedback(Chat::new(format!(
+            "There are {} of a max of {} players online: {}",
+            names.len(),
+            max_players,
+            names.join(", ")
+        )));
+        Ok(names.len() as i32)
+    })
+}
+
+fn time_command() -> CommandBuilder {
+    let mut set = literal("set").then(argument("time", time()).executes(|context| {
+        let time = context.get_integer("time")?;
+        set_time(context, time as i64)
+    }));
+    for (name, time) in [("day", 1000), ("noon", 6000), ("night", 13000), ("midnight", 18000)] {
+        set = set.then(literal(name).executes(move |context| set_time(context, time)));
+    }
+
+    let mut query = literal("query");
+    for name in ["daytime", "gametime", "day"] {
+        query = query.then(literal(name).executes(move |context| {
+            let world_time = &context.world()?.time;
+            let time = match name {
+                "daytime" => world_time.day_time(),
+                "gametime" => world_time.world_age % i32::MAX as i64,
+                _ => world_time.day() % i32::MAX as i64,
+            };
+            context.send_feedback(Chat::new(format!("The time is {}", time)));
+            Ok(time as i32)
+        }));
+    }
+
+    literal("time")
+        .requires(OPLevel::Gamemaster)
+        .permission("mcrust.command.time")
+        .then(set)
+        .then(literal("add").then(argument("time", time()).executes(|context| {
+            let ticks = context.get_integer("time")? as i64;
+            for world in context.server_data.settings.worlds.values_mut() {
+                world.time.add_time(ticks);
+            }
+            let day_time = context.world()?.time.day_time();
+            send_time(context)?;
+            context.send_feedback(Chat::new(format!("Set the time to {}", day_time)));
+            Ok(day_time as i32)
+        })))
+        .then(query)
+}
+
+fn set_time(context: &mut CommandContext, time: i64) -> Result<i32, CommandError> {
+    for world in context.server_data.settings.worlds.values_mut() {
+        world.time.set_time_of_day(time);
+    }
+    send_time(context)?;
+    context.send_feedback(Chat::new(format!("Set the time to {}", time)));
+    Ok(context.world()?.time.day_time() as i32)
+}
+
+// Every world changed, each one tells its own players
+fn send_time(context: &CommandContext) -> Result<(), CommandError> {
+    for world in context.server_data.settings.worlds.values() {
+        let player_eids = world.player_eids().map_err(|e| CommandError::new(format!("Could not get the players: {:?}", e)))?;
+        let packet = world.time.time_update_packet();
+        context.server.send_to_players(&player_eids, ClientboundPacket::TimeUpdate(packet));
+    }
+    Ok(())
+}
+
+// The weather fades in and out on the next ticks, this only changes what it is going to be
+fn weather_command() -> CommandBuilder {
+    let mut command = literal("weather")
+        .requires(OPLevel::Gamemaster)
+        .permission("mcrust.command.weather");
+    for (name, weather) in [("clear", WeatherType::Clear), ("rain", WeatherType::Rain), ("thunder", WeatherType::Thunder)] {
+        command = command.then(
+            literal(name)
+                .executes(move |context| set_weather(context, weather, None))
+                .then(
+                    argument("duration", integer_between(0, MAX_WEATHER_DURATION)).executes(move |context| {
+                        let duration = context.get_integer("duration")?;
+                        set_weather(context, weather, Some(duration * 20))
+                    }),
+                ),
+        );
+    }
+    command
+}
+
+fn set_weather(context: &mut CommandContext, weather: WeatherType, duration: Option<i32>) -> Result<i32, CommandError> {
+    let (position, _) = context.source_position()?;
+    let world = context.world_mut()?;
+    world.weather.set(weather, duration);
+    // Where the source is it might be cold enough for snow
+    let snowing = world.precipitation_at(position.y.floor() as i32) == PrecipitationType::Snow;
+    let current = world.weather.current();
+    context.send_feedback(Chat::new(
+        match (current, snowing) {
+            (WeatherType::Clear, _) => "Set the weather to clear",
+            (WeatherType::Rain, false) => "Set the weather to rain",
+            (WeatherType::Rain, true) => "Set the weather to snow",
+            (WeatherType::Thunder, false) => "Set the weather to rain & thunder",
+            (WeatherType::Thunder, true) => "Set the weather to snow & thunder",
+        }
+        .to_string(),
+    ));
+    Ok(duration.unwrap_or(0))
+}
+
+// Game rules are the same in every world
+fn gamerule_command() -> CommandBuilder {
+    let mut command = literal("gamerule")
+        .requires(OPLevel::Gamemaster)
+        .permission("mcrust.command.gamerule");
+    for name in GAME_RULES {
+        command = command.then(
+            literal(name)
+                .executes(move |context| {
+                    let value = context
+                        .world()?
+                        .game_rule(name)
+                        .ok_or_else(|| CommandError::new(format!("Unknown game rule {}", name)))?;
+                    context.send_feedback(Chat::new(format!("Gamerule {} is currently set to: {}", name, value)));
+                    Ok(value as i32)
+                })
+                .then(argument("value", boolean()).executes(move |context| {
+                    let value = context.get_bool("value")?;
+                    for world in context.server_data.settings.worlds.values_mut() {
+                        world.set_game_rule(name, value);
+                    }
+                    // The client only stops the sun when it is told so with the time
+                    if name == "doDaylightCycle" {
+                        send_time(context)?;
+                    }
+                    context.send_feedback(Chat::new(format!("Gamerule {} is now set to: {}", name, value)));
+                    Ok(value as i32)
+                })),
+        );
+    }
+    command
+}
+
+fn difficulty_command() -> CommandBuilder {
+    let command = literal("difficulty").requires(OPLevel::Gamemaster).permission("mcrust.command.difficulty");
+    let mut command = command.executes(|context| {
+        let difficulty = context.world()?.difficulty;
+        context.send_feedback(Chat::new(format!("The difficulty is {}", difficulty.display_name())));
+        Ok(difficulty as i32)
+    });
+    for difficulty in [Difficulty::Peaceful, Difficulty::Easy, Difficulty::Normal, Difficulty::Hard] {
+        command = command.then(literal(difficulty.name()).executes(move |context| {
+            let world = context.world_mut()?;
+            if world.difficulty == difficulty {
+                return Err(CommandError::new(format!(
+                    "The difficulty did not change; it is already set to {}",
+                    difficulty.name()
+                )));
+            }
+            world.difficulty = difficulty;
+            let packet = ChangeDifficultyPacket {
+                difficulty,
+                difficulty_locked: world.difficulty_locked,
+            };
+            context.server.send_to_all(ClientboundPacket::ChangeDifficulty(packet));
+            context.send_feedback(Chat::new(format!(
+                "The difficulty has been set to {}",
+                difficulty.display_name()
+            )));
+            Ok(0)
+        }));
+    }
+    command
+}
+
+fn stop_command() -> CommandBuilder {
+    literal("stop").requires(OPLevel::Owner).permission("mcrust.command.stop").executes(|context| {
+        context.send_feedback(Chat::new("Stopping the server".to_string()));
+        // Stopping needs the server data, which is locked while the command runs
+        context
+            .server
+            .scheduler
+            .schedule(Box::new(|server| server.stop()))
+            .map_err(|e| CommandError::new(format!("Could not stop the server: {:?}", e)))?;
+        Ok(1)
+    })
+}
+
+fn op_command() -> CommandBuilder {
+    literal("op").requires(OPLevel::Admin).permission("mcrust.command.op").then(
+        argument("targets", game_profile())
+            .suggested_by_server()
+            .executes(|context| {
+                let level = context.server_data.settings.op_permission_level;
+                op(context, level)
+            })
+            .then(argument("level", integer_between(1, 4)).executes(|context| {
+                let level = context.get_integer("level")?;
+                let level = OPLevel::from_number(level)
+                    .ok_or_else(|| CommandError::new(format!("Invalid operator level {}", level)))?;
+                op(context, level)
+            })),
+    )
+}
+
+fn op(context: &mut CommandContext, level: OPLevel) -> Result<i32, CommandError> {
+    let mut changed = 0;
+    for (uuid, name) in context.get_game_profiles("targets")? {
+        if context.server_data.ops.set(uuid, &name, level) {
+            update_op_level(context, uuid, level)?;
+            context.send_feedback(Chat::new(format!("Made {} a server operator", name)));
+            changed += 1;
+        }
+    }
+    if changed == 0 {
+        return Err(CommandError::new("Nothing changed. The player already is an operator".to_string()));
+    }
+    saved(context.server_data.ops.save())?;
+    Ok(changed)
+}
+
+fn deop_command() -> CommandBuilder {
+    literal("deop").requires(OPLevel::Admin).permission("mcrust.command.deop").then(
+        argument("targets", game_profile()).suggested_by_server().executes(|context| {
+            let mut changed = 0;
+            for (uuid, name) in context.get_game_profiles("targets")? {
+                if context.server_data.ops.remove(uuid) {
+                    update_op_level(context, uuid, OPLevel::Player)?;
+                    context.send_feedback(Chat::new(format!("Made {} no longer a server operator", name)));
+                    changed += 1;
+                }
+            }
+            if changed == 0 {
+                return Err(CommandError::new("Nothing changed. The player is not an operator".to_string()));
+            }
+            saved(context.server_data.ops.save())?;
+            Ok(changed)
+        }),
+    )
+}
+
+// Players that are online get told their new level and the commands they can use with it
+fn update_op_level(context: &mut CommandContext, uuid: Uuid, level: OPLevel) -> Result<(), CommandError> {
+    let player_eid = match context.player_eid(uuid) {
+        Some(player_eid) => player_eid,
+        None => return Ok(()),
+    };
+    let permissions = context.write_player(player_eid, |player| {
+        player.permissions.op_level = level;
+        player.permissions.clone()
+    })?;
+    context.server.send_to_player(
+        player_eid,
+        ClientboundPacket::EntityStatus(EntityStatusPacket {
+            entity_id: player_eid,
+            entity_status: EntityStatus::for_op_level(level),
+        }),
+    );
+    let (nodes, root_node) = context.server_data.commands.nodes_for(&permissions);
+    context.server.send_to_player(
+        player_eid,
+        ClientboundPacket::DeclareCommands(DeclareCommandsPacket { nodes, root_node }),
+    );
+    Ok(())
+}
+
+// Commands change the lists on disk right away
+fn saved(result: Result<(), ErrorType>) -> Result<(), CommandError> {
+    result.map_err(|e| CommandError::new(format!("Could not save the changes: {:?}", e)))
+}
+
+// Adds an optional reason to a command, without one the default reason is used
+fn with_reason(
+    command: CommandBuilder,
+    handler: fn(&mut CommandContext, String) -> Result<i32, CommandError>,
+) -> CommandBuilder {
+    command
+        .executes(move |context| handler(context, DEFAULT_BAN_REASON.to_string()))
+        .then(argument("reason", message()).executes(move |context| {
+            let reason = context.get_string("reason")?;
+            handler(context, reason)
+        }))
+}
+
+// When a temporary ban ends
+fn get_expiry(context: &CommandContext) -> Result<i64, CommandError> {
+    let duration = context.get_string("duration")?;
+    let seconds = parse_duration(&duration).ok_or_else(|| {
+        CommandError::new(format!("Invalid duration '{}', use something like 30m, 12h, 7d or 1w", duration))
+    })?;
+    now()
+        .checked_add(seconds)
+        .ok_or_else(|| CommandError::new(format!("Duration '{}' is too long", duration)))
+}
+
+fn ban_command() -> CommandBuilder {
+    literal("ban")
+        .requires(OPLevel::Admin)
+        .permission("mcrust.command.ban")
+        .then(with_reason(argument("targets", game_profile()).suggested_by_server(), |context, reason| {
+            ban(context, None, reason)
+        }))
+}
+
+fn tempban_command() -> CommandBuilder {
+    literal("tempban").requires(OPLevel::Admin).permission("mcrust.command.tempban").then(
+        argument("targets", game_profile())
+            .suggested_by_server()
+            .then(with_reason(argument("duration", word()), |context, reason| {
+                let expires = get_expiry(context)?;
+                ban(context, Some(expires), reason)
+            })),
+    )
+}
+
+fn ban(context: &mut CommandContext, expires: Option<i64>, reason: String) -> Result<i32, CommandError> {
+    let source = context.source_name()?;
+    let mut banned = 0;
+    for (uuid, name) in context.get_game_profiles("targets")? {
+        let entry = BanEntry::new(uuid, &name, &source, expires, &reason);
+        let message = entry.disconnect_message();
+        if !context.server_data.banned_players.add(entry) {
+            continue;
+        }
+        context.send_feedback(Chat::new(format!("Banned {}: {}", name, reason)));
+        if let Some(player_eid) = context.player_eid(uuid) {
+            context.server.disconnect_player(player_eid, Chat::new(message));
+        }
+        banned += 1;
+    }
+    if banned == 0 {
+        return Err(CommandError::new("Nothing changed. The player is already banned".to_string()));
+    }
+    saved(context.server_data.banned_players.save())?;
+    Ok(banned)
+}
+
+fn ban_ip_command() -> CommandBuilder {
+    literal("ban-ip")
+        .requires(OPLevel::Admin)
+        .permission("mcrust.command.ban-ip")
+        .then(with_reason(argument("target", word()), |context, reason| {
+            ban_ip(context, None, reason)
+        }))
+}
+
+fn tempban_ip_command() -> CommandBuilder {
+    literal("tempban-ip").requires(OPLevel::Admin).permission("mcrust.command.tempban-ip").then(
+        argument("target", word()).then(with_reason(argument("duration", word()), |context, reason| {
+            let expires = get_expiry(context)?;
+            ban_ip(context, Some(expires), reason)
+        })),
+    )
+}
+
+// The target is either an address or the name of a player that is online
+fn ban_ip(context: &mut CommandContext, expires: Option<i64>, reason: String) -> Result<i32, CommandError> {
+    let target = context.get_string("target")?;
+    let address = match target.parse::<IpAddr>() {
+        Ok(address) => Some(address),
+        Err(_) => online_player_named(context, &target)?
+            .and_then(|player_eid| context.server.player_address(player_eid)),
+    };
+    let address = address
+        .ok_or_else(|| CommandError::new("Invalid IP address or unknown player".to_string()))?
+        .to_string();
+
+    let source = context.source_name()?;
+    let entry = IpBanEntry::new(&address, &source, expires, &reason);
+    let message = entry.disconnect_message();
+    if !context.server_data.banned_ips.add(entry) {
+        return Err(CommandError::new("Nothing changed. That IP is already banned".to_string()));
+    }
+    saved(context.server_data.banned_ips.save())?;
+    context.send_feedback(Chat::new(format!("Banned IP {}: {}", address, reason)));
+
+    let mut affected = vec![];
+    for (_, player_eid) in context.online_players() {
+        if context.server.player_address(player_eid).map(|a| a.to_string()) == Some(address.clone()) {
+            affected.push((player_eid, context.entity_name(player_eid)?));
+        }
+    }
+    if !affected.is_empty() {
+        let names: Vec<String> = affected.iter().map(|(_, name)| name.clone()).collect();
+        context.send_feedback(Chat::new(format!(
+            "This IP affects {} players: {}",
+            names.len(),
+            names.join(", ")
+        )));
+    }
+    for (player_eid, _) in affected.iter() {
+        context.server.disconnect_player(*player_eid, Chat::new(message.clone()));
+    }
+    Ok(affected.len() as i32)
+}
+
+fn online_player_named(context: &CommandContext, name: &str) -> Result<Option<i32>, CommandError> {
+    for (_, player_eid) in context.online_players() {
+        if context.entity_name(player_eid)? == name {
+            return Ok(Some(player_eid));
+        }
+    }
+    Ok(None)
+}
+
+fn pardon_command() -> CommandBuilder {
+    literal("pardon").requires(OPLevel::Admin).permission("mcrust.command.pardon").then(
+        argument("targets", game_profile()).suggested_by_server().executes(|context| {
+            let mut pardoned = 0;
+            for (uuid, name) in context.get_game_profiles("targets")? {
+                if context.server_data.banned_players.remove(uuid) {
+                    context.send_feedback(Chat::new(format!("Unbanned {}", name)));
+                    pardoned += 1;
+                }
+            }
+            if pardoned == 0 {
+                return Err(CommandError::new("Nothing changed. The player isn't banned".to_string()));
+            }
+            saved(context.server_data.banned_players.save())?;
+            Ok(pardoned)
+        }),
+    )
+}
+
+fn pardon_ip_command() -> CommandBuilder {
+    literal("pardon-ip").requires(OPLevel::Admin).permission("mcrust.command.pardon-ip").then(
+        argument("target", word()).executes(|context| {
+            let target = context.get_string("target")?;
+            let address = target
+                .parse::<IpAddr>()
+                .map_err(|_| CommandError::new("Invalid IP address".to_string()))?
+                .to_string();
+            if !context.server_data.banned_ips.remove(&address) {
+                return Err(CommandError::new("Nothing changed. That IP isn't banned".to_string()));
+            }
+            saved(context.server_data.banned_ips.save())?;
+            context.send_feedback(Chat::new(format!("Unbanned IP {}", address)));
+            Ok(1)
+        }),
+    )
+}
+
+fn banlist_command() -> CommandBuilder {
+    literal("banlist")
+        .requires(OPLevel::Admin)
+        .permission("mcrust.command.banlist")
+        .executes(|context| banlist(context, true, true))
+        .then(literal("players").executes(|context| banlist(context, true, false)))
+        .then(literal("ips").executes(|context| banlist(context, false, true)))
+}
+
+fn banlist(context: &mut CommandContext, players: bool, ips: bool) -> Result<i32, CommandError> {
+    let mut lines = vec![];
+    if players {
+        for ban in context.server_data.banned_players.active() {
+            lines.push(format!("{} was banned by {}: {}", ban.name, ban.source, ban.reason));
+        }
+    }
+    if ips {
+        for ban in context.server_data.banned_ips.active() {
+            lines.push(format!("{} was banned by {}: {}", ban.ip, ban.source, ban.reason));
+        }
+    }
+    if lines.is_empty() {
+        context.send_feedback(Chat::new("There are no bans".to_string()));
+        return Ok(0);
+    }
+    context.send_feedback(Chat::new(format!("There are {} ban(s):", lines.len())));
+    for line in lines.iter() {
+        context.send_feedback(Chat::new(line.clone()));
+    }
+    Ok(lines.len() as i32)
+}
+
+fn whitelist_command() -> CommandBuilder {
+    literal("whitelist")
+        .requires(OPLevel::Admin)
+        .permission("mcrust.command.whitelist")
+        .then(literal("on").executes(|context| {
+            if context.server_data.settings.whitelist {
+                return Err(CommandError::new("Whitelist is already turned on".to_string()));
+            }
+            context.server_data.settings.whitelist = true;
+            context.send_feedback(Chat::new("Whitelist is now turned on".to_string()));
+            kick_unlisted_players(context);
+            Ok(1)
+        }))
+        .then(literal("off").executes(|context| {
+            if !context.server_data.settings.whitelist {
+                return Err(CommandError::new("Whitelist is already turned off".to_string()));
+            }
+            context.server_data.settings.whitelist = false;
+            context.send_feedback(Chat::new("Whitelist is now turned off".to_string()));
+            Ok(1)
+        }))
+        .then(literal("list").executes(|context| {
+            let names: Vec<String> = context.server_data.whitelist.entries.iter().map(|e| e.name.clone()).collect();
+            if names.is_empty() {
+                context.send_feedback(Chat::new("There are no whitelisted players".to_string()));
+            } else {
+                context.send_feedback(Chat::new(format!(
+                    "There are {} whitelisted players: {}",
+                    names.len(),
+                    names.join(", ")
+                )));
+            }
+            Ok(names.len() as i32)
+        }))
+        .then(literal("add").then(argument("targets", game_profile()).suggested_by_server().executes(|context| {
+            let mut added = 0;
+            for (uuid, name) in context.get_game_profiles("targets")? {
+                if context.server_data.whitelist.add(uuid, &name) {
+                    context.send_feedback(Chat::new(format!("Added {} to the whitelist", name)));
+                    added += 1;
+                }
+            }
+            if added == 0 {
+                return Err(CommandError::new("Player is already whitelisted".to_string()));
+            }
+            saved(context.server_data.whitelist.save())?;
+            Ok(added)
+        })))
+        .then(literal("remove").then(argument("targets", game_profile()).suggested_by_server().executes(|context| {
+            let mut removed = 0;
+            for (uuid, name) in context.get_game_profiles("targets")? {
+                if context.server_data.whitelist.remove(uuid) {
+                    context.send_feedback(Chat::new(format!("Removed {} from the whitelist", name)));
+                    removed += 1;
+                }
+            }
+            if removed == 0 {
+                return Err(CommandError::new("Player is not whitelisted".to_string()));
+            }
+            saved(context.server_data.whitelist.save())?;
+            kick_unlisted_players(context);
+            Ok(removed)
+        })))
+        .then(literal("reload").executes(|context| {
+            saved(context.server_data.whitelist.reload())?;
+            context.send_feedback(Chat::new("Reloaded the whitelist".to_string()));
+            kick_unlisted_players(context);
+            Ok(1)
+        }))
+}
+
+// Only when the whitelist is enforced do players that are already online have to leave
+fn kick_unlisted_players(context: &CommandContext) {
+    if !context.server_data.settings.enforce_whitelist {
+        return;
+    }
+    for (uuid, player_eid) in context.online_players() {
+        if !context.server_data.may_join_whitelist(uuid) {
+            context.server.disconnect_player(player_eid, Chat::new(NOT_WHITELISTED.to_string()));
+        }
+    }
+}
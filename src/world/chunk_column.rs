@@ -1,22 +1,56 @@
 use std::collections::HashMap;
+use std::mem;
 
 use crate::nbt::NBTTag;
 
-use super::ChunkSection;
+use super::{ChunkPosition, ChunkSection};
 
+#[derive(Clone)]
 pub struct ChunkColumn {
+    pub position: ChunkPosition,
     sections: Vec<ChunkSection>
 }
 
-impl From<Vec<ChunkSection>> for ChunkColumn {
-    fn from(value: Vec<ChunkSection>) -> Self {
+impl ChunkColumn {
+    pub fn new(position: ChunkPosition, sections: Vec<ChunkSection>) -> Self {
         Self {
-            sections: value
+            position,
+            sections
         }
     }
-}
 
-impl ChunkColumn {
+    // x, z are relative to the column, y is the height in the world
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> u16 {
+        match self.sections.get(y / 16) {
+            Some(section) => section.get_block(x, y % 16, z),
+            None => 0,
+        }
+    }
+
+    // x, z are relative to the column, y is the height in the world
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: u16) {
+        if let Some(section) = self.sections.get_mut(y / 16) {
+            section.set_block(x, y % 16, z, block);
+        }
+    }
+
+    // The sections one after the other, this is how a column is saved to disk
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.sections.iter().flat_map(|section| section.to_bytes()).collect()
+    }
+
+    pub fn from_bytes(position: ChunkPosition, bytes: &[u8]) -> Result<Self, String> {
+        let sections = bytes
+            .chunks(4096 * 2)
+            .map(ChunkSection::from_bytes)
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(position, sections))
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        mem::size_of::<Self>() + self.sections.len() * mem::size_of::<ChunkSection>()
+    }
+
     pub fn get_heightmaps(&self) -> NBTTag {
         let heightmaps = HashMap::new();
 
@@ -76,3 +110,22 @@ impl ChunkColumn {
         ret
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::ChunkColumn;
+    use crate::world::{ChunkPosition, ChunkSection};
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let position = ChunkPosition::new(-3, 7);
+        let sections = (0..16).map(|_| ChunkSection::from([0u16; 4096])).collect();
+        let mut column = ChunkColumn::new(position, sections);
+        column.set_block(1, 70, 15, 42);
+
+        let read_column = ChunkColumn::from_bytes(position, &column.to_bytes()).unwrap();
+        assert_eq!(read_column.get_block(1, 70, 15), 42);
+        assert_eq!(read_column.get_block(0, 70, 15), 0);
+        assert_eq!(read_column.to_bytes(), column.to_bytes());
+    }
+}
@@ -0,0 +1,55 @@
+use crate::player::Position;
+
+// The position of a chunk column, in chunks instead of blocks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ChunkPosition {
+    pub x: i32,
+    pub z: i32,
+}
+
+impl ChunkPosition {
+    pub fn new(x: i32, z: i32) -> Self {
+        Self { x, z }
+    }
+
+    // The chunk the block at block coordinates x, z is in
+    pub fn from_block(x: i32, z: i32) -> Self {
+        Self {
+            x: x.div_euclid(16),
+            z: z.div_euclid(16),
+        }
+    }
+
+    pub fn from_position(position: &Position) -> Self {
+        Self::from_block(position.x.floor() as i32, position.z.floor() as i32)
+    }
+
+    // The distance in chunks, which is what the view distance is measured in
+    pub fn distance(&self, other: &ChunkPosition) -> i32 {
+        (self.x - other.x).abs().max((self.z - other.z).abs())
+    }
+
+    // All chunks that are at most radius chunks away from this one
+    pub fn square(&self, radius: i32) -> Vec<ChunkPosition> {
+        let mut ret = vec![];
+        for x in (self.x - radius)..=(self.x + radius) {
+            for z in (self.z - radius)..=(self.z + radius) {
+                ret.push(ChunkPosition::new(x, z));
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChunkPosition;
+
+    #[test]
+    fn test_from_block() {
+        assert_eq!(ChunkPosition::from_block(0, 15), ChunkPosition::new(0, 0));
+        assert_eq!(ChunkPosition::from_block(16, 31), ChunkPosition::new(1, 1));
+        assert_eq!(ChunkPosition::from_block(-1, -16), ChunkPosition::new(-1, -1));
+        assert_eq!(ChunkPosition::from_block(-17, 0), ChunkPosition::new(-2, 0));
+    }
+}
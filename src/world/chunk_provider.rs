@@ -0,0 +1,482 @@
+use super::{ChunkColumn, ChunkPosition, ChunkSection};
+
+use crate::console;
+use crate::error_type::ErrorType;
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::mem;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+
+const WORKER_COUNT: usize = 4;
+
+// Gets called on a worker thread once a requested chunk is ready
+pub type ChunkCallback = Box<dyn FnOnce(Arc<ChunkColumn>) + Send>;
+
+struct CachedChunk {
+    // None while a worker is still loading it
+    column: Option<Arc<ChunkColumn>>,
+    // The players that have this chunk loaded
+    viewers: HashSet<i32>,
+    waiting: Vec<ChunkCallback>,
+    // Spawn chunks stay loaded, even when nobody is around
+    permanent: bool,
+    // Changed since it was loaded or last saved
+    dirty: bool,
+}
+
+impl CachedChunk {
+    fn new() -> Self {
+        Self {
+            column: None,
+            viewers: HashSet::new(),
+            waiting: vec![],
+            permanent: false,
+            dirty: false,
+        }
+    }
+
+    fn is_unused(&self) -> bool {
+        self.viewers.is_empty() && !self.permanent
+    }
+}
+
+enum ChunkJob {
+    Load(ChunkPosition),
+    Save(Arc<ChunkColumn>),
+}
+
+struct SharedState {
+    cache: Mutex<HashMap<ChunkPosition, CachedChunk>>,
+    // Chunks that are waiting for a worker to write them to disk
+    saving: Mutex<HashMap<ChunkPosition, Arc<ChunkColumn>>>,
+    // Save jobs the workers have not finished yet, whether they end up written or not
+    pending_saves: Mutex<usize>,
+    saves_done: Condvar,
+    directory: PathBuf,
+}
+
+#[derive(Debug, Default)]
+pub struct ChunkStats {
+    pub loaded: usize,
+    pub loading: usize,
+    pub permanent: usize,
+    pub dirty: usize,
+    pub memory_bytes: usize,
+}
+
+impl fmt::Display for ChunkStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} chunks loaded ({} spawn, {} unsaved, {} still loading) using {:.1} MiB",
+            self.loaded,
+            self.permanent,
+            self.dirty,
+            self.loading,
+            self.memory_bytes as f64 / (1024.0 * 1024.0)
+        )
+    }
+}
+
+// Keeps the chunks that players (or the spawn area) need in memory, and loads and saves them on
+// a pool of worker threads so connections never wait for disk I/O or world generation while
+// holding the server lock
+pub struct ChunkProvider {
+    shared: Arc<SharedState>,
+    job_sender: Mutex<Sender<ChunkJob>>,
+}
+
+impl ChunkProvider {
+    pub fn new(directory: PathBuf) -> Self {
+        let shared = Arc::new(SharedState {
+            cache: Mutex::new(HashMap::new()),
+            saving: Mutex::new(HashMap::new()),
+            pending_saves: Mutex::new(0),
+            saves_done: Condvar::new(),
+            directory,
+        });
+        let (job_sender, job_receiver) = channel();
+        let job_receiver = Arc::new(Mutex::new(job_receiver));
+
+        for i in 0..WORKER_COUNT {
+            let shared = shared.clone();
+            let job_receiver = job_receiver.clone();
+            thread::Builder::new()
+                .name(format!("chunk worker {}", i))
+                .spawn(move || worker(shared, job_receiver))
+                .expect("Could not start chunk worker");
+        }
+
+        Self {
+            shared,
+            job_sender: Mutex::new(job_sender),
+        }
+    }
+
+    // Marks the chunk as loaded by the player. A chunk that is ready is returned right away,
+    // otherwise it is loaded in the background and handed to the callback.
+    pub fn request(
+        &self,
+        position: ChunkPosition,
+        player_eid: i32,
+        callback: ChunkCallback,
+    ) -> Result<Option<Arc<ChunkColumn>>, ErrorType> {
+        let mut cache = self.lock_cache()?;
+        let cached = self.get_or_load(&mut cache, position)?;
+        cached.viewers.insert(player_eid);
+
+        if let Some(column) = &cached.column {
+            return Ok(Some(column.clone()));
+        }
+        cached.waiting.push(callback);
+        Ok(None)
+    }
+
+    // Loads the chunk and keeps it loaded for as long as the server runs
+    pub fn keep_loaded(&self, position: ChunkPosition) -> Result<(), ErrorType> {
+        let mut cache = self.lock_cache()?;
+        self.get_or_load(&mut cache, position)?.permanent = true;
+        Ok(())
+    }
+
+    // The player no longer has the chunk loaded, it is evicted once nobody has
+    pub fn release(&self, position: ChunkPosition, player_eid: i32) -> Result<(), ErrorType> {
+        let mut cache = self.lock_cache()?;
+        if let Some(cached) = cache.get_mut(&position) {
+            cached.viewers.remove(&player_eid);
+            // Chunks that are still loading are dropped by the worker
+            if cached.is_unused() && cached.column.is_some() {
+                if let Some(cached) = cache.remove(&position) {
+                    self.evict(position, cached)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn release_all(&self, player_eid: i32) -> Result<(), ErrorType> {
+        let mut cache = self.lock_cache()?;
+        let mut unused = vec![];
+        for (position, cached) in cache.iter_mut() {
+            cached.viewers.remove(&player_eid);
+            if cached.is_unused() && cached.column.is_some() {
+                unused.push(*position);
+            }
+        }
+        for position in unused {
+            if let Some(cached) = cache.remove(&position) {
+                self.evict(position, cached)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Only gives back chunks that are done loading, this never waits for a worker
+    pub fn get_loaded(&self, position: ChunkPosition) -> Result<Option<Arc<ChunkColumn>>, ErrorType> {
+        Ok(self
+            .lock_cache()?
+            .get(&position)
+            .and_then(|cached| cached.column.clone()))
+    }
+
+    // Changes a loaded chunk, returns false if the chunk is not loaded
+    pub fn modify<F>(&self, position: ChunkPosition, change: F) -> Result<bool, ErrorType>
+    where
+        F: FnOnce(&mut ChunkColumn),
+    {
+        let mut cache = self.lock_cache()?;
+        match cache.get_mut(&position) {
+            Some(CachedChunk { column: Some(column), dirty, .. }) => {
+                // Packets that are still being sent keep the old version
+                change(Arc::make_mut(column));
+                *dirty = true;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    // Writes every changed chunk to disk, they stay loaded
+    pub fn save_all(&self) -> Result<(), ErrorType> {
+        let mut cache = self.lock_cache()?;
+        for (position, cached) in cache.iter_mut() {
+            if let (true, Some(column)) = (cached.dirty, &cached.column) {
+                self.queue_save(*position, column.clone())?;
+                cached.dirty = false;
+            }
+        }
+        Ok(())
+    }
+
+    // Like save_all, but only returns once everything is on disk, for when the server stops
+    pub fn save_all_blocking(&self) -> Result<(), ErrorType> {
+        self.save_all()?;
+        let mut pending_saves = lock_pending_saves(&self.shared)?;
+        while *pending_saves > 0 {
+            pending_saves = self
+                .shared
+                .saves_done
+                .wait(pending_saves)
+                .map_err(|e| ErrorType::Fatal(format!("Could not wait for chunks to be saved: {}", e)))?;
+        }
+        Ok(())
+    }
+
+    pub fn stats(&self) -> Result<ChunkStats, ErrorType> {
+        let cache = self.lock_cache()?;
+        let mut stats = ChunkStats::default();
+        for cached in cache.values() {
+            match &cached.column {
+                Some(column) => {
+                    stats.loaded += 1;
+                    stats.memory_bytes += column.memory_usage();
+                }
+                None => stats.loading += 1,
+            }
+            if cached.permanent {
+                stats.permanent += 1;
+            }
+            if cached.dirty {
+                stats.dirty += 1;
+            }
+        }
+        Ok(stats)
+    }
+
+    fn get_or_load<'a>(
+        &self,
+        cache: &'a mut HashMap<ChunkPosition, CachedChunk>,
+        position: ChunkPosition,
+    ) -> Result<&'a mut CachedChunk, ErrorType> {
+        match cache.entry(position) {
+            Entry::Occupied(entry) => Ok(entry.into_mut()),
+            Entry::Vacant(entry) => {
+                self.send_job(ChunkJob::Load(position))?;
+                Ok(entry.insert(CachedChunk::new()))
+            }
+        }
+    }
+
+    fn evict(&self, position: ChunkPosition, cached: CachedChunk) -> Result<(), ErrorType> {
+        if let (true, Some(column)) = (cached.dirty, cached.column) {
+            self.queue_save(position, column)?;
+        }
+        Ok(())
+    }
+
+    // Until the worker has written it, loading the chunk again has to use this version
+    fn queue_save(&self, position: ChunkPosition, column: Arc<ChunkColumn>) -> Result<(), ErrorType> {
+        self.shared
+            .saving
+            .lock()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock saving chunks: {}", e)))?
+            .insert(position, column.clone());
+        *lock_pending_saves(&self.shared)? += 1;
+        self.send_job(ChunkJob::Save(column))
+    }
+
+    fn send_job(&self, job: ChunkJob) -> Result<(), ErrorType> {
+        self.job_sender
+            .lock()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock chunk jobs: {}", e)))?
+            .send(job)
+            .map_err(|e| ErrorType::Fatal(format!("Chunk workers are gone: {}", e)))
+    }
+
+    fn lock_cache(&self) -> Result<MutexGuard<'_, HashMap<ChunkPosition, CachedChunk>>, ErrorType> {
+        self.shared
+            .cache
+            .lock()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock chunk cache: {}", e)))
+    }
+}
+
+fn worker(shared: Arc<SharedState>, job_receiver: Arc<Mutex<Receiver<ChunkJob>>>) {
+    loop {
+        let job = match job_receiver.lock() {
+            Ok(receiver) => receiver.recv(),
+            Err(e) => {
+                console::log(&format!("Could not lock chunk jobs: {}", e));
+                return;
+            }
+        };
+        // The provider is gone
+        let result = match job {
+            Ok(ChunkJob::Load(position)) => load(&shared, position),
+            Ok(ChunkJob::Save(column)) => {
+                let result = save(&shared, column);
+                match lock_pending_saves(&shared) {
+                    Ok(mut pending_saves) => {
+                        *pending_saves -= 1;
+                        shared.saves_done.notify_all();
+                    }
+                    Err(e) => console::log(&format!("Chunk worker: {:?}", e)),
+                }
+                result
+            }
+            Err(_) => return,
+        };
+        if let Err(e) = result {
+            console::log(&format!("Chunk worker: {:?}", e));
+        }
+    }
+}
+
+fn lock_pending_saves(shared: &SharedState) -> Result<MutexGuard<'_, usize>, ErrorType> {
+    shared
+        .pending_saves
+        .lock()
+        .map_err(|e| ErrorType::Fatal(format!("Could not lock pending saves: {}", e)))
+}
+
+fn load(shared: &SharedState, position: ChunkPosition) -> Result<(), ErrorType> {
+    let column = Arc::new(read_column(shared, position)?);
+
+    let waiting = {
+        let mut cache = shared
+            .cache
+            .lock()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock chunk cache: {}", e)))?;
+        match cache.get_mut(&position) {
+            // Everyone who wanted it moved on while it was loading
+            Some(cached) if cached.is_unused() => {
+                cache.remove(&position);
+                vec![]
+            }
+            Some(cached) => {
+                cached.column = Some(column.clone());
+                mem::take(&mut cached.waiting)
+            }
+            None => vec![],
+        }
+    };
+
+    // The cache is unlocked again, so the callbacks are free to use it
+    for callback in waiting {
+        callback(column.clone());
+    }
+    Ok(())
+}
+
+fn read_column(shared: &SharedState, position: ChunkPosition) -> Result<ChunkColumn, ErrorType> {
+    // It might not have made it to the disk yet
+    if let Some(column) = shared
+        .saving
+        .lock()
+        .map_err(|e| ErrorType::Fatal(format!("Could not lock saving chunks: {}", e)))?
+        .get(&position)
+    {
+        return Ok(ChunkColumn::clone(column));
+    }
+
+    let path = chunk_path(shared, position);
+    if path.exists() {
+        let read_result = fs::read(&path)
+            .map_err(|e| format!("{}", e))
+            .and_then(|bytes| ChunkColumn::from_bytes(position, &bytes));
+        match read_result {
+            Ok(column) => return Ok(column),
+            Err(e) => console::log(&format!(
+                "Could not read chunk {}, generating a new one: {}",
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    Ok(generate_column(position))
+}
+
+fn save(shared: &SharedState, column: Arc<ChunkColumn>) -> Result<(), ErrorType> {
+    let path = chunk_path(shared, column.position);
+    fs::create_dir_all(&shared.directory).map_err(|e| {
+        ErrorType::Recoverable(format!("Could not create {}: {}", shared.directory.display(), e))
+    })?;
+    fs::write(&path, column.to_bytes()).map_err(|e| {
+        ErrorType::Recoverable(format!("Could not write {}: {}", path.display(), e))
+    })?;
+
+    // Unless it was changed and evicted again in the meantime, it can be loaded from disk now
+    let mut saving = shared
+        .saving
+        .lock()
+        .map_err(|e| ErrorType::Fatal(format!("Could not lock saving chunks: {}", e)))?;
+    if let Some(saving_column) = saving.get(&column.position) {
+        if Arc::ptr_eq(saving_column, &column) {
+            saving.remove(&column.position);
+        }
+    }
+    Ok(())
+}
+
+fn chunk_path(shared: &SharedState, position: ChunkPosition) -> PathBuf {
+    shared
+        .directory
+        .join(format!("{}.{}.chunk", position.x, position.z))
+}
+
+// TODO: actually generate something
+fn generate_column(position: ChunkPosition) -> ChunkColumn {
+    let sections = (0..16)
+        .map(|_| ChunkSection::from([0u16; 4096]))
+        .collect();
+    ChunkColumn::new(position, sections)
+}
+
+#[cfg(test)]
+mod test {
+    use super::ChunkProvider;
+    use crate::world::{ChunkColumn, ChunkPosition};
+
+    use std::fs;
+    use std::sync::mpsc::channel;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn request(provider: &ChunkProvider, position: ChunkPosition) -> Arc<ChunkColumn> {
+        let (sender, receiver) = channel();
+        let callback = Box::new(move |column| sender.send(column).unwrap());
+        match provider.request(position, 1, callback).unwrap() {
+            Some(column) => column,
+            None => receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let directory = std::env::temp_dir().join(format!("mcrust-chunks-{}", rand::random::<u64>()));
+        let provider = ChunkProvider::new(directory.clone());
+        let position = ChunkPosition::new(2, -5);
+
+        assert_eq!(request(&provider, position).get_block(3, 70, 4), 0);
+        assert!(provider.modify(position, |column| column.set_block(3, 70, 4, 42)).unwrap());
+        assert_eq!(provider.stats().unwrap().dirty, 1);
+
+        // Loading it again right away has to give the changed chunk, whether or not it was written yet
+        provider.release(position, 1).unwrap();
+        assert!(provider.get_loaded(position).unwrap().is_none());
+        assert_eq!(request(&provider, position).get_block(3, 70, 4), 42);
+        provider.release(position, 1).unwrap();
+
+        // Waits for the chunk that was saved when it got released
+        provider.save_all_blocking().unwrap();
+        assert!(directory.join("2.-5.chunk").exists());
+
+        // A new provider only has the disk to go by
+        let provider = ChunkProvider::new(directory.clone());
+        assert_eq!(request(&provider, position).get_block(3, 70, 4), 42);
+        assert!(provider.modify(position, |column| column.set_block(3, 70, 4, 7)).unwrap());
+        provider.save_all_blocking().unwrap();
+        assert_eq!(provider.stats().unwrap().dirty, 0);
+        let provider = ChunkProvider::new(directory.clone());
+        assert_eq!(request(&provider, position).get_block(3, 70, 4), 7);
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
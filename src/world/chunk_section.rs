@@ -41,6 +41,31 @@ impl ChunkSection {
         return vec![];
     }
     
+    // x, y, z are relative to the section (0..16)
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> u16 {
+        self.data[(y * 16 + z) * 16 + x]
+    }
+
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block: u16) {
+        self.data[(y * 16 + z) * 16 + x] = block;
+    }
+
+    // The raw block states, used to save the section to disk
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.data.iter().flat_map(|block| block.to_be_bytes()).collect()
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() != 4096 * 2 {
+            return Err(format!("Expected {} bytes for a section, got {}", 4096 * 2, bytes.len()));
+        }
+        let mut data = [0u16; 4096];
+        for (block, block_bytes) in data.iter_mut().zip(bytes.chunks_exact(2)) {
+            *block = u16::from_be_bytes([block_bytes[0], block_bytes[1]]);
+        }
+        Ok(Self { data })
+    }
+
     pub fn num_blocks(&self) -> i16 {
         return 1;
     }
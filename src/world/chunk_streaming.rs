@@ -0,0 +1,127 @@
+use super::{ChunkColumn, ChunkPosition, World};
+
+use crate::error_type::ErrorType;
+use crate::packets::clientbound::*;
+use crate::player::Player;
+use crate::server::Entity;
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+// Where packets for chunks that finish loading later go. The packet is only made when it is
+// about to be sent, it is left out if the function gives back None by then.
+pub type PacketSink = Arc<dyn Fn(DeferredPacket) + Send + Sync>;
+pub type DeferredPacket = Box<dyn FnOnce() -> Option<ClientboundPacket> + Send>;
+
+// The chunks the client of a player has loaded
+#[derive(Clone)]
+pub struct LoadedChunks {
+    pub center: Option<ChunkPosition>,
+    pub radius: i32,
+    pub chunks: HashSet<ChunkPosition>,
+}
+
+impl LoadedChunks {
+    pub fn new() -> Self {
+        Self {
+            center: None,
+            radius: 0,
+            chunks: HashSet::new(),
+        }
+    }
+}
+
+impl World {
+    // Makes the loaded chunks of the player follow them around, returns the packets that tell
+    // the client which chunks to load and unload. Chunks that still have to be loaded are sent to
+    // the sink once they are ready.
+    pub fn update_loaded_chunks(
+        &self,
+        player_eid: i32,
+        player: &mut Player,
+        sink: PacketSink,
+    ) -> Result<Vec<ClientboundPacket>, ErrorType> {
+        let center = ChunkPosition::from_position(&player.position);
+        let radius = player.view_distance;
+        let loaded_chunks = &mut player.loaded_chunks;
+        if loaded_chunks.center == Some(center) && loaded_chunks.radius == radius {
+            return Ok(vec![]);
+        }
+
+        let mut packets = vec![];
+
+        // Tell the client where they are first, it drops chunks that are too far from it
+        if loaded_chunks.center != Some(center) {
+            packets.push(ClientboundPacket::UpdateViewPosition(UpdateViewPositionPacket {
+                position: center,
+            }));
+        }
+        loaded_chunks.center = Some(center);
+        loaded_chunks.radius = radius;
+
+        let wanted_chunks = center.square(radius).into_iter().collect::<HashSet<_>>();
+
+        let unloaded_chunks = loaded_chunks
+            .chunks
+            .difference(&wanted_chunks)
+            .copied()
+            .collect::<Vec<_>>();
+        for position in unloaded_chunks {
+            loaded_chunks.chunks.remove(&position);
+            self.chunk_provider.release(position, player_eid)?;
+            packets.push(ClientboundPacket::UnloadChunk(UnloadChunkPacket { position }));
+        }
+
+        // Send the closest chunks first
+        let mut new_chunks = wanted_chunks
+            .difference(&loaded_chunks.chunks)
+            .copied()
+            .collect::<Vec<_>>();
+        new_chunks.sort_by_key(|position| position.distance(&center));
+        for position in new_chunks {
+            loaded_chunks.chunks.insert(position);
+
+            let entities = self.entities.clone();
+            let sink = sink.clone();
+            let callback = Box::new(move |column: Arc<ChunkColumn>| {
+                sink(Box::new(move || {
+                    // The player might have moved on while the chunk was loading or waiting to be sent
+                    if has_chunk_loaded(&entities, player_eid, column.position) {
+                        Some(ClientboundPacket::ChunkData(ChunkDataPacket::from_chunk_column(&column)))
+                    } else {
+                        None
+                    }
+                }))
+            });
+            if let Some(column) = self.chunk_provider.request(position, player_eid, callback)? {
+                packets.push(ClientboundPacket::ChunkData(ChunkDataPacket::from_chunk_column(
+                    &column,
+                )));
+            }
+        }
+
+        Ok(packets)
+    }
+}
+
+fn has_chunk_loaded(
+    entities: &RwLock<HashMap<i32, Arc<RwLock<Entity>>>>,
+    player_eid: i32,
+    position: ChunkPosition,
+) -> bool {
+    let entity_arc = match entities.read() {
+        Ok(entities) => match entities.get(&player_eid) {
+            Some(entity_arc) => entity_arc.clone(),
+            None => return false,
+        },
+        Err(_) => return false,
+    };
+    let entity = match entity_arc.read() {
+        Ok(entity) => entity,
+        Err(_) => return false,
+    };
+    match entity.as_player() {
+        Ok(player) => player.loaded_chunks.chunks.contains(&position),
+        Err(_) => false,
+    }
+}
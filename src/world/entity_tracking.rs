@@ -0,0 +1,274 @@
+use super::{ChunkPosition, World};
+
+use crate::error_type::ErrorType;
+use crate::packets::clientbound::*;
+use crate::player::{Look, Position};
+
+use std::convert::TryInto;
+
+impl World {
+    // Works out which players see the player with the given eid after it joined or moved, and
+    // which players it sees itself. `previous` is where the player was before, or None if it just
+    // appeared. Returns the packets to send, each with the eid of the player that should get it.
+    pub fn update_player_tracking(
+        &self,
+        eid: i32,
+        previous: Option<(Position, Look)>,
+        view_distance: i32,
+    ) -> Result<Vec<(i32, ClientboundPacket)>, ErrorType> {
+        let player_arc = self
+            .get_entity(eid)?
+            .ok_or(ErrorType::Fatal("Player does not exist".to_string()))?;
+        let player = player_arc
+            .read()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock player for reading: {}", e)))?
+            .as_player()?
+            .clone();
+
+        let others = self
+            .entities
+            .read()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock entities for reading: {}", e)))?
+            .iter()
+            .filter(|(other_eid, _)| **other_eid != eid)
+            .map(|(other_eid, other_arc)| (*other_eid, other_arc.clone()))
+            .collect::<Vec<_>>();
+
+        let mut packets = vec![];
+        let mut newly_tracked = vec![];
+        let mut newly_untracked = vec![];
+
+        for (other_eid, other_arc) in others {
+            let mut other_entity = other_arc.write().map_err(|e| {
+                ErrorType::Fatal(format!("Could not lock entity for writing: {}", e))
+            })?;
+            let other = match other_entity.as_player_mut() {
+                Ok(other) => other,
+                Err(_) => continue,
+            };
+
+            let in_range = ChunkPosition::from_position(&player.position)
+                .distance(&ChunkPosition::from_position(&other.position))
+                <= view_distance;
+
+            // What the other player sees of this one
+            let other_tracks_player = other.tracked_entities.contains(&eid);
+            if in_range && !other_tracks_player {
+                other.tracked_entities.insert(eid);
+                packets.push((other_eid, ClientboundPacket::SpawnPlayer(
+                    SpawnPlayerPacket::from_player(eid, &player)
+                )));
+                packets.push((other_eid, ClientboundPacket::EntityHeadLook(EntityHeadLookPacket {
+                    entity_id: eid,
+                    head_yaw: player.look.yaw,
+                })));
+            } else if in_range {
+                if let Some((previous_position, previous_look)) = previous.as_ref() {
+                    for packet in movement_packets(
+                        eid,
+                        previous_position,
+                        previous_look,
+                        &player.position,
+                        &player.look,
+                    ) {
+                        packets.push((other_eid, packet));
+                    }
+                }
+            } else if other_tracks_player {
+                other.tracked_entities.remove(&eid);
+                packets.push((other_eid, ClientboundPacket::DestroyEntities(DestroyEntitiesPacket {
+                    entity_ids: vec![eid],
+                })));
+            }
+
+            // What this player sees of the other one
+            let player_tracks_other = player.tracked_entities.contains(&other_eid);
+            if in_range && !player_tracks_other {
+                newly_tracked.push(other_eid);
+                packets.push((eid, ClientboundPacket::SpawnPlayer(
+                    SpawnPlayerPacket::from_player(other_eid, other)
+                )));
+                packets.push((eid, ClientboundPacket::EntityHeadLook(EntityHeadLookPacket {
+                    entity_id: other_eid,
+                    head_yaw: other.look.yaw,
+                })));
+            } else if !in_range && player_tracks_other {
+                newly_untracked.push(other_eid);
+            }
+        }
+
+        if !newly_untracked.is_empty() {
+            packets.push((eid, ClientboundPacket::DestroyEntities(DestroyEntitiesPacket {
+                entity_ids: newly_untracked.clone(),
+            })));
+        }
+
+        let mut player_entity = player_arc
+            .write()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock player for writing: {}", e)))?;
+        let tracked_entities = &mut player_entity.as_player_mut()?.tracked_entities;
+        tracked_entities.extend(newly_tracked);
+        for other_eid in newly_untracked {
+            tracked_entities.remove(&other_eid);
+        }
+
+        Ok(packets)
+    }
+
+    // Makes every player forget about an entity, used when it leaves the world
+    pub fn untrack_entity(&self, eid: i32) -> Result<(), ErrorType> {
+        let entities = self
+            .entities
+            .read()
+            .map_err(|e| ErrorType::Fatal(format!("Could not lock entities for reading: {}", e)))?;
+        for entity_arc in entities.values() {
+            let mut entity = entity_arc.write().map_err(|e| {
+                ErrorType::Fatal(format!("Could not lock entity for writing: {}", e))
+            })?;
+            if let Ok(player) = entity.as_player_mut() {
+                player.tracked_entities.remove(&eid);
+            }
+        }
+        Ok(())
+    }
+}
+
+// Picks the smallest packets that move an entity from one place to another. Small moves are sent
+// as a delta in 1/4096th of a block, moves that do not fit in that are sent as a teleport.
+fn movement_packets(
+    eid: i32,
+    previous_position: &Position,
+    previous_look: &Look,
+    position: &Position,
+    look: &Look,
+) -> Vec<ClientboundPacket> {
+    let delta = |previous: f64, current: f64| -> Option<i16> {
+        ((current * 4096.0).round() as i64 - (previous * 4096.0).round() as i64)
+            .try_into()
+            .ok()
+    };
+    let moved = previous_position.x != position.x
+        || previous_position.y != position.y
+        || previous_position.z != position.z;
+    let rotated = previous_look.yaw != look.yaw || previous_look.pitch != look.pitch;
+
+    let mut packets = vec![];
+    if moved {
+        let deltas = (
+            delta(previous_position.x, position.x),
+            delta(previous_position.y, position.y),
+            delta(previous_position.z, position.z),
+        );
+        packets.push(match deltas {
+            (Some(delta_x), Some(delta_y), Some(delta_z)) if rotated => {
+                ClientboundPacket::EntityPositionAndRotation(EntityPositionAndRotationPacket {
+                    entity_id: eid,
+                    delta_x,
+                    delta_y,
+                    delta_z,
+                    yaw: look.yaw,
+                    pitch: look.pitch,
+                    on_ground: position.on_ground,
+                })
+            }
+            (Some(delta_x), Some(delta_y), Some(delta_z)) => {
+                ClientboundPacket::EntityPosition(EntityPositionPacket {
+                    entity_id: eid,
+                    delta_x,
+                    delta_y,
+                    delta_z,
+                    on_ground: position.on_ground,
+                })
+            }
+            _ => ClientboundPacket::EntityTeleport(EntityTeleportPacket {
+                entity_id: eid,
+                x: position.x,
+                y: position.y,
+                z: position.z,
+                yaw: look.yaw,
+                pitch: look.pitch,
+                on_ground: position.on_ground,
+            }),
+        });
+    } else if rotated {
+        packets.push(ClientboundPacket::EntityRotation(EntityRotationPacket {
+            entity_id: eid,
+            yaw: look.yaw,
+            pitch: look.pitch,
+            on_ground: position.on_ground,
+        }));
+    }
+
+    if rotated {
+        packets.push(ClientboundPacket::EntityHeadLook(EntityHeadLookPacket {
+            entity_id: eid,
+            head_yaw: look.yaw,
+        }));
+    }
+
+    packets
+}
+
+#[cfg(test)]
+mod test {
+    use super::movement_packets;
+    use crate::packets::clientbound::ClientboundPacket;
+    use crate::player::{Gamemode, Look, OPLevel, Permissions, Player, Position};
+    use crate::server::{Dimension, Entity};
+    use crate::world::World;
+
+    use uuid::Uuid;
+
+    fn add_player(world: &World, x: f64) -> i32 {
+        let mut player = Player::new(
+            Uuid::new_v4(),
+            "Alice".to_string(),
+            Gamemode::Creative,
+            Dimension::dummy(),
+            Permissions::new(OPLevel::Player),
+        );
+        player.position.x = x;
+        world.register_entity(Entity::PlayerEntity(player)).unwrap()
+    }
+
+    fn count(packets: &[(i32, ClientboundPacket)], recipient: i32, kind: fn(&ClientboundPacket) -> bool) -> usize {
+        packets.iter().filter(|(eid, packet)| *eid == recipient && kind(packet)).count()
+    }
+
+    #[test]
+    fn test_tracking() {
+        let world = World::dummy(&std::env::temp_dir().join("mcrust-tracking"));
+        let alice = add_player(&world, 0.0);
+        let bob = add_player(&world, 20.0);
+        let spawns = |packet: &ClientboundPacket| matches!(packet, ClientboundPacket::SpawnPlayer(_));
+        let destroys = |packet: &ClientboundPacket| matches!(packet, ClientboundPacket::DestroyEntities(_));
+
+        let packets = world.update_player_tracking(alice, None, 2).unwrap();
+        assert_eq!((count(&packets, alice, spawns), count(&packets, bob, spawns)), (1, 1));
+        // Both already know about each other
+        assert!(world.update_player_tracking(bob, None, 2).unwrap().is_empty());
+
+        world.get_entity(bob).unwrap().unwrap().write().unwrap().as_player_mut().unwrap().position.x = 100.0;
+        let packets = world.update_player_tracking(bob, None, 2).unwrap();
+        assert_eq!((count(&packets, alice, destroys), count(&packets, bob, destroys)), (1, 1));
+    }
+
+    #[test]
+    fn test_movement_packets() {
+        let look = Look { yaw: 0.0, pitch: 0.0 };
+        let from = Position { x: 0.0, y: 64.0, z: 0.0, on_ground: true };
+        let small = Position { x: 1.5, ..from.clone() };
+        let large = Position { x: 10.0, ..from.clone() };
+
+        let packets = movement_packets(1, &from, &look, &small, &look);
+        assert!(matches!(packets[..], [ClientboundPacket::EntityPosition(_)]));
+        let packets = movement_packets(1, &from, &look, &large, &look);
+        assert!(matches!(packets[..], [ClientboundPacket::EntityTeleport(_)]));
+        let turned = Look { yaw: 90.0, pitch: 0.0 };
+        let packets = movement_packets(1, &from, &look, &from, &turned);
+        assert!(matches!(
+            packets[..],
+            [ClientboundPacket::EntityRotation(_), ClientboundPacket::EntityHeadLook(_)]
+        ));
+    }
+}
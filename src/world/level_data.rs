@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Weather, World, WorldTime};
+
+use crate::error_type::ErrorType;
+
+use std::fs;
+
+// The state of a world that is not stored in its chunks
+#[derive(Serialize, Deserialize)]
+pub struct LevelData {
+    pub time: WorldTime,
+    #[serde(default = "Weather::new")]
+    pub weather: Weather,
+}
+
+impl World {
+    pub fn load_level_data(&mut self) -> Result<(), ErrorType> {
+        let path = self.directory.join("level.json");
+        if !path.exists() {
+            return Ok(());
+        }
+        let contents = fs::read_to_string(&path).map_err(|e| {
+            ErrorType::Recoverable(format!("Could not read {}: {}", path.display(), e))
+        })?;
+        let level_data: LevelData = serde_json::from_str(&contents).map_err(|e| {
+            ErrorType::Recoverable(format!("Invalid level data in {}: {}", path.display(), e))
+        })?;
+        self.time = level_data.time;
+        self.weather = level_data.weather;
+        Ok(())
+    }
+
+    pub fn save_level_data(&self) -> Result<(), ErrorType> {
+        fs::create_dir_all(&self.directory).map_err(|e| {
+            ErrorType::Recoverable(format!("Could not create {}: {}", self.directory.display(), e))
+        })?;
+        let path = self.directory.join("level.json");
+        let contents = serde_json::to_string(&LevelData {
+            time: self.time.clone(),
+            weather: self.weather.clone(),
+        })
+        .map_err(|e| ErrorType::Recoverable(format!("Could not serialize level data: {}", e)))?;
+        fs::write(&path, contents).map_err(|e| {
+            ErrorType::Recoverable(format!("Could not write {}: {}", path.display(), e))
+        })
+    }
+}
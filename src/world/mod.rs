@@ -1,18 +1,35 @@
 mod chunk_section;
 mod chunk_column;
+mod chunk_position;
+mod chunk_provider;
+mod chunk_streaming;
 mod difficulty;
+mod entity_tracking;
+mod level_data;
+mod weather;
+mod world_time;
 
 pub use chunk_section::ChunkSection;
 pub use chunk_column::ChunkColumn;
+pub use chunk_position::ChunkPosition;
+pub use chunk_provider::*;
+pub use chunk_streaming::*;
 pub use difficulty::*;
+pub use weather::*;
+pub use world_time::*;
 
 use std::sync::{Arc, RwLock};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use rand::random;
 
 use crate::error_type::ErrorType;
-use crate::server::Entity;
+use crate::player::Player;
+use crate::server::{Biome, BiomeSettings, Entity, PrecipitationType};
+
+// The game rules that can be changed so far, by their vanilla names
+pub const GAME_RULES: [&str; 2] = ["doDaylightCycle", "doWeatherCycle"];
 
 #[derive(Clone)]
 pub struct World {
@@ -25,10 +42,20 @@ pub struct World {
     pub entities: Arc<RwLock<HashMap<i32, Arc<RwLock<Entity>>>>>,
     pub difficulty:  Difficulty,
     pub difficulty_locked: bool,
+    pub chunk_provider: Arc<ChunkProvider>,
+    pub spawn_chunk: ChunkPosition,
+    // How many chunks around the spawn chunk stay loaded
+    pub spawn_chunk_radius: i32,
+    pub time: WorldTime,
+    pub weather: Weather,
+    // Every chunk is generated with this biome
+    pub biome: BiomeSettings,
+    // Where the chunks and level data are saved
+    pub directory: PathBuf,
 }
 
 impl World {
-    pub fn dummy() -> Self {
+    pub fn dummy(data_directory: &Path) -> Self {
         Self {
             name: "overworld".to_string(),
             seed: [0; 32],
@@ -39,23 +66,76 @@ impl World {
             entities: Arc::new(RwLock::new(HashMap::new())),
             difficulty: Difficulty::Easy,
             difficulty_locked: false,
+            chunk_provider: Arc::new(ChunkProvider::new(data_directory.join("chunks"))),
+            // Where new players show up
+            spawn_chunk: ChunkPosition::from_block(10, 20),
+            spawn_chunk_radius: 4,
+            time: WorldTime::new(),
+            weather: Weather::new(),
+            biome: Biome::dummy().settings,
+            directory: data_directory.to_path_buf(),
+        }
+    }
+
+    pub fn load_spawn_chunks(&self) -> Result<(), ErrorType> {
+        for position in self.spawn_chunk.square(self.spawn_chunk_radius) {
+            self.chunk_provider.keep_loaded(position)?;
+        }
+        Ok(())
+    }
+
+    // x, y, z are block coordinates, returns false if the chunk is not loaded
+    pub fn set_block(&self, x: i32, y: i32, z: i32, block: u16) -> Result<bool, ErrorType> {
+        if !(0..256).contains(&y) {
+            return Ok(false);
+        }
+        self.chunk_provider.modify(ChunkPosition::from_block(x, z), |column| {
+            column.set_block(x.rem_euclid(16) as usize, y as usize, z.rem_euclid(16) as usize, block)
+        })
+    }
+
+    // x, y, z are block coordinates, everything above or below the world and in chunks that are
+    // not loaded is air
+    pub fn get_block(&self, x: i32, y: i32, z: i32) -> u16 {
+        if !(0..256).contains(&y) {
+            return 0;
+        }
+        match self.chunk_provider.get_loaded(ChunkPosition::from_block(x, z)) {
+            Ok(Some(column)) => column.get_block(
+                x.rem_euclid(16) as usize,
+                y as usize,
+                z.rem_euclid(16) as usize,
+            ),
+            _ => 0,
         }
     }
 
-    // x, y, z are chunk indices (= position // 16)
-    pub fn get_chunk_section(&self, _x: isize, _y: isize, _z: isize) -> ChunkSection {
-        // TODO: actually implement this
-        let chunk = [0u16; 4096];
+    // TODO: there are more blocks you can walk through than just air
+    pub fn is_solid_block(&self, x: i32, y: i32, z: i32) -> bool {
+        self.get_block(x, y, z) != 0
+    }
 
-        return ChunkSection::from(chunk);
+    // Whether it rains or snows at height y, or neither
+    pub fn precipitation_at(&self, y: i32) -> PrecipitationType {
+        self.weather.precipitation(&self.biome, y)
     }
 
-    pub fn get_chunk_column(&self, x: isize, z: isize) -> ChunkColumn {
-        let mut ret = vec![];
-        for y in 0..16 {
-            ret.push(self.get_chunk_section(x, y, z));
+    pub fn game_rule(&self, name: &str) -> Option<bool> {
+        match name {
+            "doDaylightCycle" => Some(self.time.do_daylight_cycle),
+            "doWeatherCycle" => Some(self.weather.do_weather_cycle),
+            _ => None,
         }
-        return ret.into();
+    }
+
+    // Returns false if there is no such game rule
+    pub fn set_game_rule(&mut self, name: &str, value: bool) -> bool {
+        match name {
+            "doDaylightCycle" => self.time.do_daylight_cycle = value,
+            "doWeatherCycle" => self.weather.do_weather_cycle = value,
+            _ => return false,
+        }
+        true
     }
 
     pub fn get_entity(&self, eid: i32) -> Result<Option<Arc<RwLock<Entity>>>, ErrorType> {
@@ -99,4 +179,71 @@ impl World {
         Ok(eid)
     }
 
+    // Copies of all players in this world
+    pub fn get_players(&self) -> Result<Vec<Player>, ErrorType> {
+        let entities = self.entities.read().map_err(|e| {
+            ErrorType::Fatal(format!("Could not lock entities for reading: {}", e))
+        })?;
+        let mut players = vec![];
+        for entity_arc in entities.values() {
+            let entity = entity_arc.read().map_err(|e| {
+                ErrorType::Fatal(format!("Could not lock entity for reading: {}", e))
+            })?;
+            if let Ok(player) = entity.as_player() {
+                players.push(player.clone());
+            }
+        }
+        Ok(players)
+    }
+
+    pub fn player_eids(&self) -> Result<Vec<i32>, ErrorType> {
+        let entities = self.entities.read().map_err(|e| {
+            ErrorType::Fatal(format!("Could not lock entities for reading: {}", e))
+        })?;
+        let mut eids = vec![];
+        for (eid, entity_arc) in entities.iter() {
+            let entity = entity_arc.read().map_err(|e| {
+                ErrorType::Fatal(format!("Could not lock entity for reading: {}", e))
+            })?;
+            if entity.as_player().is_ok() {
+                eids.push(*eid);
+            }
+        }
+        Ok(eids)
+    }
+
+    pub fn remove_entity(&self, eid: i32) -> Result<Option<Arc<RwLock<Entity>>>, ErrorType> {
+        Ok(self
+            .entities
+            .write()
+            .map_err(|e| {
+                ErrorType::Fatal(format!(
+                    "Could not lock entities for writing: {}",
+                    e
+                ))
+            })?
+            .remove(&eid))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{World, GAME_RULES};
+
+    #[test]
+    fn test_game_rules() {
+        let mut world = World::dummy(&std::env::temp_dir().join("mcrust-game-rules"));
+        for name in GAME_RULES {
+            assert_eq!(world.game_rule(name), Some(true));
+        }
+        assert!(world.set_game_rule("doDaylightCycle", false));
+        assert!(!world.set_game_rule("keepInventory", true));
+        assert_eq!(world.game_rule("keepInventory"), None);
+
+        // The sun stands still, but the world keeps ageing
+        world.time.set_time_of_day(1000);
+        world.time.tick();
+        assert_eq!((world.time.world_age, world.time.time_of_day), (1, 1000));
+        assert!(world.time.time_update_packet().time_of_day < 0);
+    }
 }
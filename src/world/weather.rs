@@ -0,0 +1,243 @@
+use rand::{thread_rng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::packets::clientbound::{ChangeGameStatePacket, ClientboundPacket, GameStateChange};
+use crate::server::{BiomeSettings, PrecipitationType};
+
+// How many ticks each kind of weather lasts, these are the ranges the vanilla server uses
+const CLEAR_DURATION: (i32, i32) = (12000, 180000);
+const RAIN_DURATION: (i32, i32) = (12000, 24000);
+const THUNDER_DURATION: (i32, i32) = (3600, 15600);
+const NO_THUNDER_DURATION: (i32, i32) = (12000, 180000);
+
+// How fast rain and thunder fade in and out each tick
+const LEVEL_CHANGE: f32 = 0.01;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum WeatherType {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Weather {
+    pub raining: bool,
+    // Ticks until raining flips, 0 means a new duration still has to be picked
+    pub rain_time: i32,
+    pub thundering: bool,
+    pub thunder_time: i32,
+    // Set by commands, keeps the weather clear for this many ticks
+    pub clear_weather_time: i32,
+    pub do_weather_cycle: bool,
+    pub rain_level: f32,
+    pub thunder_level: f32,
+}
+
+impl Weather {
+    pub fn new() -> Self {
+        Self {
+            raining: false,
+            rain_time: 0,
+            thundering: false,
+            thunder_time: 0,
+            clear_weather_time: 0,
+            do_weather_cycle: true,
+            rain_level: 0.0,
+            thunder_level: 0.0,
+        }
+    }
+
+    // Like the client, only count it as rain once it is raining hard enough
+    pub fn is_raining(&self) -> bool {
+        self.rain_level > 0.2
+    }
+
+    // Thunder only counts while it rains, like the client only shows it then
+    pub fn current(&self) -> WeatherType {
+        if self.raining && self.thundering {
+            WeatherType::Thunder
+        } else if self.raining {
+            WeatherType::Rain
+        } else {
+            WeatherType::Clear
+        }
+    }
+
+    // Changes the weather right away, duration is in ticks or None for a random one
+    pub fn set(&mut self, weather: WeatherType, duration: Option<i32>) {
+        match weather {
+            WeatherType::Clear => {
+                self.clear_weather_time = duration.unwrap_or_else(|| random_duration(CLEAR_DURATION));
+                self.rain_time = 0;
+                self.thunder_time = 0;
+                self.raining = false;
+                self.thundering = false;
+            }
+            WeatherType::Rain | WeatherType::Thunder => {
+                let duration = duration.unwrap_or_else(|| random_duration(RAIN_DURATION));
+                self.clear_weather_time = 0;
+                self.rain_time = duration;
+                self.thunder_time = duration;
+                self.raining = true;
+                self.thundering = weather == WeatherType::Thunder;
+            }
+        }
+    }
+
+    // Advances the weather by one tick, returns the changes that have to be sent to the players
+    pub fn tick(&mut self) -> Vec<ClientboundPacket> {
+        let was_raining = self.is_raining();
+
+        if self.do_weather_cycle {
+            if self.clear_weather_time > 0 {
+                self.clear_weather_time -= 1;
+                self.thunder_time = if self.thundering { 0 } else { 1 };
+                self.rain_time = if self.raining { 0 } else { 1 };
+                self.thundering = false;
+                self.raining = false;
+            } else {
+                if self.thunder_time > 0 {
+                    self.thunder_time -= 1;
+                    if self.thunder_time == 0 {
+                        self.thundering = !self.thundering;
+                    }
+                } else if self.thundering {
+                    self.thunder_time = random_duration(THUNDER_DURATION);
+                } else {
+                    self.thunder_time = random_duration(NO_THUNDER_DURATION);
+                }
+
+                if self.rain_time > 0 {
+                    self.rain_time -= 1;
+                    if self.rain_time == 0 {
+                        self.raining = !self.raining;
+                    }
+                } else if self.raining {
+                    self.rain_time = random_duration(RAIN_DURATION);
+                } else {
+                    self.rain_time = random_duration(CLEAR_DURATION);
+                }
+            }
+        }
+
+        let mut packets = vec![];
+
+        let previous_thunder_level = self.thunder_level;
+        self.thunder_level = fade(self.thunder_level, self.thundering);
+        if self.thunder_level != previous_thunder_level {
+            packets.push(change_packet(GameStateChange::ThunderLevelChange(self.thunder_level)));
+        }
+
+        let previous_rain_level = self.rain_level;
+        self.rain_level = fade(self.rain_level, self.raining);
+        if self.rain_level != previous_rain_level {
+            packets.push(change_packet(GameStateChange::RainLevelChange(self.rain_level)));
+        }
+
+        if was_raining != self.is_raining() {
+            packets.push(change_packet(if self.is_raining() {
+                GameStateChange::BeginRaining
+            } else {
+                GameStateChange::EndRaining
+            }));
+        }
+
+        packets
+    }
+
+    // What a player that just joined needs to know
+    pub fn join_packets(&self) -> Vec<ClientboundPacket> {
+        if !self.is_raining() {
+            return vec![];
+        }
+        vec![
+            change_packet(GameStateChange::BeginRaining),
+            change_packet(GameStateChange::RainLevelChange(self.rain_level)),
+            change_packet(GameStateChange::ThunderLevelChange(self.thunder_level)),
+        ]
+    }
+
+    // What falls down at height y in the given biome with the weather as it is set
+    pub fn precipitation(&self, biome: &BiomeSettings, y: i32) -> PrecipitationType {
+        match self.current() {
+            WeatherType::Clear => PrecipitationType::None,
+            WeatherType::Rain | WeatherType::Thunder => biome.precipitation_at(y),
+        }
+    }
+
+}
+
+fn fade(level: f32, active: bool) -> f32 {
+    let change = if active { LEVEL_CHANGE } else { -LEVEL_CHANGE };
+    (level + change).clamp(0.0, 1.0)
+}
+
+fn random_duration((min, max): (i32, i32)) -> i32 {
+    thread_rng().gen_range(min..max)
+}
+
+fn change_packet(change: GameStateChange) -> ClientboundPacket {
+    ClientboundPacket::ChangeGameState(ChangeGameStatePacket { change })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Weather, WeatherType};
+    use crate::packets::clientbound::{ClientboundPacket, GameStateChange};
+    use crate::server::{Biome, PrecipitationType};
+
+    fn ticks(weather: &mut Weather, count: usize) -> Vec<GameStateChange> {
+        (0..count)
+            .flat_map(|_| weather.tick())
+            .filter_map(|packet| match packet {
+                ClientboundPacket::ChangeGameState(packet) => Some(packet.change),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_weather() {
+        let mut weather = Weather::new();
+        assert_eq!(weather.current(), WeatherType::Clear);
+
+        weather.set(WeatherType::Rain, Some(1000));
+        assert_eq!(weather.current(), WeatherType::Rain);
+        let changes = ticks(&mut weather, 30);
+        assert_eq!(changes.iter().filter(|change| matches!(change, GameStateChange::BeginRaining)).count(), 1);
+        assert!(weather.is_raining());
+
+        weather.set(WeatherType::Thunder, Some(1000));
+        assert_eq!(weather.current(), WeatherType::Thunder);
+        // Thunder without rain is just clear weather
+        weather.raining = false;
+        assert_eq!(weather.current(), WeatherType::Clear);
+
+        weather.set(WeatherType::Clear, Some(1000));
+        let changes = ticks(&mut weather, 30);
+        assert!(changes.iter().any(|change| matches!(change, GameStateChange::EndRaining)));
+        assert!(!weather.is_raining());
+        assert!(weather.join_packets().is_empty());
+    }
+
+    #[test]
+    fn test_precipitation() {
+        let mut biome = Biome::dummy().settings;
+        let mut weather = Weather::new();
+        assert_eq!(weather.precipitation(&biome, 64), PrecipitationType::None);
+
+        weather.set(WeatherType::Rain, None);
+        assert_eq!(weather.precipitation(&biome, 64), PrecipitationType::Rain);
+        assert_eq!(weather.precipitation(&biome, 255), PrecipitationType::Rain);
+
+        // Like in the mountains, it is just warm enough for rain at sea level
+        biome.temperature = 0.3;
+        assert_eq!(weather.precipitation(&biome, 64), PrecipitationType::Rain);
+        assert_eq!(weather.precipitation(&biome, 200), PrecipitationType::Snow);
+        biome.temperature = 0.0;
+        assert_eq!(weather.precipitation(&biome, 64), PrecipitationType::Snow);
+        biome.precipitation = PrecipitationType::None;
+        assert_eq!(weather.precipitation(&biome, 64), PrecipitationType::None);
+    }
+}
@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+
+use crate::packets::clientbound::TimeUpdatePacket;
+
+pub const TICKS_PER_DAY: i64 = 24000;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorldTime {
+    // Ticks since the world was created, this never goes back
+    pub world_age: i64,
+    // 0 is sunrise, 6000 noon, 12000 sunset and 18000 midnight, it keeps counting up across days
+    pub time_of_day: i64,
+    pub do_daylight_cycle: bool,
+}
+
+impl WorldTime {
+    pub fn new() -> Self {
+        Self {
+            world_age: 0,
+            time_of_day: 0,
+            do_daylight_cycle: true,
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.world_age += 1;
+        if self.do_daylight_cycle {
+            self.time_of_day += 1;
+        }
+    }
+
+    pub fn set_time_of_day(&mut self, time_of_day: i64) {
+        self.time_of_day = time_of_day;
+    }
+
+    pub fn add_time(&mut self, ticks: i64) {
+        self.time_of_day += ticks;
+    }
+
+    // The time within the current day
+    pub fn day_time(&self) -> i64 {
+        self.time_of_day.rem_euclid(TICKS_PER_DAY)
+    }
+
+    pub fn day(&self) -> i64 {
+        self.time_of_day.div_euclid(TICKS_PER_DAY)
+    }
+
+    pub fn time_update_packet(&self) -> TimeUpdatePacket {
+        // The client keeps the sun still when the time is negative, -0 does not exist so use -1
+        let time_of_day = if self.do_daylight_cycle {
+            self.time_of_day
+        } else if self.time_of_day == 0 {
+            -1
+        } else {
+            -self.time_of_day
+        };
+        TimeUpdatePacket {
+            world_age: self.world_age,
+            time_of_day,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::WorldTime;
+
+    #[test]
+    fn test_world_time() {
+        let mut time = WorldTime::new();
+        time.set_time_of_day(23999);
+        time.tick();
+        assert_eq!((time.world_age, time.day_time(), time.day()), (1, 0, 1));
+
+        time.add_time(-30000);
+        assert_eq!((time.day_time(), time.day()), (18000, -1));
+
+        time.do_daylight_cycle = false;
+        time.set_time_of_day(6000);
+        time.tick();
+        assert_eq!((time.world_age, time.time_of_day), (2, 6000));
+        assert_eq!(time.time_update_packet().time_of_day, -6000);
+        time.set_time_of_day(0);
+        assert_eq!(time.time_update_packet().time_of_day, -1);
+    }
+}